@@ -0,0 +1,323 @@
+#!/usr/bin/env python3
+"""Stand-in for a signer following the HWI command line protocol, used by the
+tests of the external signer. It knows a single unlocked device holding the
+master key of the BIP32 test vector 1, and signs with it the inputs of the psbt
+whose BIP32 derivations start from its fingerprint. Only the standard library
+is used, keys are never meant to hold funds."""
+
+import base64
+import hashlib
+import hmac
+import json
+import sys
+
+FINGERPRINT = "3442193e"
+XPRV = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+XPUB = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw"
+
+SIGHASH_ALL = 0x01
+SIGHASH_ANYONECANPAY = 0x80
+
+PSBT_IN_WITNESS_UTXO = 0x01
+PSBT_IN_PARTIAL_SIG = 0x02
+PSBT_IN_SIGHASH_TYPE = 0x03
+PSBT_IN_WITNESS_SCRIPT = 0x05
+PSBT_IN_BIP32_DERIVATION = 0x06
+
+# secp256k1
+P = 2**256 - 2**32 - 977
+N = 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141
+G = (
+    0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798,
+    0x483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8,
+)
+
+
+class SignerError(Exception):
+    pass
+
+
+def point_add(a, b):
+    if a is None:
+        return b
+    if b is None:
+        return a
+    if a[0] == b[0] and (a[1] + b[1]) % P == 0:
+        return None
+    if a == b:
+        lam = 3 * a[0] * a[0] * pow(2 * a[1], P - 2, P) % P
+    else:
+        lam = (b[1] - a[1]) * pow(b[0] - a[0], P - 2, P) % P
+    x = (lam * lam - a[0] - b[0]) % P
+    return (x, (lam * (a[0] - x) - a[1]) % P)
+
+
+def point_mul(k, point=G):
+    result = None
+    while k:
+        if k & 1:
+            result = point_add(result, point)
+        point = point_add(point, point)
+        k >>= 1
+    return result
+
+
+def serialize_pubkey(point):
+    return bytes([2 + (point[1] & 1)]) + point[0].to_bytes(32, "big")
+
+
+def dsha256(data):
+    return hashlib.sha256(hashlib.sha256(data).digest()).digest()
+
+
+def base58check_decode(text):
+    alphabet = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+    num = 0
+    for c in text:
+        num = num * 58 + alphabet.index(c)
+    data = num.to_bytes((num.bit_length() + 7) // 8, "big")
+    data = b"\x00" * (len(text) - len(text.lstrip("1"))) + data
+    payload, checksum = data[:-4], data[-4:]
+    if dsha256(payload)[:4] != checksum:
+        raise SignerError("Invalid checksum")
+    return payload
+
+
+def master_key():
+    payload = base58check_decode(XPRV)
+    return int.from_bytes(payload[46:78], "big"), payload[13:45]
+
+
+def derive(key, chain_code, path):
+    """derive returns the private key at the given path of child numbers."""
+    for index in path:
+        if index >= 0x80000000:
+            data = b"\x00" + key.to_bytes(32, "big")
+        else:
+            data = serialize_pubkey(point_mul(key))
+        digest = hmac.new(chain_code, data + index.to_bytes(4, "big"), hashlib.sha512).digest()
+        key = (int.from_bytes(digest[:32], "big") + key) % N
+        chain_code = digest[32:]
+    return key
+
+
+def nonce(key, digest):
+    """nonce returns the deterministic nonce of RFC 6979."""
+    x = key.to_bytes(32, "big")
+    v = b"\x01" * 32
+    k = b"\x00" * 32
+    k = hmac.new(k, v + b"\x00" + x + digest, hashlib.sha256).digest()
+    v = hmac.new(k, v, hashlib.sha256).digest()
+    k = hmac.new(k, v + b"\x01" + x + digest, hashlib.sha256).digest()
+    v = hmac.new(k, v, hashlib.sha256).digest()
+    while True:
+        v = hmac.new(k, v, hashlib.sha256).digest()
+        candidate = int.from_bytes(v, "big")
+        if 1 <= candidate < N:
+            return candidate
+        k = hmac.new(k, v + b"\x00", hashlib.sha256).digest()
+        v = hmac.new(k, v, hashlib.sha256).digest()
+
+
+def der_integer(value):
+    data = value.to_bytes((value.bit_length() + 8) // 8, "big")
+    return b"\x02" + bytes([len(data)]) + data
+
+
+def sign_digest(key, digest):
+    k = nonce(key, digest)
+    r = point_mul(k)[0] % N
+    s = pow(k, N - 2, N) * (int.from_bytes(digest, "big") + r * key) % N
+    # Low S signatures only are standard.
+    s = min(s, N - s)
+    body = der_integer(r) + der_integer(s)
+    return b"\x30" + bytes([len(body)]) + body
+
+
+class Reader:
+    def __init__(self, data):
+        self.data = data
+        self.pos = 0
+
+    def read(self, n):
+        if self.pos + n > len(self.data):
+            raise SignerError("Unexpected end of data")
+        chunk = self.data[self.pos : self.pos + n]
+        self.pos += n
+        return chunk
+
+    def varint(self):
+        first = self.read(1)[0]
+        if first < 0xFD:
+            return first
+        size = {0xFD: 2, 0xFE: 4, 0xFF: 8}[first]
+        return int.from_bytes(self.read(size), "little")
+
+    def varbytes(self):
+        return self.read(self.varint())
+
+
+def varint(n):
+    if n < 0xFD:
+        return bytes([n])
+    if n <= 0xFFFF:
+        return b"\xfd" + n.to_bytes(2, "little")
+    if n <= 0xFFFFFFFF:
+        return b"\xfe" + n.to_bytes(4, "little")
+    return b"\xff" + n.to_bytes(8, "little")
+
+
+def varbytes(data):
+    return varint(len(data)) + data
+
+
+def parse_tx(data):
+    reader = Reader(data)
+    tx = {"version": reader.read(4), "inputs": [], "outputs": []}
+    for _ in range(reader.varint()):
+        outpoint = reader.read(36)
+        reader.varbytes()
+        tx["inputs"].append((outpoint, reader.read(4)))
+    for _ in range(reader.varint()):
+        value = reader.read(8)
+        tx["outputs"].append(value + varbytes(reader.varbytes()))
+    tx["locktime"] = reader.read(4)
+    return tx
+
+
+def parse_map(reader):
+    entries = []
+    while True:
+        key = reader.varbytes()
+        if not key:
+            return entries
+        entries.append((key, reader.varbytes()))
+
+
+def serialize_map(entries):
+    return b"".join(varbytes(key) + varbytes(value) for key, value in entries) + b"\x00"
+
+
+def sighash(tx, index, script_code, amount, sighash_type):
+    """sighash returns the BIP143 signature hash of the input."""
+    if sighash_type not in (SIGHASH_ALL, SIGHASH_ALL | SIGHASH_ANYONECANPAY):
+        raise SignerError("Unsupported sighash type")
+    zero = b"\x00" * 32
+    if sighash_type & SIGHASH_ANYONECANPAY:
+        hash_prevouts = zero
+        hash_sequence = zero
+    else:
+        hash_prevouts = dsha256(b"".join(outpoint for outpoint, _ in tx["inputs"]))
+        hash_sequence = dsha256(b"".join(sequence for _, sequence in tx["inputs"]))
+    outpoint, sequence = tx["inputs"][index]
+    preimage = (
+        tx["version"]
+        + hash_prevouts
+        + hash_sequence
+        + outpoint
+        + varbytes(script_code)
+        + amount
+        + sequence
+        + dsha256(b"".join(tx["outputs"]))
+        + tx["locktime"]
+        + sighash_type.to_bytes(4, "little")
+    )
+    return dsha256(preimage)
+
+
+def sign_psbt(encoded):
+    try:
+        data = base64.b64decode(encoded, validate=True)
+    except ValueError:
+        raise SignerError("Invalid psbt encoding")
+    if data[:5] != b"psbt\xff":
+        raise SignerError("Invalid psbt magic")
+    reader = Reader(data[5:])
+    global_map = parse_map(reader)
+    unsigned_tx = dict(global_map).get(b"\x00")
+    if unsigned_tx is None:
+        raise SignerError("Missing unsigned transaction")
+    tx = parse_tx(unsigned_tx)
+    inputs = [parse_map(reader) for _ in tx["inputs"]]
+    outputs = [parse_map(reader) for _ in tx["outputs"]]
+
+    master, chain_code = master_key()
+    fingerprint = bytes.fromhex(FINGERPRINT)
+    for index, entries in enumerate(inputs):
+        fields = {}
+        for key, value in entries:
+            fields.setdefault(key[0], []).append((key[1:], value))
+        derivations = [
+            (pubkey, value)
+            for pubkey, value in fields.get(PSBT_IN_BIP32_DERIVATION, [])
+            if value[:4] == fingerprint
+        ]
+        if not derivations:
+            continue
+        if PSBT_IN_WITNESS_UTXO not in fields or PSBT_IN_WITNESS_SCRIPT not in fields:
+            raise SignerError("Only segwit inputs with a witness script are supported")
+        amount = fields[PSBT_IN_WITNESS_UTXO][0][1][:8]
+        script_code = fields[PSBT_IN_WITNESS_SCRIPT][0][1]
+        sighash_type = SIGHASH_ALL
+        if PSBT_IN_SIGHASH_TYPE in fields:
+            sighash_type = int.from_bytes(fields[PSBT_IN_SIGHASH_TYPE][0][1], "little")
+        digest = sighash(tx, index, script_code, amount, sighash_type)
+        for pubkey, value in derivations:
+            path = [int.from_bytes(value[i : i + 4], "little") for i in range(4, len(value), 4)]
+            key = derive(master, chain_code, path)
+            if serialize_pubkey(point_mul(key)) != pubkey:
+                continue
+            sig_key = bytes([PSBT_IN_PARTIAL_SIG]) + pubkey
+            entries[:] = [(k, v) for k, v in entries if k != sig_key]
+            entries.append((sig_key, sign_digest(key, digest) + bytes([sighash_type])))
+
+    signed = b"psbt\xff" + serialize_map(global_map)
+    signed += b"".join(serialize_map(entries) for entries in inputs + outputs)
+    return base64.b64encode(signed).decode()
+
+
+def main(args):
+    fingerprint = ""
+    chain = ""
+    command = ""
+    psbt = ""
+    args = list(args)
+    while args:
+        arg = args.pop(0)
+        if arg.startswith("--fingerprint="):
+            fingerprint = arg[len("--fingerprint=") :]
+        elif arg == "--chain":
+            chain = args.pop(0) if args else ""
+        elif arg == "--stdin":
+            words = sys.stdin.readline().split()
+            command = words[0] if words else ""
+            psbt = words[1] if len(words) > 1 else ""
+        else:
+            command = arg
+
+    if chain != "test":
+        return {"error": "Unsupported chain"}
+
+    if command == "enumerate":
+        return [
+            {"type": "stand-in", "model": "stand-in", "fingerprint": FINGERPRINT},
+            {"type": "stand-in", "model": "stand-in", "error": "Device is locked"},
+        ]
+    if command not in ("getdescriptors", "signtx"):
+        print("Unknown command: {}".format(command), file=sys.stderr)
+        sys.exit(1)
+    if fingerprint != FINGERPRINT:
+        return {"error": "Device not found"}
+    if command == "getdescriptors":
+        return {
+            "receive": ["wpkh([{}/0h]{}/0/*)".format(FINGERPRINT, XPUB)],
+            "internal": ["wpkh([{}/0h]{}/1/*)".format(FINGERPRINT, XPUB)],
+        }
+    try:
+        return {"psbt": sign_psbt(psbt)}
+    except SignerError as e:
+        return {"error": str(e)}
+
+
+if __name__ == "__main__":
+    print(json.dumps(main(sys.argv[1:])))
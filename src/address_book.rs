@@ -0,0 +1,271 @@
+use std::str::FromStr;
+
+use bitcoin::Network;
+use serde::{Deserialize, Serialize};
+
+/// Name of the address book file in the GUI data directory.
+pub const FILE_NAME: &str = "address_book.json";
+
+/// Maximum number of characters two addresses may differ by to be reported as similar.
+const SIMILARITY_DISTANCE: usize = 4;
+
+/// Number of characters at both ends that addresses crafted to look like
+/// another one usually have in common.
+const SIMILARITY_AFFIXES: usize = 4;
+
+/// Entry is a labelled recipient of the address book.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub label: String,
+    pub address: String,
+    #[serde(with = "bitcoin_network")]
+    pub network: Network,
+    #[serde(default)]
+    pub notes: String,
+    /// The counterparty is trusted to receive funds.
+    #[serde(default)]
+    pub whitelisted: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddressBook {
+    pub entries: Vec<Entry>,
+}
+
+/// Knowledge is what the address book knows about an address.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Knowledge<'a> {
+    Known(&'a Entry),
+    /// The address is not in the address book but looks like a known address.
+    Similar(&'a Entry),
+    New,
+}
+
+impl AddressBook {
+    /// insert adds the entry or replaces the entry with the same address and network.
+    pub fn insert(&mut self, entry: Entry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.address == entry.address && e.network == entry.network)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn remove(&mut self, address: &str, network: Network) {
+        self.entries
+            .retain(|e| !(e.address == address && e.network == network));
+    }
+
+    pub fn knowledge(&self, address: &str, network: Network) -> Knowledge {
+        let entries = self.entries.iter().filter(|e| e.network == network);
+        let mut similar = None;
+        for entry in entries {
+            if entry.address == address {
+                return Knowledge::Known(entry);
+            }
+            if similar.is_none() && is_similar(&entry.address, address) {
+                similar = Some(entry);
+            }
+        }
+        similar.map(Knowledge::Similar).unwrap_or(Knowledge::New)
+    }
+
+    /// search returns the entries of the network whose label or address
+    /// contains the query, ignoring case.
+    pub fn search(&self, query: &str, network: Network) -> Vec<&Entry> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.entries
+            .iter()
+            .filter(|e| {
+                e.network == network
+                    && (e.label.to_lowercase().contains(&query)
+                        || e.address.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+}
+
+/// is_similar returns true if the addresses differ by a few characters or share
+/// their first and last characters, as addresses crafted to be mistaken for another do.
+fn is_similar(a: &str, b: &str) -> bool {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    if a == b {
+        return false;
+    }
+    // get does not panic on the addresses that are not ascii.
+    let prefix = |s: &str| s.get(..SIMILARITY_AFFIXES).map(str::to_string);
+    let suffix = |s: &str| {
+        s.get(s.len().saturating_sub(SIMILARITY_AFFIXES)..)
+            .map(str::to_string)
+    };
+    if a.len() > 2 * SIMILARITY_AFFIXES
+        && b.len() > 2 * SIMILARITY_AFFIXES
+        && prefix(&a).is_some()
+        && prefix(&a) == prefix(&b)
+        && suffix(&a).is_some()
+        && suffix(&a) == suffix(&b)
+    {
+        return true;
+    }
+    edit_distance(&a, &b) <= SIMILARITY_DISTANCE
+}
+
+/// edit_distance is the Levenshtein distance between the two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+mod bitcoin_network {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(network: &Network, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&network.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Network, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Network::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "tb1qfufcrdyarcg5eph608c6l8vktrc9re6agu4se2";
+
+    fn entry(label: &str, address: &str, network: Network) -> Entry {
+        Entry {
+            label: label.to_string(),
+            address: address.to_string(),
+            network,
+            notes: String::new(),
+            whitelisted: false,
+        }
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut book = AddressBook::default();
+        book.insert(entry("Alice", ADDRESS, Network::Testnet));
+        book.insert(entry("Alice", ADDRESS, Network::Regtest));
+        book.insert(entry("Bob", ADDRESS, Network::Testnet));
+        assert_eq!(book.entries.len(), 2);
+        assert_eq!(book.entries[0].label, "Bob");
+
+        book.remove(ADDRESS, Network::Testnet);
+        assert_eq!(
+            book.entries,
+            vec![entry("Alice", ADDRESS, Network::Regtest)]
+        );
+    }
+
+    #[test]
+    fn knowledge() {
+        let mut book = AddressBook::default();
+        book.insert(entry("Alice", ADDRESS, Network::Testnet));
+        let alice = &book.entries[0];
+
+        assert_eq!(
+            book.knowledge(ADDRESS, Network::Testnet),
+            Knowledge::Known(alice)
+        );
+        assert_eq!(book.knowledge(ADDRESS, Network::Regtest), Knowledge::New);
+
+        // Same first and last characters.
+        let lookalike = format!("tb1q{}4se2", "x".repeat(34));
+        assert_eq!(
+            book.knowledge(&lookalike, Network::Testnet),
+            Knowledge::Similar(alice)
+        );
+        let typo = ADDRESS.replace("fufc", "fufd");
+        assert_eq!(
+            book.knowledge(&typo, Network::Testnet),
+            Knowledge::Similar(alice)
+        );
+        assert_eq!(
+            book.knowledge(
+                "tb1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+                Network::Testnet
+            ),
+            Knowledge::New
+        );
+    }
+
+    #[test]
+    fn similarity() {
+        assert!(!is_similar(ADDRESS, ADDRESS));
+        assert!(is_similar(
+            ADDRESS,
+            &ADDRESS.to_uppercase().replace("GU4", "GU5")
+        ));
+        assert!(!is_similar("é".repeat(10).as_str(), ADDRESS));
+        assert!(is_similar("aéééééééééb", "aéééééééééb\u{301}"));
+
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn search() {
+        let mut book = AddressBook::default();
+        book.insert(entry("Alice", ADDRESS, Network::Testnet));
+        book.insert(entry("Bob", "tb1qbob", Network::Testnet));
+        book.insert(entry("Alice regtest", ADDRESS, Network::Regtest));
+
+        let labels = |query: &str| -> Vec<String> {
+            book.search(query, Network::Testnet)
+                .into_iter()
+                .map(|e| e.label.clone())
+                .collect()
+        };
+        assert_eq!(labels(" alice "), vec!["Alice"]);
+        assert_eq!(labels("TB1Q"), vec!["Alice", "Bob"]);
+        assert!(labels("").is_empty());
+        assert!(labels("carol").is_empty());
+    }
+
+    #[test]
+    fn serialization() {
+        let mut book = AddressBook::default();
+        book.insert(entry("Alice", ADDRESS, Network::Testnet));
+        let json = serde_json::to_string(&book).unwrap();
+        assert!(json.contains("\"network\":\"testnet\""));
+        let read: AddressBook = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.entries, book.entries);
+
+        let entry: Entry =
+            serde_json::from_str(r#"{"label":"A","address":"B","network":"bitcoin"}"#).unwrap();
+        assert_eq!(entry.network, Network::Bitcoin);
+        assert!(!entry.whitelisted);
+        assert!(serde_json::from_str::<Entry>(
+            r#"{"label":"A","address":"B","network":"unknown"}"#
+        )
+        .is_err());
+    }
+}
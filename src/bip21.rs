@@ -0,0 +1,233 @@
+use std::str::FromStr;
+
+use bitcoin::{Address, Amount, Denomination};
+
+const SCHEME: &str = "bitcoin:";
+
+/// Uri is a BIP21 payment request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uri {
+    pub address: Address,
+    /// Amount in satoshis.
+    pub amount: Option<u64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The text does not start with the bitcoin scheme.
+    NotBip21,
+    InvalidAddress,
+    InvalidAmount,
+    InvalidEncoding,
+    /// The payment request requires a parameter this wallet does not understand.
+    UnknownRequiredParameter(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::NotBip21 => write!(f, "Payment URI must start with bitcoin:"),
+            Self::InvalidAddress => write!(f, "Payment URI has an invalid address"),
+            Self::InvalidAmount => write!(f, "Payment URI has an invalid amount"),
+            Self::InvalidEncoding => write!(f, "Payment URI has an invalid encoding"),
+            Self::UnknownRequiredParameter(p) => {
+                write!(f, "Payment URI requires the unsupported parameter {}", p)
+            }
+        }
+    }
+}
+
+impl Uri {
+    pub fn new(address: Address) -> Self {
+        Self {
+            address,
+            amount: None,
+            label: None,
+            message: None,
+        }
+    }
+}
+
+impl FromStr for Uri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.get(..SCHEME.len()) {
+            Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => {}
+            _ => return Err(Error::NotBip21),
+        }
+        let mut parts = s[SCHEME.len()..].splitn(2, '?');
+        let address = parts
+            .next()
+            .and_then(|address| Address::from_str(address).ok())
+            .ok_or(Error::InvalidAddress)?;
+
+        let mut uri = Uri::new(address);
+        if let Some(query) = parts.next() {
+            for param in query.split('&').filter(|p| !p.is_empty()) {
+                let mut kv = param.splitn(2, '=');
+                let key = kv.next().unwrap_or_default();
+                let value = percent_decode(kv.next().unwrap_or_default())?;
+                match key {
+                    "amount" => {
+                        uri.amount = Some(
+                            Amount::from_str_in(&value, Denomination::Bitcoin)
+                                .map_err(|_| Error::InvalidAmount)?
+                                .as_sat(),
+                        )
+                    }
+                    "label" => uri.label = Some(value),
+                    "message" => uri.message = Some(value),
+                    key if key.starts_with("req-") => {
+                        return Err(Error::UnknownRequiredParameter(key.to_string()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(uri)
+    }
+}
+
+impl std::fmt::Display for Uri {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", SCHEME, self.address)?;
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!(
+                "amount={}",
+                Amount::from_sat(amount).to_string_in(Denomination::Bitcoin)
+            ));
+        }
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", percent_encode(label)));
+        }
+        if let Some(message) = &self.message {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+        if !params.is_empty() {
+            write!(f, "?{}", params.join("&"))?;
+        }
+        Ok(())
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                // from_str_radix accepts a sign, the two characters must be digits.
+                let hex = s
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .ok_or(Error::InvalidEncoding)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| Error::InvalidEncoding)?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| Error::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+
+    #[test]
+    fn parse() {
+        let uri = Uri::from_str(&format!(
+            "bitcoin:{}?amount=0.5&label=Luke-Jr&message=Donation%20for+project&other=1",
+            ADDRESS
+        ))
+        .unwrap();
+        assert_eq!(uri.address, Address::from_str(ADDRESS).unwrap());
+        assert_eq!(uri.amount, Some(50_000_000));
+        assert_eq!(uri.label, Some("Luke-Jr".to_string()));
+        assert_eq!(uri.message, Some("Donation for project".to_string()));
+
+        let uri = Uri::from_str(&format!(" BITCOIN:{} ", ADDRESS)).unwrap();
+        assert_eq!(uri, Uri::new(Address::from_str(ADDRESS).unwrap()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            (ADDRESS.to_string(), Error::NotBip21),
+            ("bitcoin".to_string(), Error::NotBip21),
+            ("bitcoin:notanaddress".to_string(), Error::InvalidAddress),
+            (
+                format!("bitcoin:{}?amount=abc", ADDRESS),
+                Error::InvalidAmount,
+            ),
+            (
+                format!("bitcoin:{}?amount=-1", ADDRESS),
+                Error::InvalidAmount,
+            ),
+            (
+                format!("bitcoin:{}?label=%zz", ADDRESS),
+                Error::InvalidEncoding,
+            ),
+            (
+                format!("bitcoin:{}?label=%4", ADDRESS),
+                Error::InvalidEncoding,
+            ),
+            (
+                format!("bitcoin:{}?label=%+1", ADDRESS),
+                Error::InvalidEncoding,
+            ),
+            (
+                format!("bitcoin:{}?label=%C3", ADDRESS),
+                Error::InvalidEncoding,
+            ),
+            (
+                format!("bitcoin:{}?req-pop=1", ADDRESS),
+                Error::UnknownRequiredParameter("req-pop".to_string()),
+            ),
+        ];
+        for (uri, error) in cases.iter() {
+            assert_eq!(Uri::from_str(uri), Err(error.clone()), "{}", uri);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut uri = Uri::new(Address::from_str(ADDRESS).unwrap());
+        assert_eq!(uri.to_string(), format!("bitcoin:{}", ADDRESS));
+
+        uri.amount = Some(123_456_789);
+        uri.label = Some("Rent & bills = 100%".to_string());
+        uri.message = Some("Loyer d'été".to_string());
+        let encoded = uri.to_string();
+        assert!(!encoded.contains(' '));
+        assert_eq!(Uri::from_str(&encoded), Ok(uri));
+    }
+}
@@ -0,0 +1,188 @@
+/// Virtual size of the spend transaction without inputs and outputs.
+const SPEND_TX_BASE_VSIZE: u64 = 11;
+/// Approximate virtual size of a spend transaction input: unvault outputs are
+/// P2WSH with a witness script of the managers and cosigners keys.
+const SPEND_TX_INPUT_VSIZE: u64 = 200;
+/// Virtual size of a P2WSH output.
+const SPEND_TX_OUTPUT_VSIZE: u64 = 43;
+
+/// Maximum number of combinations explored before keeping the best found selection.
+const MAX_TRIES: usize = 100_000;
+
+/// estimate_fee returns an approximation of the fee of a spend transaction with
+/// the given number of inputs and outputs, change output included.
+pub fn estimate_fee(feerate: u64, inputs: usize, outputs: usize) -> u64 {
+    feerate
+        * (SPEND_TX_BASE_VSIZE
+            + SPEND_TX_INPUT_VSIZE * inputs as u64
+            + SPEND_TX_OUTPUT_VSIZE * (outputs as u64 + 1))
+}
+
+/// Candidate is a spendable vault.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub amount: u64,
+    /// Timestamp of the last vault update, older vaults are preferred.
+    pub timestamp: i64,
+}
+
+/// Selection score, lower is better: fewer unvaults first, then less change,
+/// then older vaults.
+type Score = (usize, u64, i64);
+
+struct Search<'a, F: Fn(usize) -> u64> {
+    candidates: &'a [Candidate],
+    /// candidates indexes sorted by amount, largest first.
+    order: Vec<usize>,
+    /// remaining[i] is the total amount of the candidates order[i..].
+    remaining: Vec<u64>,
+    amount: u64,
+    fee: F,
+    max_inputs: usize,
+    tries: usize,
+    best: Option<(Score, Vec<usize>)>,
+}
+
+impl<'a, F: Fn(usize) -> u64> Search<'a, F> {
+    fn explore(&mut self, start: usize, selected: &mut Vec<usize>, total: u64) {
+        if self.tries >= MAX_TRIES {
+            return;
+        }
+        self.tries += 1;
+
+        let target = self.amount + (self.fee)(selected.len());
+        if !selected.is_empty() && total >= target {
+            let score = (
+                selected.len(),
+                total - target,
+                selected.iter().map(|i| self.candidates[*i].timestamp).sum(),
+            );
+            if self.best.as_ref().map_or(true, |(best, _)| score < *best) {
+                self.best = Some((score, selected.clone()));
+            }
+            // More inputs can only make the selection worse.
+            return;
+        }
+
+        if selected.len() >= self.max_inputs {
+            return;
+        }
+        if let Some(((inputs, _, _), _)) = &self.best {
+            if selected.len() + 1 > *inputs {
+                return;
+            }
+        }
+
+        for position in start..self.order.len() {
+            let target = self.amount + (self.fee)(selected.len() + 1);
+            if total + self.remaining[position] < target {
+                return;
+            }
+            let index = self.order[position];
+            selected.push(index);
+            self.explore(
+                position + 1,
+                selected,
+                total + self.candidates[index].amount,
+            );
+            selected.pop();
+        }
+    }
+}
+
+/// select returns the indexes of the candidates to spend in order to pay the amount
+/// and the fee of a transaction with the selected number of inputs, or None if the
+/// amount cannot be reached with at most max_inputs candidates.
+pub fn select<F: Fn(usize) -> u64>(
+    candidates: &[Candidate],
+    amount: u64,
+    fee: F,
+    max_inputs: usize,
+) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| {
+        candidates[*b]
+            .amount
+            .cmp(&candidates[*a].amount)
+            .then(candidates[*a].timestamp.cmp(&candidates[*b].timestamp))
+    });
+    let mut remaining = vec![0; order.len() + 1];
+    for position in (0..order.len()).rev() {
+        remaining[position] = remaining[position + 1] + candidates[order[position]].amount;
+    }
+
+    let mut search = Search {
+        candidates,
+        order,
+        remaining,
+        amount,
+        fee,
+        max_inputs,
+        tries: 0,
+        best: None,
+    };
+    search.explore(0, &mut Vec::new(), 0);
+    search.best.map(|(_, mut selected)| {
+        selected.sort_unstable();
+        selected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(amounts: &[u64]) -> Vec<Candidate> {
+        amounts
+            .iter()
+            .map(|amount| Candidate {
+                amount: *amount,
+                timestamp: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fee_estimation() {
+        assert_eq!(estimate_fee(2, 1, 1), 2 * (11 + 200 + 2 * 43));
+        assert_eq!(estimate_fee(0, 5, 5), 0);
+    }
+
+    #[test]
+    fn select_fewest_inputs() {
+        let no_fee = |_| 0;
+        let c = candidates(&[5, 3, 2, 10]);
+        assert_eq!(select(&c, 5, no_fee, 10), Some(vec![0]));
+        assert_eq!(select(&c, 6, no_fee, 10), Some(vec![3]));
+        assert_eq!(select(&c, 13, no_fee, 10), Some(vec![1, 3]));
+        assert_eq!(select(&c, 20, no_fee, 10), Some(vec![0, 1, 2, 3]));
+        assert_eq!(select(&c, 21, no_fee, 10), None);
+        assert_eq!(select(&[], 1, no_fee, 10), None);
+    }
+
+    #[test]
+    fn select_older_vaults() {
+        let mut c = candidates(&[6, 3, 3]);
+        c[2].timestamp = -1;
+        assert_eq!(select(&c, 7, |_| 0, 10), Some(vec![0, 2]));
+        assert_eq!(select(&c, 3, |_| 0, 10), Some(vec![2]));
+    }
+
+    #[test]
+    fn select_with_fee() {
+        let c = candidates(&[100, 60, 50]);
+        let fee = |inputs: usize| inputs as u64 * 10;
+        assert_eq!(select(&c, 90, fee, 10), Some(vec![0]));
+        // 100 cannot pay the amount and the fee of one input anymore.
+        assert_eq!(select(&c, 95, fee, 10), Some(vec![0, 2]));
+        assert_eq!(select(&c, 180, fee, 10), Some(vec![0, 1, 2]));
+        assert_eq!(select(&c, 181, fee, 10), None);
+    }
+
+    #[test]
+    fn select_max_inputs() {
+        let c = candidates(&[1, 1, 1]);
+        assert_eq!(select(&c, 3, |_| 0, 2), None);
+        assert_eq!(select(&c, 3, |_| 0, 3), Some(vec![0, 1, 2]));
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use bitcoin::{
+    util::psbt::PartiallySignedTransaction as Psbt, Address, Amount, Denomination, Network,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    psbt::{self, Format},
+    recipients::Recipient,
+    revaultd::model::{FeePriority, StoredSpendTransaction},
+};
+
+/// Name of the directory of the drafts in the GUI data directory.
+pub const DIR_NAME: &str = "drafts";
+
+/// Draft is a spend in progress, saved while the spend wizard is edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    pub id: String,
+    /// Last save as Unix Epoch timestamp.
+    #[serde(default)]
+    pub updated_at: i64,
+    pub recipients: Vec<Recipient>,
+    /// Outpoints of the selected vaults.
+    pub inputs: Vec<String>,
+    /// Fee rate in sat/vbyte.
+    pub feerate: String,
+    pub fee_priority: Option<FeePriority>,
+    #[serde(default)]
+    pub memo: String,
+    /// Base64 encoded spend transaction, with the signatures collected so far.
+    pub psbt: Option<String>,
+}
+
+impl Draft {
+    pub fn new() -> Self {
+        Self {
+            id: chrono::Utc::now().timestamp_millis().to_string(),
+            updated_at: 0,
+            recipients: Vec::new(),
+            inputs: Vec::new(),
+            feerate: "".to_string(),
+            fee_priority: None,
+            memo: "".to_string(),
+            psbt: None,
+        }
+    }
+
+    /// from_stored_spend_tx makes a draft of a spend transaction stored by revaultd,
+    /// the outputs paying one of our addresses are considered as change.
+    pub fn from_stored_spend_tx(
+        spend_tx: &StoredSpendTransaction,
+        network: Network,
+        own_addresses: &HashSet<String>,
+    ) -> Self {
+        let recipients = spend_tx
+            .psbt
+            .global
+            .unsigned_tx
+            .output
+            .iter()
+            .filter_map(|output| {
+                let address = Address::from_script(&output.script_pubkey, network)?.to_string();
+                if own_addresses.contains(&address) {
+                    return None;
+                }
+                Some(Recipient {
+                    address,
+                    amount: Amount::from_sat(output.value).to_string_in(Denomination::Bitcoin),
+                    label: None,
+                })
+            })
+            .collect();
+        let mut draft = Self::new();
+        draft.id = spend_tx.psbt.global.unsigned_tx.txid().to_string();
+        draft.recipients = recipients;
+        draft.inputs = spend_tx.deposit_outpoints.clone();
+        draft.set_psbt(Some(&spend_tx.psbt));
+        draft
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipients.is_empty() && self.inputs.is_empty()
+    }
+
+    pub fn psbt(&self) -> Option<Psbt> {
+        self.psbt
+            .as_ref()
+            .and_then(|psbt| psbt::from_bytes(psbt.as_bytes()))
+    }
+
+    pub fn set_psbt(&mut self, psbt: Option<&Psbt>) {
+        self.psbt = psbt.map(|psbt| {
+            String::from_utf8(psbt::to_bytes(psbt, Format::Base64)).expect("base64 is valid utf8")
+        });
+    }
+
+    /// amount returns the sum in satoshis of the recipients amounts.
+    pub fn amount(&self) -> u64 {
+        self.recipients
+            .iter()
+            .filter_map(|r| Amount::from_str_in(&r.amount, Denomination::Bitcoin).ok())
+            .map(|amount| amount.as_sat())
+            .sum()
+    }
+
+    /// signatures returns the number of signatures of the least signed input
+    /// of the spend transaction.
+    pub fn signatures(&self) -> Option<usize> {
+        self.psbt()
+            .and_then(|psbt| psbt::signature_counts(&psbt).into_iter().min())
+    }
+
+    /// txid returns the id of the spend transaction of the draft if it was built.
+    pub fn txid(&self) -> Option<String> {
+        self.psbt()
+            .map(|psbt| psbt.global.unsigned_tx.txid().to_string())
+    }
+
+    /// file_name returns the name of the draft file in the drafts directory.
+    pub fn file_name(id: &str) -> String {
+        format!("{}.json", id)
+    }
+}
+
+/// sync lists the pending spends: the saved drafts, with the signatures collected
+/// by revaultd for their spend transaction, and the spend transactions stored by
+/// revaultd that have no draft.
+pub fn sync(
+    drafts: &[Draft],
+    spend_txs: &[StoredSpendTransaction],
+    network: Network,
+    own_addresses: &HashSet<String>,
+) -> Vec<Draft> {
+    let mut pending: Vec<Draft> = drafts.to_vec();
+    for spend_tx in spend_txs {
+        let txid = spend_tx.psbt.global.unsigned_tx.txid().to_string();
+        match pending
+            .iter_mut()
+            .find(|draft| draft.txid().as_ref() == Some(&txid))
+        {
+            Some(draft) => {
+                if let Some(mut psbt) = draft.psbt() {
+                    if psbt::combine(&mut psbt, &spend_tx.psbt).is_ok() {
+                        draft.set_psbt(Some(&psbt));
+                    }
+                }
+            }
+            None => pending.push(Draft::from_stored_spend_tx(
+                spend_tx,
+                network,
+                own_addresses,
+            )),
+        }
+    }
+    pending.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        secp256k1::{PublicKey, Secp256k1, SecretKey},
+        OutPoint, Script, Transaction, TxIn, TxOut,
+    };
+    use std::str::FromStr;
+
+    const RECIPIENT: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+    const OWN_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    fn spend_tx(vout: u32, outputs: &[(&str, u64)]) -> StoredSpendTransaction {
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(OutPoint::null().txid, vout),
+                script_sig: Script::new(),
+                sequence: 0xffff_ffff,
+                witness: Vec::new(),
+            }],
+            output: outputs
+                .iter()
+                .map(|(address, value)| TxOut {
+                    value: *value,
+                    script_pubkey: Address::from_str(address).unwrap().script_pubkey(),
+                })
+                .collect(),
+        };
+        StoredSpendTransaction {
+            deposit_outpoints: vec![format!("{}:{}", OutPoint::null().txid, vout)],
+            psbt: Psbt::from_unsigned_tx(tx).unwrap(),
+        }
+    }
+
+    fn add_signature(psbt: &mut Psbt, seed: u8) {
+        let secp = Secp256k1::new();
+        let key = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[seed; 32]).unwrap());
+        psbt.inputs[0].partial_sigs.insert(
+            bitcoin::PublicKey {
+                compressed: true,
+                key,
+            },
+            vec![seed],
+        );
+    }
+
+    fn own_addresses() -> HashSet<String> {
+        vec![OWN_ADDRESS.to_string()].into_iter().collect()
+    }
+
+    #[test]
+    fn from_stored_spend_tx() {
+        let stored = spend_tx(0, &[(RECIPIENT, 50_000), (OWN_ADDRESS, 10_000)]);
+        let draft = Draft::from_stored_spend_tx(&stored, Network::Bitcoin, &own_addresses());
+        assert_eq!(draft.id, stored.psbt.global.unsigned_tx.txid().to_string());
+        assert_eq!(draft.txid(), Some(draft.id.clone()));
+        assert_eq!(draft.recipients.len(), 1);
+        assert_eq!(draft.recipients[0].address, RECIPIENT);
+        assert_eq!(draft.amount(), 50_000);
+        assert_eq!(draft.inputs, stored.deposit_outpoints);
+        assert_eq!(draft.psbt(), Some(stored.psbt));
+        assert_eq!(draft.signatures(), Some(0));
+    }
+
+    #[test]
+    fn sync() {
+        let known = spend_tx(0, &[(RECIPIENT, 50_000)]);
+        let mut saved = Draft::new();
+        saved.id = "saved".to_string();
+        saved.updated_at = 1;
+        let mut psbt = known.psbt.clone();
+        add_signature(&mut psbt, 1);
+        saved.set_psbt(Some(&psbt));
+
+        let mut unbuilt = Draft::new();
+        unbuilt.id = "unbuilt".to_string();
+        unbuilt.updated_at = 2;
+
+        let mut known_signed = known.clone();
+        add_signature(&mut known_signed.psbt, 2);
+        let unknown = spend_tx(1, &[(RECIPIENT, 20_000)]);
+
+        let pending = super::sync(
+            &[saved, unbuilt],
+            &[known_signed, unknown.clone()],
+            Network::Bitcoin,
+            &own_addresses(),
+        );
+        let ids: Vec<&str> = pending.iter().map(|d| d.id.as_str()).collect();
+        let unknown_txid = unknown.psbt.global.unsigned_tx.txid().to_string();
+        assert_eq!(ids, vec!["unbuilt", "saved", unknown_txid.as_str()]);
+        // The signatures of the draft and of revaultd are combined.
+        assert_eq!(pending[1].signatures(), Some(2));
+        assert_eq!(pending[2].signatures(), Some(0));
+        assert_eq!(pending[2].amount(), 20_000);
+    }
+
+    #[test]
+    fn sync_without_spend_txs() {
+        let mut draft = Draft::new();
+        draft.recipients.push(Recipient {
+            address: RECIPIENT.to_string(),
+            amount: "0.1".to_string(),
+            label: None,
+        });
+        let pending = super::sync(&[draft.clone()], &[], Network::Bitcoin, &own_addresses());
+        assert_eq!(pending, vec![draft]);
+        assert!(super::sync(&[], &[], Network::Bitcoin, &own_addresses()).is_empty());
+    }
+}
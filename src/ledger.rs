@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use bitcoin::{Address, Network, Transaction, Txid};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::{
+    conversion::Converter,
+    recipients::quote_field,
+    revaultd::model::{BroadcastedTransaction, Vault, VaultTransactions},
+};
+
+/// Format of an exported ledger file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// Ledger is the list of the exported vaults with their on-chain transactions.
+/// Amounts are in the unit of the ledger and dates are in UTC.
+#[derive(Debug, Serialize)]
+pub struct Ledger {
+    pub unit: String,
+    pub vaults: Vec<LedgerVault>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerVault {
+    pub outpoint: String,
+    pub address: String,
+    pub amount: f64,
+    pub status: String,
+    pub updated_at: String,
+    pub transactions: Vec<LedgerTransaction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerTransaction {
+    pub txid: String,
+    pub kind: String,
+    pub blockheight: Option<u64>,
+    pub received_at: String,
+    /// fee is unknown if one of the spent outputs is not a transaction of
+    /// the exported vaults, like the inputs of a deposit.
+    pub fee: Option<f64>,
+    pub destinations: Vec<String>,
+}
+
+impl Ledger {
+    /// new keeps the order of the given vaults, the transactions of a vault
+    /// are ordered from the deposit to the last one.
+    pub fn new(network: Network, vaults: &[Vault], txs: &[VaultTransactions]) -> Self {
+        let converter = Converter::new(network);
+        let known: HashMap<Txid, &Transaction> = txs
+            .iter()
+            .flat_map(|vault_txs| transactions(vault_txs))
+            .map(|(_, broadcasted)| (broadcasted.tx.txid(), &broadcasted.tx))
+            .collect();
+
+        let vaults = vaults
+            .iter()
+            .map(|vault| {
+                let outpoint = vault.outpoint();
+                let transactions = txs
+                    .iter()
+                    .find(|vault_txs| vault_txs.vault_outpoint == outpoint)
+                    .map(|vault_txs| {
+                        transactions(vault_txs)
+                            .into_iter()
+                            .map(|(kind, broadcasted)| LedgerTransaction {
+                                txid: broadcasted.tx.txid().to_string(),
+                                kind: kind.to_string(),
+                                blockheight: broadcasted.blockheight,
+                                received_at: format_date(broadcasted.received_at),
+                                fee: fee(&broadcasted.tx, &known)
+                                    .map(|fee| converter.converts(fee)),
+                                destinations: broadcasted
+                                    .tx
+                                    .output
+                                    .iter()
+                                    .filter_map(|output| {
+                                        Address::from_script(&output.script_pubkey, network)
+                                    })
+                                    .map(|address| address.to_string())
+                                    .collect(),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                LedgerVault {
+                    outpoint,
+                    address: vault.address.clone(),
+                    amount: converter.converts(vault.amount),
+                    status: vault.status.to_string(),
+                    updated_at: format_date(vault.updated_at),
+                    transactions,
+                }
+            })
+            .collect();
+
+        Ledger {
+            unit: converter.unit.to_string(),
+            vaults,
+        }
+    }
+
+    /// to_csv writes a row for each transaction with the columns of its vault,
+    /// a vault without transaction has a row with empty transaction columns.
+    pub fn to_csv(&self) -> String {
+        let mut content = format!(
+            "outpoint,address,amount ({unit}),status,updated_at,txid,kind,blockheight,received_at,fee ({unit}),destination\n",
+            unit = self.unit
+        );
+        for vault in &self.vaults {
+            let columns = format!(
+                "{},{},{},{},{}",
+                quote_field(&vault.outpoint),
+                quote_field(&vault.address),
+                vault.amount,
+                quote_field(&vault.status),
+                quote_field(&vault.updated_at),
+            );
+            if vault.transactions.is_empty() {
+                content.push_str(&format!("{},,,,,,\n", columns));
+            }
+            for tx in &vault.transactions {
+                content.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    columns,
+                    tx.txid,
+                    tx.kind,
+                    tx.blockheight.map(|h| h.to_string()).unwrap_or_default(),
+                    quote_field(&tx.received_at),
+                    tx.fee.map(|fee| fee.to_string()).unwrap_or_default(),
+                    quote_field(&tx.destinations.join(" ")),
+                ));
+            }
+        }
+        content
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// transactions returns the broadcasted transactions of a vault with their kind.
+fn transactions(txs: &VaultTransactions) -> Vec<(&'static str, &BroadcastedTransaction)> {
+    let mut list = vec![("deposit", &txs.deposit)];
+    for &(kind, tx) in &[
+        ("unvault", &txs.unvault),
+        ("spend", &txs.spend),
+        ("cancel", &txs.cancel),
+        ("emergency", &txs.emergency),
+        ("unvault_emergency", &txs.unvault_emergency),
+    ] {
+        if let Some(tx) = tx {
+            list.push((kind, tx));
+        }
+    }
+    list
+}
+
+/// fee returns the fee in satoshis of the transaction if all its spent
+/// outputs are known.
+fn fee(tx: &Transaction, known: &HashMap<Txid, &Transaction>) -> Option<u64> {
+    let mut input_amount: u64 = 0;
+    for input in &tx.input {
+        let previous = known.get(&input.previous_output.txid)?;
+        input_amount = input_amount.checked_add(
+            previous
+                .output
+                .get(input.previous_output.vout as usize)?
+                .value,
+        )?;
+    }
+    let output_amount = tx
+        .output
+        .iter()
+        .try_fold(0u64, |total, output| total.checked_add(output.value))?;
+    input_amount.checked_sub(output_amount)
+}
+
+/// format_date returns an empty date if the timestamp is out of range.
+fn format_date(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp_opt(timestamp, 0)
+        .map(|date| date.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revaultd::model::VaultStatus;
+    use bitcoin::{OutPoint, Script, TxIn, TxOut};
+    use std::str::FromStr;
+
+    const VAULT_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    const RECIPIENT: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+
+    fn tx(inputs: &[OutPoint], outputs: &[(&str, u64)]) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: inputs
+                .iter()
+                .map(|previous_output| TxIn {
+                    previous_output: *previous_output,
+                    script_sig: Script::new(),
+                    sequence: 0xffff_ffff,
+                    witness: Vec::new(),
+                })
+                .collect(),
+            output: outputs
+                .iter()
+                .map(|(address, value)| TxOut {
+                    value: *value,
+                    script_pubkey: Address::from_str(address).unwrap().script_pubkey(),
+                })
+                .collect(),
+        }
+    }
+
+    fn broadcasted(tx: Transaction, blockheight: Option<u64>) -> BroadcastedTransaction {
+        BroadcastedTransaction {
+            blockheight,
+            tx,
+            received_at: 0,
+        }
+    }
+
+    fn vault(deposit: &Transaction, status: VaultStatus) -> Vault {
+        Vault {
+            address: VAULT_ADDRESS.to_string(),
+            amount: deposit.output[0].value,
+            status,
+            txid: deposit.txid().to_string(),
+            updated_at: 0,
+            vout: 0,
+        }
+    }
+
+    fn ledger() -> Ledger {
+        let deposit = tx(&[OutPoint::null()], &[(VAULT_ADDRESS, 100_000)]);
+        let unvault = tx(
+            &[OutPoint::new(deposit.txid(), 0)],
+            &[(VAULT_ADDRESS, 99_000)],
+        );
+        let spend = tx(
+            &[OutPoint::new(unvault.txid(), 0)],
+            &[(RECIPIENT, 50_000), (VAULT_ADDRESS, 48_000)],
+        );
+        let secured = tx(
+            &[OutPoint::new(OutPoint::null().txid, 1)],
+            &[(VAULT_ADDRESS, 200_000)],
+        );
+        let spent = vault(&deposit, VaultStatus::Spent);
+        let txs = VaultTransactions {
+            vault_outpoint: spent.outpoint(),
+            deposit: broadcasted(deposit, Some(1)),
+            unvault: Some(broadcasted(unvault, Some(2))),
+            spend: Some(broadcasted(spend, None)),
+            cancel: None,
+            emergency: None,
+            unvault_emergency: None,
+        };
+        Ledger::new(
+            Network::Bitcoin,
+            &[spent, vault(&secured, VaultStatus::Secured)],
+            &[txs],
+        )
+    }
+
+    #[test]
+    fn new() {
+        let ledger = ledger();
+        assert_eq!(
+            ledger.unit,
+            Converter::new(Network::Bitcoin).unit.to_string()
+        );
+        assert_eq!(ledger.vaults.len(), 2);
+
+        let spent = &ledger.vaults[0];
+        assert_eq!(spent.amount, 0.001);
+        assert_eq!(spent.updated_at, "1970-01-01 00:00:00");
+        let kinds: Vec<&str> = spent
+            .transactions
+            .iter()
+            .map(|tx| tx.kind.as_str())
+            .collect();
+        assert_eq!(kinds, vec!["deposit", "unvault", "spend"]);
+        // The inputs of the deposit are not transactions of the vaults.
+        assert_eq!(spent.transactions[0].fee, None);
+        assert_eq!(spent.transactions[1].fee, Some(0.00001));
+        assert_eq!(spent.transactions[2].fee, Some(0.00001));
+        assert_eq!(spent.transactions[2].blockheight, None);
+        assert_eq!(
+            spent.transactions[2].destinations,
+            vec![RECIPIENT.to_string(), VAULT_ADDRESS.to_string()]
+        );
+
+        assert!(ledger.vaults[1].transactions.is_empty());
+        assert_eq!(ledger.vaults[1].amount, 0.002);
+    }
+
+    #[test]
+    fn csv() {
+        let csv = ledger().to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[0].starts_with("outpoint,address,amount ("));
+        let columns = |row: &str| row.split(',').count();
+        for row in &rows {
+            assert_eq!(columns(row), 11, "{}", row);
+        }
+        assert!(rows[1].contains(",deposit,1,"));
+        assert!(rows[3].ends_with(&format!(",0.00001,{} {}", RECIPIENT, VAULT_ADDRESS)));
+        assert!(rows[4].ends_with(",,,,,,"));
+    }
+
+    #[test]
+    fn json() {
+        let json: serde_json::Value = serde_json::from_str(&ledger().to_json().unwrap()).unwrap();
+        assert_eq!(json["vaults"].as_array().unwrap().len(), 2);
+        assert_eq!(json["vaults"][0]["transactions"][1]["kind"], "unvault");
+        assert_eq!(json["vaults"][0]["transactions"][1]["fee"], 0.00001);
+        assert!(json["vaults"][0]["transactions"][0]["fee"].is_null());
+    }
+
+    #[test]
+    fn fee_overflow() {
+        let funding = tx(
+            &[OutPoint::null()],
+            &[(RECIPIENT, u64::MAX), (RECIPIENT, 1)],
+        );
+        let known: HashMap<Txid, &Transaction> =
+            vec![(funding.txid(), &funding)].into_iter().collect();
+        let spend = tx(
+            &[
+                OutPoint::new(funding.txid(), 0),
+                OutPoint::new(funding.txid(), 1),
+            ],
+            &[(RECIPIENT, 1)],
+        );
+        assert_eq!(fee(&spend, &known), None);
+        let missing = tx(&[OutPoint::new(funding.txid(), 2)], &[(RECIPIENT, 1)]);
+        assert_eq!(fee(&missing, &known), None);
+        let spend = tx(&[OutPoint::new(funding.txid(), 1)], &[(RECIPIENT, 1)]);
+        assert_eq!(fee(&spend, &known), Some(0));
+    }
+
+    #[test]
+    fn date_out_of_range() {
+        assert_eq!(format_date(0), "1970-01-01 00:00:00");
+        assert_eq!(format_date(i64::MAX), "");
+    }
+}
@@ -6,10 +6,22 @@ use tracing_subscriber::filter::EnvFilter;
 extern crate serde;
 extern crate serde_json;
 
+mod address_book;
+mod bip21;
+mod coin_selection;
 mod conversion;
+mod draft;
+mod ledger;
+mod notification;
+mod policy;
+mod psbt;
+mod recipients;
 mod revault;
 mod revaultd;
+mod revocation;
+mod signer;
 mod ui;
+mod ur;
 
 fn main() {
     let revaultd_config_path = match std::env::var("REVAULTD_CONF") {
@@ -68,6 +80,57 @@ fn main() {
         Err(VarError::NotPresent) => None,
     };
 
+    let psbt_dir = match std::env::var("REVAULTGUI_PSBT_DIR") {
+        Ok(p) => Some(PathBuf::from(p)),
+        Err(VarError::NotUnicode(_)) => {
+            println!("Error: REVAULTGUI_PSBT_DIR unicode only");
+            std::process::exit(1);
+        }
+        Err(VarError::NotPresent) => None,
+    };
+
+    let signer_path = match std::env::var("REVAULTGUI_SIGNER") {
+        Ok(p) => Some(PathBuf::from(p)),
+        Err(VarError::NotUnicode(_)) => {
+            println!("Error: REVAULTGUI_SIGNER unicode only");
+            std::process::exit(1);
+        }
+        Err(VarError::NotPresent) => None,
+    };
+
+    let signer_key_path = match std::env::var("REVAULTGUI_SIGNER_KEY") {
+        Ok(p) => Some(PathBuf::from(p)),
+        Err(VarError::NotUnicode(_)) => {
+            println!("Error: REVAULTGUI_SIGNER_KEY unicode only");
+            std::process::exit(1);
+        }
+        Err(VarError::NotPresent) => None,
+    };
+
+    let spending_policy_path = match std::env::var("REVAULTGUI_SPENDING_POLICY") {
+        Ok(p) => Some(PathBuf::from(p)),
+        Err(VarError::NotUnicode(_)) => {
+            println!("Error: REVAULTGUI_SPENDING_POLICY unicode only");
+            std::process::exit(1);
+        }
+        Err(VarError::NotPresent) => None,
+    };
+
+    let fee_warning_percent = match std::env::var("REVAULTGUI_FEE_WARNING_PERCENT") {
+        Ok(var) => match u64::from_str(&var) {
+            Ok(v) => Some(v),
+            Err(_) => {
+                println!("Error: REVAULTGUI_FEE_WARNING_PERCENT must be a positive integer");
+                std::process::exit(1);
+            }
+        },
+        Err(VarError::NotUnicode(_)) => {
+            println!("Error: REVAULTGUI_FEE_WARNING_PERCENT must be a positive integer");
+            std::process::exit(1);
+        }
+        Err(VarError::NotPresent) => None,
+    };
+
     let subscriber = tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(logfilter)
         .finish();
@@ -80,6 +143,11 @@ fn main() {
     if let Err(e) = ui::app::run(ui::app::Config {
         revaultd_config_path,
         revaultd_path,
+        psbt_dir,
+        signer_path,
+        signer_key_path,
+        fee_warning_percent,
+        spending_policy_path,
         debug,
     }) {
         println!("Error: failed to launch UI: {}", e.to_string());
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use bitcoin::{Amount, Denomination};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    revault::Role,
+    revaultd::model::{Vault, VaultStatus},
+};
+
+/// Name of the notification preferences file in the GUI data directory.
+pub const FILE_NAME: &str = "notifications.json";
+
+/// Maximum number of notifications kept in the notification center.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// Statuses of the vaults raising an event, the polls only list the vaults having one
+/// of them. A vault leaving them is forgotten and is notified again when it reaches
+/// one of them.
+pub const WATCHED_STATUSES: &[VaultStatus] = &[
+    VaultStatus::Funded,
+    VaultStatus::Unvaulting,
+    VaultStatus::Canceling,
+    VaultStatus::EmergencyVaulting,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A deposit is confirmed and its revocation transactions must be signed.
+    DepositToSign,
+    UnvaultStarted,
+    CancelBroadcast,
+    EmergencyBroadcast,
+    /// revaultd does not answer anymore.
+    Disconnected,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 5] = [
+        EventKind::DepositToSign,
+        EventKind::UnvaultStarted,
+        EventKind::CancelBroadcast,
+        EventKind::EmergencyBroadcast,
+        EventKind::Disconnected,
+    ];
+
+    /// from_status returns the kind of the event of a vault reaching the given status.
+    fn from_status(status: &VaultStatus) -> Option<Self> {
+        match status {
+            VaultStatus::Funded => Some(Self::DepositToSign),
+            VaultStatus::Unvaulting => Some(Self::UnvaultStarted),
+            VaultStatus::Canceling => Some(Self::CancelBroadcast),
+            VaultStatus::EmergencyVaulting => Some(Self::EmergencyBroadcast),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DepositToSign => write!(f, "Deposit to sign"),
+            Self::UnvaultStarted => write!(f, "Unvault started"),
+            Self::CancelBroadcast => write!(f, "Cancel broadcast"),
+            Self::EmergencyBroadcast => write!(f, "Emergency broadcast"),
+            Self::Disconnected => write!(f, "revaultd disconnected"),
+        }
+    }
+}
+
+/// Preferences are the kinds of events raising a desktop notification for each role.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preferences {
+    pub stakeholder: Vec<EventKind>,
+    pub manager: Vec<EventKind>,
+}
+
+impl Default for Preferences {
+    /// Only the stakeholders sign the deposits, every other event is notified to both roles.
+    fn default() -> Self {
+        Self {
+            stakeholder: EventKind::ALL.to_vec(),
+            manager: EventKind::ALL
+                .iter()
+                .copied()
+                .filter(|kind| *kind != EventKind::DepositToSign)
+                .collect(),
+        }
+    }
+}
+
+impl Preferences {
+    fn kinds_mut(&mut self, role: Role) -> &mut Vec<EventKind> {
+        match role {
+            Role::Stakeholder => &mut self.stakeholder,
+            Role::Manager => &mut self.manager,
+        }
+    }
+
+    pub fn enabled(&self, role: Role, kind: EventKind) -> bool {
+        match role {
+            Role::Stakeholder => self.stakeholder.contains(&kind),
+            Role::Manager => self.manager.contains(&kind),
+        }
+    }
+
+    pub fn toggle(&mut self, role: Role, kind: EventKind) {
+        let kinds = self.kinds_mut(role);
+        if let Some(i) = kinds.iter().position(|k| *k == kind) {
+            kinds.remove(i);
+        } else {
+            kinds.push(kind);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Identifier of the notification in the notification center.
+    pub id: u64,
+    pub kind: EventKind,
+    pub message: String,
+    /// Unix Epoch timestamp.
+    pub received_at: i64,
+    pub read: bool,
+}
+
+impl Notification {
+    fn new(kind: EventKind, message: String) -> Self {
+        Self {
+            id: 0,
+            kind,
+            message,
+            received_at: chrono::Utc::now().timestamp(),
+            read: false,
+        }
+    }
+
+    fn of_vault(kind: EventKind, vault: &Vault) -> Self {
+        let amount = Amount::from_sat(vault.amount).to_string_in(Denomination::Bitcoin);
+        let message = match kind {
+            EventKind::DepositToSign => format!(
+                "Deposit of {} BTC received, its revocation transactions must be signed",
+                amount
+            ),
+            EventKind::UnvaultStarted => format!("Vault of {} BTC is being unvaulted", amount),
+            EventKind::CancelBroadcast => {
+                format!(
+                    "Cancel transaction of the vault of {} BTC broadcast",
+                    amount
+                )
+            }
+            EventKind::EmergencyBroadcast => format!(
+                "Emergency transaction of the vault of {} BTC broadcast",
+                amount
+            ),
+            EventKind::Disconnected => kind.to_string(),
+        };
+        Self::new(kind, format!("{} ({})", message, vault.outpoint()))
+    }
+}
+
+/// NotificationCenter records the events found by comparing the successive
+/// statuses of the vaults.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationCenter {
+    /// Notifications from the most recent.
+    pub notifications: Vec<Notification>,
+    pub preferences: Preferences,
+    /// Last known status of every vault, none until the vaults are first listed.
+    statuses: Option<HashMap<String, VaultStatus>>,
+    disconnected: bool,
+    next_id: u64,
+}
+
+impl NotificationCenter {
+    /// push gives the notification its identifier, records it and returns it.
+    fn push(&mut self, mut notification: Notification) -> Notification {
+        notification.id = self.next_id;
+        self.next_id += 1;
+        self.notifications.insert(0, notification.clone());
+        self.notifications.truncate(MAX_NOTIFICATIONS);
+        notification
+    }
+
+    /// update_vaults records the events of the vaults whose status changed since the
+    /// last update and returns them. The first update only records the statuses.
+    pub fn update_vaults(&mut self, vaults: &[Vault]) -> Vec<Notification> {
+        self.disconnected = false;
+        let statuses: HashMap<String, VaultStatus> = vaults
+            .iter()
+            .map(|vault| (vault.outpoint(), vault.status.clone()))
+            .collect();
+        let previous = match self.statuses.replace(statuses) {
+            Some(previous) => previous,
+            None => return Vec::new(),
+        };
+
+        let mut events = Vec::new();
+        for vault in vaults {
+            if previous.get(&vault.outpoint()) == Some(&vault.status) {
+                continue;
+            }
+            if let Some(kind) = EventKind::from_status(&vault.status) {
+                events.push(self.push(Notification::of_vault(kind, vault)));
+            }
+        }
+        events
+    }
+
+    /// disconnected records the loss of the connection to revaultd, only once
+    /// until the vaults are listed again.
+    pub fn disconnected(&mut self, error: &str) -> Option<Notification> {
+        if self.disconnected {
+            return None;
+        }
+        self.disconnected = true;
+        Some(self.push(Notification::new(
+            EventKind::Disconnected,
+            format!("Connection to revaultd lost: {}", error),
+        )))
+    }
+
+    pub fn unread(&self) -> usize {
+        self.notifications.iter().filter(|n| !n.read).count()
+    }
+
+    /// toggle_read marks the notification with the given identifier as read or unread.
+    pub fn toggle_read(&mut self, id: u64) {
+        if let Some(notification) = self.notifications.iter_mut().find(|n| n.id == id) {
+            notification.read = !notification.read;
+        }
+    }
+
+    pub fn mark_all_read(&mut self) {
+        for notification in &mut self.notifications {
+            notification.read = true;
+        }
+    }
+}
+
+/// show raises a desktop notification, through D-Bus on the freedesktop platforms.
+/// The call blocks until the notification server answers.
+pub fn show(notification: &Notification) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .summary(&format!("Revault: {}", notification.kind))
+        .body(&notification.message)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(txid: &str, status: VaultStatus) -> Vault {
+        Vault {
+            address: String::new(),
+            amount: 100_000_000,
+            status,
+            txid: txid.to_string(),
+            updated_at: 0,
+            vout: 0,
+        }
+    }
+
+    #[test]
+    fn watched_statuses_raise_events() {
+        for status in WATCHED_STATUSES {
+            assert!(EventKind::from_status(status).is_some());
+        }
+        assert!(EventKind::from_status(&VaultStatus::Secured).is_none());
+    }
+
+    #[test]
+    fn update_vaults() {
+        let mut center = NotificationCenter::default();
+        // The first listing only records the statuses.
+        assert!(center
+            .update_vaults(&[vault("a", VaultStatus::Funded)])
+            .is_empty());
+        assert!(center
+            .update_vaults(&[vault("a", VaultStatus::Funded)])
+            .is_empty());
+
+        let events = center.update_vaults(&[
+            vault("a", VaultStatus::Funded),
+            vault("b", VaultStatus::Funded),
+        ]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EventKind::DepositToSign);
+
+        // "a" left the watched statuses and reaches one of them again.
+        center.update_vaults(&[vault("b", VaultStatus::Funded)]);
+        let events = center.update_vaults(&[
+            vault("a", VaultStatus::Unvaulting),
+            vault("b", VaultStatus::Canceling),
+        ]);
+        let kinds: Vec<EventKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::UnvaultStarted, EventKind::CancelBroadcast]
+        );
+        assert_eq!(center.notifications.len(), 3);
+        assert_eq!(center.unread(), 3);
+    }
+
+    #[test]
+    fn disconnected_once() {
+        let mut center = NotificationCenter::default();
+        assert!(center.disconnected("error").is_some());
+        assert!(center.disconnected("error").is_none());
+        center.update_vaults(&[]);
+        assert!(center.disconnected("error").is_some());
+        assert_eq!(center.notifications.len(), 2);
+    }
+
+    #[test]
+    fn read_state() {
+        let mut center = NotificationCenter::default();
+        let first = center.disconnected("error").unwrap();
+        center.update_vaults(&[]);
+        let second = center.disconnected("error").unwrap();
+        assert_ne!(first.id, second.id);
+
+        center.toggle_read(first.id);
+        assert_eq!(center.unread(), 1);
+        assert!(
+            center
+                .notifications
+                .iter()
+                .find(|n| n.id == first.id)
+                .unwrap()
+                .read
+        );
+        center.toggle_read(first.id);
+        assert_eq!(center.unread(), 2);
+
+        center.mark_all_read();
+        assert_eq!(center.unread(), 0);
+        center.toggle_read(second.id);
+        assert_eq!(center.unread(), 1);
+    }
+}
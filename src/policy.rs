@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Action is what the GUI does when a rule of the spending policy is broken.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// The spend transaction cannot be submitted.
+    Block,
+    /// The manager is warned but may submit the spend transaction.
+    Warn,
+}
+
+impl Default for Action {
+    fn default() -> Self {
+        Self::Block
+    }
+}
+
+/// SpendingPolicy is the set of rules the watchtowers enforce on spend
+/// transactions, checked by the GUI before a spend is submitted.
+/// Amounts are in satoshis and fee rates in sat/vbyte.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpendingPolicy {
+    pub per_transaction_limit: Option<AmountLimit>,
+    /// Limit of the amount unvaulted during the last 24 hours.
+    pub daily_limit: Option<AmountLimit>,
+    pub destinations: Option<DestinationWhitelist>,
+    pub max_feerate: Option<FeerateLimit>,
+    /// The spend must be described by a memo.
+    pub memo: Option<MemoRequirement>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmountLimit {
+    pub amount: u64,
+    #[serde(default)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DestinationWhitelist {
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// The whitelisted entries of the address book are allowed destinations.
+    #[serde(default)]
+    pub address_book: bool,
+    #[serde(default)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeerateLimit {
+    pub feerate: u64,
+    #[serde(default)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemoRequirement {
+    #[serde(default)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone)]
+pub enum PolicyError {
+    ReadingFile(String),
+    Parsing(String),
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ReadingFile(e) => write!(f, "Reading spending policy file: {}", e),
+            Self::Parsing(e) => write!(f, "Parsing spending policy file: {}", e),
+        }
+    }
+}
+
+/// Spend is what the spending policy is checked against.
+#[derive(Debug, Clone)]
+pub struct Spend<'a> {
+    /// Amount paid to the recipients, change excluded.
+    pub amount: u64,
+    /// Amount of the vaults spent by the transaction.
+    pub unvaulted_amount: u64,
+    /// Amount of the vaults unvaulted during the last 24 hours.
+    pub unvaulted_last_day: u64,
+    pub destinations: Vec<String>,
+    /// Addresses of the whitelisted entries of the address book.
+    pub address_book_whitelist: HashSet<String>,
+    pub feerate: u64,
+    pub memo: &'a str,
+}
+
+/// Violation is a rule of the spending policy broken by a spend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub action: Action,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl SpendingPolicy {
+    pub fn from_file(path: &Path) -> Result<Self, PolicyError> {
+        let content = std::fs::read(path).map_err(|e| PolicyError::ReadingFile(e.to_string()))?;
+        toml::from_slice(&content).map_err(|e| PolicyError::Parsing(e.to_string()))
+    }
+
+    /// check returns the rules broken by the spend.
+    pub fn check(&self, spend: &Spend) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some(limit) = &self.per_transaction_limit {
+            if spend.amount > limit.amount {
+                violations.push(Violation {
+                    action: limit.action,
+                    reason: format!(
+                        "Per transaction limit: {} sats are sent, the limit is {} sats",
+                        spend.amount, limit.amount
+                    ),
+                });
+            }
+        }
+        if let Some(limit) = &self.daily_limit {
+            let total = spend.unvaulted_last_day + spend.unvaulted_amount;
+            if total > limit.amount {
+                violations.push(Violation {
+                    action: limit.action,
+                    reason: format!(
+                        "Daily limit: {} sats would be unvaulted in 24 hours, the limit is {} sats",
+                        total, limit.amount
+                    ),
+                });
+            }
+        }
+        if let Some(whitelist) = &self.destinations {
+            for destination in &spend.destinations {
+                let allowed = whitelist.addresses.contains(destination)
+                    || (whitelist.address_book
+                        && spend.address_book_whitelist.contains(destination));
+                if !allowed {
+                    violations.push(Violation {
+                        action: whitelist.action,
+                        reason: format!(
+                            "Destination whitelist: {} is not an allowed destination",
+                            destination
+                        ),
+                    });
+                }
+            }
+        }
+        if let Some(limit) = &self.max_feerate {
+            if spend.feerate > limit.feerate {
+                violations.push(Violation {
+                    action: limit.action,
+                    reason: format!(
+                        "Maximum fee rate: {} sat/vbyte is above {} sat/vbyte",
+                        spend.feerate, limit.feerate
+                    ),
+                });
+            }
+        }
+        if let Some(requirement) = &self.memo {
+            if spend.memo.trim().is_empty() {
+                violations.push(Violation {
+                    action: requirement.action,
+                    reason: "Required memo: the spend must be described by a memo".to_string(),
+                });
+            }
+        }
+        violations
+    }
+}
+
+/// is_blocked returns true if one of the violations blocks the spend.
+pub fn is_blocked(violations: &[Violation]) -> bool {
+    violations.iter().any(|v| v.action == Action::Block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+        [per_transaction_limit]
+        amount = 1000
+
+        [daily_limit]
+        amount = 5000
+        action = "warn"
+
+        [destinations]
+        addresses = ["allowed"]
+        address_book = true
+
+        [max_feerate]
+        feerate = 100
+        action = "warn"
+
+        [memo]
+    "#;
+
+    fn spend<'a>() -> Spend<'a> {
+        Spend {
+            amount: 1000,
+            unvaulted_amount: 2000,
+            unvaulted_last_day: 3000,
+            destinations: vec!["allowed".to_string(), "whitelisted".to_string()],
+            address_book_whitelist: vec!["whitelisted".to_string()].into_iter().collect(),
+            feerate: 100,
+            memo: "rent",
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let policy: SpendingPolicy = toml::from_str(POLICY).unwrap();
+        assert_eq!(policy.per_transaction_limit.unwrap().action, Action::Block);
+        assert_eq!(policy.daily_limit.unwrap().action, Action::Warn);
+        assert_eq!(policy.memo.unwrap().action, Action::Block);
+
+        let policy: SpendingPolicy = toml::from_str("").unwrap();
+        assert!(policy.check(&spend()).is_empty());
+
+        assert!(toml::from_str::<SpendingPolicy>("[unknown_rule]").is_err());
+        assert!(toml::from_str::<SpendingPolicy>("[memo]\nactions = \"warn\"").is_err());
+        assert!(toml::from_str::<SpendingPolicy>("[memo]\naction = \"ignore\"").is_err());
+    }
+
+    #[test]
+    fn from_file() {
+        assert!(matches!(
+            SpendingPolicy::from_file(Path::new("/nonexistent/policy.toml")),
+            Err(PolicyError::ReadingFile(_))
+        ));
+    }
+
+    #[test]
+    fn check() {
+        let policy: SpendingPolicy = toml::from_str(POLICY).unwrap();
+        assert!(policy.check(&spend()).is_empty());
+
+        let mut s = spend();
+        s.amount = 1001;
+        let violations = policy.check(&s);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].reason.starts_with("Per transaction limit"));
+        assert!(is_blocked(&violations));
+
+        let mut s = spend();
+        s.unvaulted_last_day = 3001;
+        let violations = policy.check(&s);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].reason.starts_with("Daily limit"));
+        assert!(!is_blocked(&violations));
+
+        let mut s = spend();
+        s.destinations.push("unknown".to_string());
+        let violations = policy.check(&s);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].reason.contains("unknown"));
+        assert!(is_blocked(&violations));
+
+        let mut s = spend();
+        s.feerate = 101;
+        assert!(!is_blocked(&policy.check(&s)));
+        assert_eq!(policy.check(&s).len(), 1);
+
+        let mut s = spend();
+        s.memo = "  ";
+        assert!(is_blocked(&policy.check(&s)));
+    }
+
+    #[test]
+    fn address_book_whitelist() {
+        let policy: SpendingPolicy =
+            toml::from_str("[destinations]\naddresses = [\"allowed\"]").unwrap();
+        let violations = policy.check(&spend());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].reason.contains("whitelisted"));
+    }
+}
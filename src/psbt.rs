@@ -0,0 +1,696 @@
+use bitcoin::{
+    base64,
+    consensus::encode,
+    secp256k1::{Message, Secp256k1, Signature, Verification},
+    util::{
+        bip143::SigHashCache,
+        bip32::ExtendedPubKey,
+        psbt::{Input, PartiallySignedTransaction as Psbt},
+    },
+    PublicKey, SigHashType,
+};
+
+use crate::revault::TransactionKind;
+
+/// BIP174 magic bytes prefixing every binary serialized psbt.
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+/// Format is the way a psbt is written to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// BIP174 binary serialization, the `.psbt` files.
+    Binary,
+    /// Base64 encoding of the binary serialization in a text file.
+    Base64,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Binary => "psbt",
+            Self::Base64 => "txt",
+        }
+    }
+}
+
+/// to_bytes serializes the psbt according to the given format.
+pub fn to_bytes(psbt: &Psbt, format: Format) -> Vec<u8> {
+    let bytes = encode::serialize(psbt);
+    match format {
+        Format::Binary => bytes,
+        Format::Base64 => base64::encode(&bytes).into_bytes(),
+    }
+}
+
+/// from_bytes deserializes a psbt either from its binary serialization or from
+/// its base64 encoding, surrounding whitespaces are ignored.
+pub fn from_bytes(bytes: &[u8]) -> Option<Psbt> {
+    if bytes.starts_with(PSBT_MAGIC) {
+        return encode::deserialize(bytes).ok();
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    base64::decode(text.trim())
+        .ok()
+        .and_then(|bytes| encode::deserialize(&bytes).ok())
+}
+
+/// fee returns the difference between the amounts of the inputs and the outputs,
+/// if every input has its witness utxo.
+pub fn fee(psbt: &Psbt) -> Option<u64> {
+    let mut input_amount = 0;
+    for input in &psbt.inputs {
+        input_amount += input.witness_utxo.as_ref()?.value;
+    }
+    let output_amount: u64 = psbt
+        .global
+        .unsigned_tx
+        .output
+        .iter()
+        .map(|output| output.value)
+        .sum();
+    input_amount.checked_sub(output_amount)
+}
+
+/// file_name returns a name for the psbt file of the given transaction.
+pub fn file_name(psbt: &Psbt, transaction_kind: &TransactionKind, format: Format) -> String {
+    format!(
+        "{}-{}.{}",
+        transaction_kind.to_string().replace(' ', "_"),
+        psbt.global.unsigned_tx.txid(),
+        format.extension()
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The imported psbt does not spend and create the same coins than the original one.
+    UnsignedTxMismatch,
+    /// The imported psbt has no partial signature that the original psbt did not have.
+    NoNewSignature,
+    /// None of the new partial signatures were made by one of our keys.
+    NoSignatureFromOurKeys,
+    /// None of our keys is known, the signature of the psbt cannot be attributed.
+    NoKeys,
+    WrongSigHashType {
+        input: usize,
+        expected: SigHashType,
+        found: u32,
+    },
+    MissingWitnessUtxo(usize),
+    MissingWitnessScript(usize),
+    InvalidSignature {
+        input: usize,
+        pubkey: PublicKey,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnsignedTxMismatch => write!(
+                f,
+                "The imported PSBT transaction is not the transaction to sign"
+            ),
+            Self::NoNewSignature => write!(f, "The imported PSBT has no new signature"),
+            Self::NoSignatureFromOurKeys => {
+                write!(f, "The imported PSBT is not signed by one of your keys")
+            }
+            Self::NoKeys => write!(
+                f,
+                "None of your keys is configured, the PSBT signature cannot be checked"
+            ),
+            Self::WrongSigHashType {
+                input,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Input {} is signed with sighash type {}, expected {}",
+                input,
+                sighash_type_str(*found),
+                sighash_type_str(expected.as_u32()),
+            ),
+            Self::MissingWitnessUtxo(input) => write!(
+                f,
+                "Input {} has no witness utxo, its signature cannot be checked",
+                input
+            ),
+            Self::MissingWitnessScript(input) => write!(
+                f,
+                "Input {} has no witness script, its signature cannot be checked",
+                input
+            ),
+            Self::InvalidSignature { input, pubkey } => write!(
+                f,
+                "Input {} has an invalid signature for key {}",
+                input, pubkey
+            ),
+        }
+    }
+}
+
+fn sighash_type_str(sighash_type: u32) -> String {
+    match sighash_type {
+        0x01 => "ALL".to_string(),
+        0x02 => "NONE".to_string(),
+        0x03 => "SINGLE".to_string(),
+        0x81 => "ALL|ANYONECANPAY".to_string(),
+        0x82 => "NONE|ANYONECANPAY".to_string(),
+        0x83 => "SINGLE|ANYONECANPAY".to_string(),
+        n => format!("{:#x}", n),
+    }
+}
+
+/// check_signed_psbt verifies that the signed psbt is the original psbt with at least one new
+/// valid signature made with the sighash type required by the Revault protocol for the given
+/// transaction kind. One of the new signatures must be made by a key derived from our_keys.
+pub fn check_signed_psbt(
+    original: &Psbt,
+    signed: &Psbt,
+    transaction_kind: &TransactionKind,
+    our_keys: &[ExtendedPubKey],
+) -> Result<(), Error> {
+    if our_keys.is_empty() {
+        return Err(Error::NoKeys);
+    }
+    if !check_signatures(original, signed, transaction_kind, our_keys)? {
+        return Err(Error::NoSignatureFromOurKeys);
+    }
+    Ok(())
+}
+
+/// check_cosigned_psbt verifies the psbt signed by a co-signer: it is the original psbt
+/// with at least one new valid signature, whoever made it.
+pub fn check_cosigned_psbt(
+    original: &Psbt,
+    signed: &Psbt,
+    transaction_kind: &TransactionKind,
+) -> Result<(), Error> {
+    check_signatures(original, signed, transaction_kind, &[]).map(|_| ())
+}
+
+/// check_signatures verifies the new signatures of the signed psbt and returns true if
+/// one of them is made by a key derived from our_keys.
+fn check_signatures(
+    original: &Psbt,
+    signed: &Psbt,
+    transaction_kind: &TransactionKind,
+    our_keys: &[ExtendedPubKey],
+) -> Result<bool, Error> {
+    if signed.global.unsigned_tx != original.global.unsigned_tx
+        || signed.inputs.len() != original.inputs.len()
+    {
+        return Err(Error::UnsignedTxMismatch);
+    }
+
+    let secp = Secp256k1::verification_only();
+    let expected = transaction_kind.sighash_type();
+    let mut cache = SigHashCache::new(&signed.global.unsigned_tx);
+    let mut new_signatures = 0;
+    let mut signed_by_us = false;
+
+    for (index, input) in signed.inputs.iter().enumerate() {
+        if let Some(sighash_type) = input.sighash_type {
+            if sighash_type != expected {
+                return Err(Error::WrongSigHashType {
+                    input: index,
+                    expected,
+                    found: sighash_type.as_u32(),
+                });
+            }
+        }
+
+        for (pubkey, sig) in &input.partial_sigs {
+            if original.inputs[index].partial_sigs.get(pubkey) == Some(sig) {
+                continue;
+            }
+            new_signatures += 1;
+
+            let (sighash_type, der) = sig.split_last().ok_or(Error::InvalidSignature {
+                input: index,
+                pubkey: *pubkey,
+            })?;
+            if *sighash_type as u32 != expected.as_u32() {
+                return Err(Error::WrongSigHashType {
+                    input: index,
+                    expected,
+                    found: *sighash_type as u32,
+                });
+            }
+
+            let witness_script = input
+                .witness_script
+                .as_ref()
+                .ok_or(Error::MissingWitnessScript(index))?;
+            let value = input
+                .witness_utxo
+                .as_ref()
+                .ok_or(Error::MissingWitnessUtxo(index))?
+                .value;
+            let sighash = cache.signature_hash(index, witness_script, value, expected);
+            let msg = Message::from_slice(&sighash[..]).expect("sighash is 32 bytes");
+            Signature::from_der(der)
+                .and_then(|signature| secp.verify(&msg, &signature, &pubkey.key))
+                .map_err(|_| Error::InvalidSignature {
+                    input: index,
+                    pubkey: *pubkey,
+                })?;
+
+            if is_our_key(&secp, our_keys, input, pubkey) {
+                signed_by_us = true;
+            }
+        }
+    }
+
+    if new_signatures == 0 {
+        return Err(Error::NoNewSignature);
+    }
+
+    Ok(signed_by_us)
+}
+
+/// is_our_key checks with the psbt input derivation paths if the public key is derived
+/// from one of the given extended public keys.
+fn is_our_key<C: Verification>(
+    secp: &Secp256k1<C>,
+    our_keys: &[ExtendedPubKey],
+    input: &Input,
+    pubkey: &PublicKey,
+) -> bool {
+    if let Some((fingerprint, path)) = input.hd_keypaths.get(pubkey) {
+        for xpub in our_keys {
+            if xpub.fingerprint() == *fingerprint {
+                if let Ok(derived) = xpub.derive_pub(secp, path) {
+                    if derived.public_key == *pubkey {
+                        return true;
+                    }
+                }
+            }
+            // Revault descriptors use xpub/* keys, the derivation path may only
+            // be known relatively to our extended public key.
+            if let Some(child) = path.as_ref().last() {
+                if let Ok(derived) = xpub.derive_pub(secp, &vec![*child]) {
+                    if derived.public_key == *pubkey {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+    false
+}
+
+/// signed_by returns true if every input of the psbt has a partial signature made
+/// by a key derived from the extended public key.
+pub fn signed_by(psbt: &Psbt, xpub: &ExtendedPubKey) -> bool {
+    let secp = Secp256k1::verification_only();
+    psbt.inputs.iter().all(|input| {
+        input
+            .partial_sigs
+            .keys()
+            .any(|pubkey| is_our_key(&secp, std::slice::from_ref(xpub), input, pubkey))
+    })
+}
+
+/// Conflict is reported when two imported psbts have a different signature for
+/// the same key of the same input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub input: usize,
+    pub pubkey: PublicKey,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Input {} has two different signatures for key {}, the first one is kept",
+            self.input, self.pubkey
+        )
+    }
+}
+
+/// combine merges the partial signatures of the signed psbt into the combined psbt.
+/// A signature conflicting with one already present is not merged and is reported instead.
+pub fn combine(combined: &mut Psbt, signed: &Psbt) -> Result<Vec<Conflict>, Error> {
+    if signed.global.unsigned_tx != combined.global.unsigned_tx
+        || signed.inputs.len() != combined.inputs.len()
+    {
+        return Err(Error::UnsignedTxMismatch);
+    }
+
+    let mut conflicts = Vec::new();
+    for (index, (input, signed_input)) in combined
+        .inputs
+        .iter_mut()
+        .zip(signed.inputs.iter())
+        .enumerate()
+    {
+        for (pubkey, sig) in &signed_input.partial_sigs {
+            match input.partial_sigs.get(pubkey) {
+                Some(existing) if existing != sig => conflicts.push(Conflict {
+                    input: index,
+                    pubkey: *pubkey,
+                }),
+                Some(_) => {}
+                None => {
+                    input.partial_sigs.insert(*pubkey, sig.clone());
+                    // Keep the derivation path so that the signature can still
+                    // be matched with our keys.
+                    if let Some(keypath) = signed_input.hd_keypaths.get(pubkey) {
+                        input
+                            .hd_keypaths
+                            .entry(*pubkey)
+                            .or_insert_with(|| keypath.clone());
+                    }
+                }
+            }
+        }
+        if input.sighash_type.is_none() {
+            input.sighash_type = signed_input.sighash_type;
+        }
+    }
+    Ok(conflicts)
+}
+
+/// signature_counts returns the number of partial signatures of each psbt input.
+pub fn signature_counts(psbt: &Psbt) -> Vec<usize> {
+    psbt.inputs
+        .iter()
+        .map(|input| input.partial_sigs.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{
+        secp256k1::{All, SecretKey},
+        util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey},
+        Network, OutPoint, Script, Transaction, TxIn, TxOut,
+    };
+
+    const VALUE: u64 = 100_000;
+
+    fn xpriv(seed: u8) -> ExtendedPrivKey {
+        ExtendedPrivKey::new_master(Network::Testnet, &[seed; 32]).unwrap()
+    }
+
+    fn psbt(inputs: u32, output_value: u64) -> Psbt {
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: (0..inputs)
+                .map(|vout| TxIn {
+                    previous_output: OutPoint::new(OutPoint::null().txid, vout),
+                    script_sig: Script::new(),
+                    sequence: 0xffff_ffff,
+                    witness: Vec::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: output_value,
+                script_pubkey: Script::new(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for input in &mut psbt.inputs {
+            input.witness_utxo = Some(TxOut {
+                value: VALUE,
+                script_pubkey: Script::new(),
+            });
+            input.witness_script = Some(Script::from(vec![0x51]));
+        }
+        psbt
+    }
+
+    /// sign adds to every input the signature of the key derived from the master
+    /// key at m/index, with its derivation path.
+    fn sign(psbt: &mut Psbt, master: &ExtendedPrivKey, index: u32, sighash_type: SigHashType) {
+        let secp = Secp256k1::new();
+        let path = DerivationPath::from(vec![ChildNumber::from_normal_idx(index).unwrap()]);
+        let child = master.derive_priv(&secp, &path).unwrap();
+        let pubkey = child.private_key.public_key(&secp);
+        let fingerprint = master.fingerprint(&secp);
+        for index in 0..psbt.inputs.len() {
+            let sig = signature(&secp, psbt, index, &child.private_key.key, sighash_type);
+            let input = &mut psbt.inputs[index];
+            input.partial_sigs.insert(pubkey, sig);
+            input
+                .hd_keypaths
+                .insert(pubkey, (fingerprint, path.clone()));
+        }
+    }
+
+    fn signature(
+        secp: &Secp256k1<All>,
+        psbt: &Psbt,
+        index: usize,
+        key: &SecretKey,
+        sighash_type: SigHashType,
+    ) -> Vec<u8> {
+        let input = &psbt.inputs[index];
+        let sighash = SigHashCache::new(&psbt.global.unsigned_tx).signature_hash(
+            index,
+            input.witness_script.as_ref().unwrap(),
+            input.witness_utxo.as_ref().unwrap().value,
+            sighash_type,
+        );
+        let msg = Message::from_slice(&sighash[..]).unwrap();
+        let mut sig = secp.sign(&msg, key).serialize_der().to_vec();
+        sig.push(sighash_type.as_u32() as u8);
+        sig
+    }
+
+    fn xpub(master: &ExtendedPrivKey) -> ExtendedPubKey {
+        ExtendedPubKey::from_private(&Secp256k1::new(), master)
+    }
+
+    #[test]
+    fn check_signed_psbt_signed_by_us() {
+        let master = xpriv(1);
+        let original = psbt(2, 90_000);
+        let mut signed = original.clone();
+        sign(&mut signed, &master, 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &signed,
+                &TransactionKind::Spend,
+                &[xpub(&master)]
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_wrong_txid() {
+        let master = xpriv(1);
+        let original = psbt(1, 90_000);
+        let mut signed = psbt(1, 80_000);
+        sign(&mut signed, &master, 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &signed,
+                &TransactionKind::Spend,
+                &[xpub(&master)]
+            ),
+            Err(Error::UnsignedTxMismatch)
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_extra_or_missing_inputs() {
+        let master = xpriv(1);
+        let original = psbt(2, 90_000);
+
+        let mut extra = psbt(3, 90_000);
+        sign(&mut extra, &master, 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(&original, &extra, &TransactionKind::Spend, &[xpub(&master)]),
+            Err(Error::UnsignedTxMismatch)
+        );
+
+        let mut missing = original.clone();
+        sign(&mut missing, &master, 0, SigHashType::All);
+        missing.inputs.pop();
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &missing,
+                &TransactionKind::Spend,
+                &[xpub(&master)]
+            ),
+            Err(Error::UnsignedTxMismatch)
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_bad_signature() {
+        let master = xpriv(1);
+        let original = psbt(1, 90_000);
+        let mut signed = original.clone();
+        sign(&mut signed, &master, 0, SigHashType::All);
+        // The signature of another transaction is not valid for this one.
+        let mut other = psbt(1, 80_000);
+        sign(&mut other, &master, 0, SigHashType::All);
+        let (pubkey, sig) = other.inputs[0].partial_sigs.iter().next().unwrap();
+        signed.inputs[0].partial_sigs.insert(*pubkey, sig.clone());
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &signed,
+                &TransactionKind::Spend,
+                &[xpub(&master)]
+            ),
+            Err(Error::InvalidSignature {
+                input: 0,
+                pubkey: *pubkey
+            })
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_wrong_sighash_type() {
+        let master = xpriv(1);
+        let original = psbt(1, 90_000);
+        let mut signed = original.clone();
+        sign(&mut signed, &master, 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &signed,
+                &TransactionKind::Emergency,
+                &[xpub(&master)]
+            ),
+            Err(Error::WrongSigHashType {
+                input: 0,
+                expected: SigHashType::AllPlusAnyoneCanPay,
+                found: SigHashType::All.as_u32(),
+            })
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_foreign_key() {
+        let original = psbt(1, 90_000);
+        let mut signed = original.clone();
+        sign(&mut signed, &xpriv(2), 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &signed,
+                &TransactionKind::Spend,
+                &[xpub(&xpriv(1))]
+            ),
+            Err(Error::NoSignatureFromOurKeys)
+        );
+        // A co-signer signature is accepted.
+        assert_eq!(
+            check_cosigned_psbt(&original, &signed, &TransactionKind::Spend),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_empty_key_set() {
+        let original = psbt(1, 90_000);
+        let mut signed = original.clone();
+        sign(&mut signed, &xpriv(1), 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(&original, &signed, &TransactionKind::Spend, &[]),
+            Err(Error::NoKeys)
+        );
+    }
+
+    #[test]
+    fn check_signed_psbt_no_new_signature() {
+        let master = xpriv(1);
+        let mut original = psbt(1, 90_000);
+        sign(&mut original, &master, 0, SigHashType::All);
+        assert_eq!(
+            check_signed_psbt(
+                &original,
+                &original.clone(),
+                &TransactionKind::Spend,
+                &[xpub(&master)]
+            ),
+            Err(Error::NoNewSignature)
+        );
+    }
+
+    #[test]
+    fn is_our_key_derivation() {
+        let secp = Secp256k1::new();
+        let master = xpriv(1);
+        let mut signed = psbt(1, 90_000);
+        sign(&mut signed, &master, 3, SigHashType::All);
+        let input = &signed.inputs[0];
+        let pubkey = *input.partial_sigs.keys().next().unwrap();
+
+        // Derived from the master key with the full path.
+        assert!(is_our_key(&secp, &[xpub(&master)], input, &pubkey));
+        // Not derived from a foreign key.
+        assert!(!is_our_key(&secp, &[xpub(&xpriv(2))], input, &pubkey));
+        // No key to match.
+        assert!(!is_our_key(&secp, &[], input, &pubkey));
+
+        // Only the last step of the path is known relatively to the account xpub.
+        let account_path = DerivationPath::from(vec![ChildNumber::from_normal_idx(7).unwrap()]);
+        let account = master.derive_priv(&secp, &account_path).unwrap();
+        let mut relative = psbt(1, 90_000);
+        sign(&mut relative, &account, 3, SigHashType::All);
+        let input = &mut relative.inputs[0];
+        let pubkey = *input.partial_sigs.keys().next().unwrap();
+        let path = DerivationPath::from(vec![
+            ChildNumber::from_normal_idx(7).unwrap(),
+            ChildNumber::from_normal_idx(3).unwrap(),
+        ]);
+        input
+            .hd_keypaths
+            .insert(pubkey, (master.fingerprint(&secp), path));
+        assert!(is_our_key(&secp, &[xpub(&account)], input, &pubkey));
+
+        // Without derivation path, the key cannot be attributed.
+        input.hd_keypaths.clear();
+        assert!(!is_our_key(&secp, &[xpub(&account)], input, &pubkey));
+    }
+
+    #[test]
+    fn combine_signatures() {
+        let original = psbt(2, 90_000);
+        let mut first = original.clone();
+        sign(&mut first, &xpriv(1), 0, SigHashType::All);
+        let mut second = original.clone();
+        sign(&mut second, &xpriv(2), 0, SigHashType::All);
+
+        let mut combined = original.clone();
+        assert_eq!(combine(&mut combined, &first), Ok(Vec::new()));
+        assert_eq!(signature_counts(&combined), vec![1, 1]);
+        // Importing the same psbt again brings no new signature.
+        assert_eq!(combine(&mut combined, &first), Ok(Vec::new()));
+        assert_eq!(signature_counts(&combined), vec![1, 1]);
+        assert_eq!(combine(&mut combined, &second), Ok(Vec::new()));
+        assert_eq!(signature_counts(&combined), vec![2, 2]);
+
+        // Another signature for the same key is a conflict and is not merged.
+        let mut conflicting = original.clone();
+        sign(&mut conflicting, &xpriv(1), 0, SigHashType::All);
+        let (pubkey, _) = conflicting.inputs[0].partial_sigs.iter().next().unwrap();
+        let pubkey = *pubkey;
+        conflicting.inputs[0]
+            .partial_sigs
+            .insert(pubkey, vec![0x30, 0x01]);
+        assert_eq!(
+            combine(&mut combined, &conflicting),
+            Ok(vec![Conflict { input: 0, pubkey }])
+        );
+        assert_eq!(signature_counts(&combined), vec![2, 2]);
+
+        assert_eq!(
+            combine(&mut combined, &psbt(1, 90_000)),
+            Err(Error::UnsignedTxMismatch)
+        );
+    }
+}
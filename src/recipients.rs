@@ -0,0 +1,317 @@
+use std::str::FromStr;
+
+use bitcoin::{Address, Amount, Denomination};
+use serde::{Deserialize, Serialize};
+
+/// Header of the recipients csv files, amounts are in BTC.
+const HEADER: [&str; 3] = ["address", "amount", "label"];
+
+/// Recipient is a row of a recipients csv file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recipient {
+    pub address: String,
+    /// Amount in BTC as written in the file.
+    pub amount: String,
+    pub label: Option<String>,
+}
+
+/// Rejected is a row of a recipients csv file that cannot be imported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejected {
+    /// Line number in the file, starting at 1.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Import is the result of the import of a recipients csv file.
+#[derive(Debug, Clone, Default)]
+pub struct Import {
+    /// Recipients with the line number of their row.
+    pub recipients: Vec<(usize, Recipient)>,
+    pub rejected: Vec<Rejected>,
+}
+
+/// from_csv parses the rows `address,amount[,label]` of a csv file, the header
+/// row and the empty rows are skipped. Each row is checked on its own, a rejected
+/// row does not prevent the import of the others.
+pub fn from_csv(content: &str) -> Import {
+    let mut import = Import::default();
+    let mut lines = content.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        // A quoted field may span several lines.
+        let mut row = line.to_string();
+        let mut fields = split_fields(&row);
+        while fields.is_none() {
+            match lines.next() {
+                Some((_, next)) => {
+                    row.push('\n');
+                    row.push_str(next);
+                    fields = split_fields(&row);
+                }
+                None => break,
+            }
+        }
+        let fields = match fields {
+            Some(fields) => fields,
+            None => {
+                import.rejected.push(Rejected {
+                    line: line_number,
+                    reason: "unterminated quoted field".to_string(),
+                });
+                continue;
+            }
+        };
+        if import.recipients.is_empty()
+            && import.rejected.is_empty()
+            && fields
+                .first()
+                .map(|f| f.eq_ignore_ascii_case(HEADER[0]))
+                .unwrap_or(false)
+        {
+            continue;
+        }
+        match check_fields(fields) {
+            Ok(recipient) => import.recipients.push((line_number, recipient)),
+            Err(reason) => import.rejected.push(Rejected {
+                line: line_number,
+                reason,
+            }),
+        }
+    }
+    import
+}
+
+fn check_fields(mut fields: Vec<String>) -> Result<Recipient, String> {
+    if fields.len() < 2 || fields.len() > 3 {
+        return Err(format!(
+            "expected address, amount and optional label, found {} fields",
+            fields.len()
+        ));
+    }
+    let label = if fields.len() == 3 {
+        Some(fields.remove(2)).filter(|label| !label.is_empty())
+    } else {
+        None
+    };
+    let amount = fields.remove(1);
+    let address = fields.remove(0);
+
+    Address::from_str(&address).map_err(|_| format!("invalid address {}", address))?;
+    let sat = Amount::from_str_in(&amount, Denomination::Bitcoin)
+        .map_err(|_| format!("invalid amount {}", amount))?;
+    if sat.as_sat() == 0 {
+        return Err("amount must be greater than zero".to_string());
+    }
+
+    Ok(Recipient {
+        address,
+        amount,
+        label,
+    })
+}
+
+/// to_csv writes the recipients with a header row.
+pub fn to_csv(recipients: &[Recipient]) -> String {
+    let mut content = HEADER.join(",");
+    content.push('\n');
+    for recipient in recipients {
+        content.push_str(&format!(
+            "{},{},{}\n",
+            quote_field(&recipient.address),
+            quote_field(&recipient.amount),
+            recipient
+                .label
+                .as_ref()
+                .map(|label| quote_field(label))
+                .unwrap_or_default()
+        ));
+    }
+    content
+}
+
+/// split_fields splits a csv row, fields may be double quoted and
+/// double quotes escaped by doubling them. Unquoted fields are trimmed,
+/// the spaces around the quotes of the quoted ones are ignored.
+fn split_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut was_quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+                was_quoted = true;
+            }
+            ',' if !quoted => {
+                fields.push(end_field(&mut field, was_quoted));
+                was_quoted = false;
+            }
+            c if was_quoted && !quoted && c.is_whitespace() => {}
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(end_field(&mut field, was_quoted));
+    Some(fields)
+}
+
+fn end_field(field: &mut String, was_quoted: bool) -> String {
+    let value = if was_quoted {
+        field.clone()
+    } else {
+        field.trim().to_string()
+    };
+    field.clear();
+    value
+}
+
+/// quote_field quotes the field if it would not be read back as is.
+pub fn quote_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') || field.trim() != field {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+    const OTHER_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    fn fields(fields: &[&str]) -> Option<Vec<String>> {
+        Some(fields.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(split_fields("a, b ,c"), fields(&["a", "b", "c"]));
+        assert_eq!(split_fields("a,,"), fields(&["a", "", ""]));
+        assert_eq!(split_fields("\"a,b\",c"), fields(&["a,b", "c"]));
+        assert_eq!(split_fields("\" a \" , b"), fields(&[" a ", "b"]));
+        assert_eq!(split_fields("\"say \"\"hi\"\"\""), fields(&["say \"hi\""]));
+        assert_eq!(split_fields("\"\""), fields(&[""]));
+        // Quotes inside an unquoted field are kept.
+        assert_eq!(split_fields("a\"b"), fields(&["a\"b"]));
+        assert_eq!(split_fields("\"a,b"), None);
+        assert_eq!(split_fields("a,\"b\"\""), None);
+    }
+
+    #[test]
+    fn import() {
+        let content = format!(
+            "Address,Amount,Label\n\
+             {a},0.5,rent\n\
+             \n\
+             {b},1\n\
+             {a},0,zero\n\
+             notanaddress,1\n\
+             {a},abc\n\
+             {a}\n\
+             {a},1,label,extra\n\
+             \"{b}\",\"0.1\",\"multi\n\
+             line, \"\"label\"\"\"\n\
+             {a},2,\"unterminated\n",
+            a = ADDRESS,
+            b = OTHER_ADDRESS
+        );
+        let import = from_csv(&content);
+        assert_eq!(
+            import.recipients,
+            vec![
+                (
+                    2,
+                    Recipient {
+                        address: ADDRESS.to_string(),
+                        amount: "0.5".to_string(),
+                        label: Some("rent".to_string()),
+                    }
+                ),
+                (
+                    4,
+                    Recipient {
+                        address: OTHER_ADDRESS.to_string(),
+                        amount: "1".to_string(),
+                        label: None,
+                    }
+                ),
+                (
+                    10,
+                    Recipient {
+                        address: OTHER_ADDRESS.to_string(),
+                        amount: "0.1".to_string(),
+                        label: Some("multi\nline, \"label\"".to_string()),
+                    }
+                ),
+            ]
+        );
+        let rejected: Vec<usize> = import.rejected.iter().map(|r| r.line).collect();
+        assert_eq!(rejected, vec![5, 6, 7, 8, 9, 12]);
+        assert_eq!(
+            import.rejected.last().unwrap().reason,
+            "unterminated quoted field"
+        );
+    }
+
+    #[test]
+    fn header_only_first_row() {
+        let import = from_csv(&format!("{},1\naddress,amount\n", ADDRESS));
+        assert_eq!(import.recipients.len(), 1);
+        assert_eq!(import.rejected.len(), 1);
+        assert_eq!(import.rejected[0].line, 2);
+    }
+
+    #[test]
+    fn quote() {
+        assert_eq!(quote_field("plain"), "plain");
+        assert_eq!(quote_field("a,b"), "\"a,b\"");
+        assert_eq!(quote_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote_field(" padded "), "\" padded \"");
+        assert_eq!(quote_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn round_trip() {
+        let recipients = vec![
+            Recipient {
+                address: ADDRESS.to_string(),
+                amount: "0.00000001".to_string(),
+                label: Some(" Alice, \"Bob\"\nand Carol ".to_string()),
+            },
+            Recipient {
+                address: OTHER_ADDRESS.to_string(),
+                amount: "21".to_string(),
+                label: None,
+            },
+        ];
+        let import = from_csv(&to_csv(&recipients));
+        assert!(import.rejected.is_empty());
+        let imported: Vec<Recipient> = import.recipients.into_iter().map(|(_, r)| r).collect();
+        assert_eq!(imported, recipients);
+    }
+}
@@ -1,3 +1,5 @@
+use bitcoin::SigHashType;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Role {
     Manager,
@@ -29,3 +31,33 @@ pub enum TransactionKind {
     Cancel,
     Spend,
 }
+
+impl TransactionKind {
+    /// Revocation transactions are signed with ALL|ANYONECANPAY in order to let
+    /// the watchtowers and the stakeholders bump their feerate by adding inputs,
+    /// the others are signed with ALL.
+    pub fn sighash_type(&self) -> SigHashType {
+        match self {
+            Self::Emergency | Self::EmergencyUnvault | Self::Cancel => {
+                SigHashType::AllPlusAnyoneCanPay
+            }
+            Self::Unvault | Self::Spend => SigHashType::All,
+        }
+    }
+}
+
+impl std::fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Emergency => "emergency",
+                Self::EmergencyUnvault => "emergency unvault",
+                Self::Unvault => "unvault",
+                Self::Cancel => "cancel",
+                Self::Spend => "spend",
+            }
+        )
+    }
+}
@@ -1,11 +1,19 @@
+use bitcoin::util::bip32::ExtendedPubKey;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub bitcoind_config: BitcoindConfig,
     /// An optional custom data directory
     pub data_dir: Option<PathBuf>,
+    /// Present if the daemon is run by a stakeholder
+    pub stakeholder_config: Option<StakeholderConfig>,
+    /// Present if the daemon is run by a manager
+    pub manager_config: Option<ManagerConfig>,
+    /// Descriptors of the Revault scripts
+    pub scripts_config: Option<ScriptsConfig>,
 }
 
 impl Config {
@@ -23,17 +31,50 @@ impl Config {
         Ok(config)
     }
 
-    /// default revaultd socket path is .revault/bitcoin/revaultd_rpc
-    pub fn socket_path(&self) -> Result<PathBuf, ConfigError> {
+    /// default revaultd network data directory is .revault/bitcoin
+    pub fn network_datadir(&self) -> Result<PathBuf, ConfigError> {
         let mut path = if let Some(ref datadir) = self.data_dir {
             datadir.clone()
         } else {
             default_datadir()?
         };
         path.push(&self.bitcoind_config.network.to_string());
+        Ok(path)
+    }
+
+    /// default revaultd socket path is .revault/bitcoin/revaultd_rpc
+    pub fn socket_path(&self) -> Result<PathBuf, ConfigError> {
+        let mut path = self.network_datadir()?;
         path.push("revaultd_rpc");
         Ok(path)
     }
+
+    /// spend_signers returns the keys of the managers and the number of them required
+    /// to sign a spend transaction, read from the threshold of the unvault descriptor
+    /// including our manager key. Without it, only the signature of our manager key is
+    /// known to be required.
+    pub fn spend_signers(&self) -> (Vec<ExtendedPubKey>, usize) {
+        let our_key = match &self.manager_config {
+            Some(cfg) => cfg.xpub,
+            None => return (Vec::new(), 0),
+        };
+        self.scripts_config
+            .as_ref()
+            .and_then(|scripts| {
+                key_thresholds(&scripts.unvault_descriptor)
+                    .into_iter()
+                    .find(|(_, keys)| keys.contains(&our_key))
+            })
+            .map(|(threshold, keys)| (keys, threshold))
+            .unwrap_or_else(|| (vec![our_key], 1))
+    }
+
+    /// default GUI data directory is .revault/bitcoin/gui
+    pub fn gui_datadir(&self) -> Result<PathBuf, ConfigError> {
+        let mut path = self.network_datadir()?;
+        path.push("gui");
+        Ok(path)
+    }
 }
 
 impl std::default::Default for Config {
@@ -41,8 +82,101 @@ impl std::default::Default for Config {
         Config {
             bitcoind_config: BitcoindConfig::default(),
             data_dir: None,
+            stakeholder_config: None,
+            manager_config: None,
+            scripts_config: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StakeholderConfig {
+    #[serde(with = "bitcoin_xpub")]
+    pub xpub: ExtendedPubKey,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManagerConfig {
+    #[serde(with = "bitcoin_xpub")]
+    pub xpub: ExtendedPubKey,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptsConfig {
+    /// Descriptor of the unvault output: the stakeholders or, after the
+    /// relative timelock, a threshold of the managers and the cosigners.
+    pub unvault_descriptor: String,
+}
+
+/// key_thresholds returns the `multi` and `thresh` fragments of the descriptor
+/// whose every argument is a single extended public key, with their threshold.
+fn key_thresholds(descriptor: &str) -> Vec<(usize, Vec<ExtendedPubKey>)> {
+    let mut thresholds = Vec::new();
+    for (start, _) in descriptor.match_indices('(') {
+        let fragment = &descriptor[..start];
+        if !fragment.ends_with("multi") && !fragment.ends_with("thresh") {
+            continue;
+        }
+        let args = match fragment_args(&descriptor[start + 1..]) {
+            Some(args) => args,
+            None => continue,
+        };
+        let threshold = match args.first().and_then(|k| k.trim().parse::<usize>().ok()) {
+            Some(k) => k,
+            None => continue,
+        };
+        let keys: Option<Vec<ExtendedPubKey>> = args[1..]
+            .iter()
+            .map(|arg| {
+                let mut keys = xpubs(arg);
+                if keys.len() == 1 {
+                    keys.pop()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if let Some(keys) = keys {
+            if threshold > 0 && threshold <= keys.len() {
+                thresholds.push((threshold, keys));
+            }
         }
     }
+    thresholds
+}
+
+/// fragment_args splits the arguments of a fragment, the string starts after its
+/// opening parenthesis.
+fn fragment_args(s: &str) -> Option<Vec<&str>> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ']' => depth -= 1,
+            ')' if depth == 0 => {
+                args.push(&s[start..i]);
+                return Some(args);
+            }
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// xpubs returns the extended public keys written in the descriptor expression.
+fn xpubs(expression: &str) -> Vec<ExtendedPubKey> {
+    expression
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| token.starts_with("xpub") || token.starts_with("tpub"))
+        .filter_map(|token| ExtendedPubKey::from_str(token).ok())
+        .collect()
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -73,6 +207,20 @@ mod bitcoin_network {
     }
 }
 
+mod bitcoin_xpub {
+    use bitcoin::util::bip32::ExtendedPubKey;
+    use serde::{self, Deserialize, Deserializer};
+    use std::str::FromStr;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ExtendedPubKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        ExtendedPubKey::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 // From github.com/re-vault/revaultd:
 // Get the absolute path to the revault configuration folder.
 ///
@@ -128,3 +276,74 @@ impl std::fmt::Display for ConfigError {
 }
 
 impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: [&str; 5] = [
+        "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+        "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+        "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+        "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+        "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+    ];
+
+    fn key(i: usize) -> ExtendedPubKey {
+        ExtendedPubKey::from_str(KEYS[i]).unwrap()
+    }
+
+    /// unvault_descriptor is paid to the stakeholder, or after 6 blocks
+    /// to 2 of the 3 managers.
+    fn unvault_descriptor() -> String {
+        format!(
+            "wsh(andor(thresh(2,pk({}/*),s:pk([3442193e/48h]{}/*),s:pk({}/*)),older(6),\
+             multi(1,[deadbeef/48h]{}/*)))#abcdefgh",
+            KEYS[0], KEYS[1], KEYS[2], KEYS[3],
+        )
+    }
+
+    fn config(manager: usize) -> Config {
+        let mut config = Config::default();
+        config.manager_config = Some(ManagerConfig { xpub: key(manager) });
+        config.scripts_config = Some(ScriptsConfig {
+            unvault_descriptor: unvault_descriptor(),
+        });
+        config
+    }
+
+    #[test]
+    fn parse_key_thresholds() {
+        assert_eq!(
+            key_thresholds(&unvault_descriptor()),
+            vec![(2, vec![key(0), key(1), key(2)]), (1, vec![key(3)])]
+        );
+        assert_eq!(
+            key_thresholds(&format!("wsh(sortedmulti(1,{},{}))", KEYS[0], KEYS[1])),
+            vec![(1, vec![key(0), key(1)])]
+        );
+        // Thresholds of sub policies, invalid thresholds and unclosed fragments are skipped.
+        assert!(key_thresholds(&format!(
+            "wsh(thresh(1,and_v(v:pk({}),pk({}))))",
+            KEYS[0], KEYS[1]
+        ))
+        .is_empty());
+        assert!(key_thresholds(&format!("wsh(multi(3,{},{}))", KEYS[0], KEYS[1])).is_empty());
+        assert!(key_thresholds(&format!("wsh(multi(1,{},{}", KEYS[0], KEYS[1])).is_empty());
+        assert!(key_thresholds("wsh(multi(x,notakey))").is_empty());
+    }
+
+    #[test]
+    fn spend_signers() {
+        assert_eq!(config(1).spend_signers(), (vec![key(0), key(1), key(2)], 2));
+
+        // Our key is not in the descriptor, only our signature is known to be required.
+        assert_eq!(config(4).spend_signers(), (vec![key(4)], 1));
+
+        let mut config = config(1);
+        config.scripts_config = None;
+        assert_eq!(config.spend_signers(), (vec![key(1)], 1));
+        config.manager_config = None;
+        assert_eq!(config.spend_signers(), (Vec::new(), 0));
+    }
+}
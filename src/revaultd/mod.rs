@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::path::Path;
 use std::process::Command;
@@ -14,8 +15,8 @@ pub mod model;
 use client::Client;
 use config::Config;
 use model::{
-    DepositAddress, RevocationTransactions, UnvaultTransaction, Vault, VaultStatus,
-    VaultTransactions,
+    DepositAddress, FeeEstimates, ListSpendTransactionsResponse, RevocationTransactions,
+    SpendTransaction, UnvaultTransaction, Vault, VaultStatus, VaultTransactions,
 };
 
 #[derive(Debug, Clone)]
@@ -76,6 +77,10 @@ impl RevaultD {
         self.config.bitcoind_config.network
     }
 
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Generic call function for RPC calls.
     fn call<T: Serialize + Debug, U: DeserializeOwned + Debug>(
         &self,
@@ -161,6 +166,55 @@ impl RevaultD {
             self.call("unvaulttx", Some(vec![outpoint, &unvault_tx]))?;
         Ok(())
     }
+
+    /// get_fee_estimates returns the fee rates estimated by the bitcoind of revaultd.
+    pub fn get_fee_estimates(&self) -> Result<FeeEstimates, RevaultDError> {
+        self.call("getfeeestimates", Option::<Request>::None)
+    }
+
+    /// get_spend_tx asks revaultd to build a spend transaction of the given vaults
+    /// paying the outputs amounts in satoshis to their addresses.
+    pub fn get_spend_tx(
+        &self,
+        outpoints: &[String],
+        outputs: &HashMap<String, u64>,
+        feerate: u64,
+    ) -> Result<SpendTransaction, RevaultDError> {
+        self.call(
+            "getspendtx",
+            Some(serde_json::json!([outpoints, outputs, feerate])),
+        )
+    }
+
+    /// update_spend_tx stores the signatures of the spend transaction.
+    pub fn update_spend_tx(&self, spend_tx: &Psbt) -> Result<(), RevaultDError> {
+        let spend_tx = base64::encode(&consensus::serialize(spend_tx));
+        let _res: serde_json::value::Value = self.call("updatespendtx", Some(vec![spend_tx]))?;
+        Ok(())
+    }
+
+    /// list_spend_txs returns the spend transactions stored by revaultd.
+    pub fn list_spend_txs(&self) -> Result<ListSpendTransactionsResponse, RevaultDError> {
+        self.call("listspendtxs", Option::<Request>::None)
+    }
+
+    /// delete_spend_tx removes a spend transaction that was not announced yet.
+    pub fn delete_spend_tx(&self, spend_txid: &str) -> Result<(), RevaultDError> {
+        let _res: serde_json::value::Value = self.call("delspendtx", Some(vec![spend_txid]))?;
+        Ok(())
+    }
+
+    /// set_spend_tx announces the spend transaction, the vaults are then unvaulted.
+    pub fn set_spend_tx(&self, spend_txid: &str) -> Result<(), RevaultDError> {
+        let _res: serde_json::value::Value = self.call("setspendtx", Some(vec![spend_txid]))?;
+        Ok(())
+    }
+
+    /// revault broadcasts the cancel transaction of the unvaulted vault.
+    pub fn revault(&self, outpoint: &str) -> Result<(), RevaultDError> {
+        let _res: serde_json::value::Value = self.call("revault", Some(vec![outpoint]))?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
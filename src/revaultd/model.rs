@@ -75,6 +75,45 @@ pub enum VaultStatus {
     Spent,
 }
 
+impl VaultStatus {
+    pub const ALL: [VaultStatus; 13] = [
+        VaultStatus::Unconfirmed,
+        VaultStatus::Funded,
+        VaultStatus::Secured,
+        VaultStatus::Active,
+        VaultStatus::Unvaulting,
+        VaultStatus::Unvaulted,
+        VaultStatus::Canceling,
+        VaultStatus::Canceled,
+        VaultStatus::EmergencyVaulting,
+        VaultStatus::EmergencyVaulted,
+        VaultStatus::Spendable,
+        VaultStatus::Spending,
+        VaultStatus::Spent,
+    ];
+
+    /// is_spendable returns true if the vault can be an input of a spend transaction.
+    pub fn is_spendable(&self) -> bool {
+        matches!(self, Self::Active | Self::Spendable)
+    }
+
+    /// spend_restriction explains why a vault with this status cannot be spent.
+    pub fn spend_restriction(&self) -> Option<&'static str> {
+        match self {
+            Self::Active | Self::Spendable => None,
+            Self::Unconfirmed => Some("The deposit is not confirmed yet"),
+            Self::Funded => Some("The revocation transactions are not signed yet"),
+            Self::Secured => Some("The unvault transaction is not signed yet"),
+            Self::Unvaulting | Self::Unvaulted => Some("The vault is already being unvaulted"),
+            Self::Canceling | Self::Canceled => Some("The vault is canceled"),
+            Self::EmergencyVaulting | Self::EmergencyVaulted => {
+                Some("The vault was sent to the emergency deep vault")
+            }
+            Self::Spending | Self::Spent => Some("The vault is already spent"),
+        }
+    }
+}
+
 impl std::fmt::Display for VaultStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -167,6 +206,63 @@ pub struct UnvaultTransaction {
     pub unvault_tx: PartiallySignedTransaction,
 }
 
+/// getfeeestimates response, fee rates are in sat/vbyte.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeEstimates {
+    pub fast: u64,
+    pub normal: u64,
+    pub slow: u64,
+}
+
+impl FeeEstimates {
+    pub fn feerate(&self, priority: FeePriority) -> u64 {
+        match priority {
+            FeePriority::Fast => self.fast,
+            FeePriority::Normal => self.normal,
+            FeePriority::Slow => self.slow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FeePriority {
+    Fast,
+    Normal,
+    Slow,
+}
+
+impl std::fmt::Display for FeePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Fast => write!(f, "Fast"),
+            Self::Normal => write!(f, "Normal"),
+            Self::Slow => write!(f, "Slow"),
+        }
+    }
+}
+
+/// getspendtx response
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpendTransaction {
+    #[serde(with = "bitcoin_psbt")]
+    pub spend_tx: PartiallySignedTransaction,
+}
+
+/// Spend transaction stored by revaultd with the signatures collected so far.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StoredSpendTransaction {
+    /// Outpoints of the vaults spent by the transaction.
+    pub deposit_outpoints: Vec<String>,
+    #[serde(with = "bitcoin_psbt")]
+    pub psbt: PartiallySignedTransaction,
+}
+
+/// listspendtxs response
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListSpendTransactionsResponse {
+    pub spend_txs: Vec<StoredSpendTransaction>,
+}
+
 mod bitcoin_transaction {
     use bitcoin::{consensus::encode, hashes::hex::FromHex, Transaction};
     use serde::{self, Deserialize, Deserializer};
@@ -0,0 +1,84 @@
+use bitcoin::util::{bip32::ExtendedPubKey, psbt::PartiallySignedTransaction as Psbt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    psbt::{self, Format},
+    revault::TransactionKind,
+};
+
+/// Name of the directory of the signed revocation transactions in the GUI data directory.
+pub const DIR_NAME: &str = "revocation";
+
+/// SignedRevocation keeps the revocation transactions of a deposit signed so far,
+/// so that they do not have to be signed again with the device if the GUI stops
+/// before revaultd received all of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedRevocation {
+    pub outpoint: String,
+    /// Base64 encoded signed transactions.
+    pub emergency_tx: Option<String>,
+    pub emergency_unvault_tx: Option<String>,
+    pub cancel_tx: Option<String>,
+}
+
+impl SignedRevocation {
+    pub fn new(outpoint: String) -> Self {
+        Self {
+            outpoint,
+            emergency_tx: None,
+            emergency_unvault_tx: None,
+            cancel_tx: None,
+        }
+    }
+
+    fn field(&mut self, transaction_kind: &TransactionKind) -> Option<&mut Option<String>> {
+        match transaction_kind {
+            TransactionKind::Emergency => Some(&mut self.emergency_tx),
+            TransactionKind::EmergencyUnvault => Some(&mut self.emergency_unvault_tx),
+            TransactionKind::Cancel => Some(&mut self.cancel_tx),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, transaction_kind: &TransactionKind, psbt: &Psbt) {
+        if let Some(field) = self.field(transaction_kind) {
+            *field = Some(
+                String::from_utf8(psbt::to_bytes(psbt, Format::Base64))
+                    .expect("base64 is valid utf8"),
+            );
+        }
+    }
+
+    /// signed returns the saved signed version of the given revocation transaction,
+    /// if it was saved and is still the original transaction revaultd asks to sign.
+    /// The saved transaction is checked like a freshly signed one before it is
+    /// returned.
+    pub fn signed(
+        &self,
+        transaction_kind: &TransactionKind,
+        original: &Psbt,
+        our_keys: &[ExtendedPubKey],
+    ) -> Result<Option<Psbt>, psbt::Error> {
+        let saved = match transaction_kind {
+            TransactionKind::Emergency => &self.emergency_tx,
+            TransactionKind::EmergencyUnvault => &self.emergency_unvault_tx,
+            TransactionKind::Cancel => &self.cancel_tx,
+            _ => return Ok(None),
+        };
+        let saved = match saved
+            .as_ref()
+            .and_then(|saved| psbt::from_bytes(saved.as_bytes()))
+            .filter(|saved| saved.global.unsigned_tx == original.global.unsigned_tx)
+        {
+            Some(saved) => saved,
+            None => return Ok(None),
+        };
+        psbt::check_signed_psbt(original, &saved, transaction_kind, our_keys)?;
+        Ok(Some(saved))
+    }
+
+    /// file_name returns the name of the file of the deposit with the given outpoint.
+    pub fn file_name(outpoint: &str) -> String {
+        format!("{}.json", outpoint.replace(':', "_"))
+    }
+}
@@ -0,0 +1,265 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use bitcoin::{base64, consensus, util::psbt::PartiallySignedTransaction as Psbt, Network};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::{io::AsyncWriteExt, process::Command};
+use tracing::{debug, error};
+
+use super::{Descriptors, Device, SignerError};
+use crate::psbt;
+
+/// ExternalSigner drives signing devices through a command following the
+/// HWI command line protocol used by bitcoind external signers:
+/// `enumerate`, `getdescriptors` and `signtx`, answers are json on stdout.
+#[derive(Debug, Clone)]
+pub struct ExternalSigner {
+    path: PathBuf,
+    network: Network,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignTxResponse {
+    psbt: String,
+}
+
+impl ExternalSigner {
+    pub fn new(path: PathBuf, network: Network) -> Self {
+        Self { path, network }
+    }
+
+    fn chain(&self) -> &'static str {
+        match self.network {
+            Network::Bitcoin => "main",
+            Network::Testnet => "test",
+            Network::Regtest => "regtest",
+        }
+    }
+
+    /// call runs the signer command with the given arguments and
+    /// writes the optional input to its stdin. The command is awaited
+    /// without blocking the executor, devices may wait for the user.
+    async fn call<T: DeserializeOwned>(
+        &self,
+        args: &[&str],
+        input: Option<&str>,
+    ) -> Result<T, SignerError> {
+        debug!("signer call: {:?}", args);
+        let mut child = Command::new(&self.path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                SignerError::IOError(format!(
+                    "Failed to launch {}: {}",
+                    self.path.display(),
+                    e.to_string()
+                ))
+            })?;
+
+        // stdin is dropped once written, so that the signer reads its end.
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(input) = input {
+                stdin
+                    .write_all(input.as_bytes())
+                    .await
+                    .map_err(|e| SignerError::IOError(e.to_string()))?;
+            }
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| SignerError::IOError(e.to_string()))?;
+
+        let value: serde_json::Value = serde_json::from_slice(&output.stdout).map_err(|_| {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            error!("signer call failed: {}", stderr);
+            if output.status.success() {
+                SignerError::UnexpectedError("Signer answer is not json".to_string())
+            } else {
+                SignerError::DeviceError(stderr)
+            }
+        })?;
+
+        if let Some(e) = value.get("error") {
+            return Err(SignerError::DeviceError(
+                e.as_str()
+                    .map(|e| e.to_string())
+                    .unwrap_or_else(|| e.to_string()),
+            ));
+        }
+
+        serde_json::from_value(value).map_err(|e| SignerError::UnexpectedError(e.to_string()))
+    }
+
+    /// enumerate lists the connected devices.
+    pub async fn enumerate(&self) -> Result<Vec<Device>, SignerError> {
+        self.call(&["--chain", self.chain(), "enumerate"], None)
+            .await
+    }
+
+    /// get_descriptors returns the descriptors of the device standard accounts.
+    pub async fn get_descriptors(&self, fingerprint: &str) -> Result<Descriptors, SignerError> {
+        self.call(
+            &[
+                &format!("--fingerprint={}", fingerprint),
+                "--chain",
+                self.chain(),
+                "getdescriptors",
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// sign_tx asks the device to sign the psbt and returns the signed psbt.
+    /// The psbt is given through stdin, it may be too large for the command line.
+    pub async fn sign_tx(&self, fingerprint: &str, psbt: &Psbt) -> Result<Psbt, SignerError> {
+        let res: SignTxResponse = self
+            .call(
+                &[
+                    "--stdin",
+                    &format!("--fingerprint={}", fingerprint),
+                    "--chain",
+                    self.chain(),
+                ],
+                Some(&format!(
+                    "signtx {}",
+                    base64::encode(&consensus::serialize(psbt))
+                )),
+            )
+            .await?;
+        psbt::from_bytes(res.psbt.as_bytes()).ok_or_else(|| {
+            SignerError::UnexpectedError("Signer returned an invalid psbt".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revault::TransactionKind;
+    use bitcoin::{
+        blockdata::{opcodes, script::Builder},
+        secp256k1::Secp256k1,
+        util::bip32::{DerivationPath, ExtendedPrivKey, ExtendedPubKey},
+        OutPoint, Script, Transaction, TxIn, TxOut,
+    };
+    use std::str::FromStr;
+
+    /// Master key of the stand-in signer, the BIP32 test vector 1.
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+    const FINGERPRINT: &str = "3442193e";
+
+    fn signer(network: Network) -> ExternalSigner {
+        ExternalSigner::new(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("contrib/stand-in-signer.py"),
+            network,
+        )
+    }
+
+    #[tokio::test]
+    async fn enumerate() {
+        let devices = signer(Network::Testnet).enumerate().await.unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].fingerprint.as_deref(), Some(FINGERPRINT));
+        assert_eq!(devices[0].error, None);
+        assert_eq!(devices[1].fingerprint, None);
+        assert_eq!(devices[1].error.as_deref(), Some("Device is locked"));
+    }
+
+    #[tokio::test]
+    async fn get_descriptors() {
+        let descriptors = signer(Network::Testnet)
+            .get_descriptors(FINGERPRINT)
+            .await
+            .unwrap();
+        assert_eq!(descriptors.receive.len(), 1);
+        assert_eq!(descriptors.internal.len(), 1);
+
+        match signer(Network::Testnet).get_descriptors("deadbeef").await {
+            Err(SignerError::DeviceError(e)) => assert_eq!(e, "Device not found"),
+            res => panic!("unexpected answer: {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_tx() {
+        let secp = Secp256k1::new();
+        let master = ExtendedPrivKey::from_str(XPRV).unwrap();
+        let path = DerivationPath::from_str("m/0'/3").unwrap();
+        let key = master.derive_priv(&secp, &path).unwrap();
+        let pubkey = key.private_key.public_key(&secp);
+
+        let mut psbt = Psbt::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(OutPoint::null().txid, 0),
+                script_sig: Script::new(),
+                sequence: 0xffff_ffff,
+                witness: Vec::new(),
+            }],
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: Script::new(),
+            }],
+        })
+        .unwrap();
+        let input = &mut psbt.inputs[0];
+        input.witness_utxo = Some(TxOut {
+            value: 20_000,
+            script_pubkey: Script::new(),
+        });
+        input.witness_script = Some(
+            Builder::new()
+                .push_key(&pubkey)
+                .push_opcode(opcodes::all::OP_CHECKSIG)
+                .into_script(),
+        );
+        input
+            .hd_keypaths
+            .insert(pubkey, (master.fingerprint(&secp), path));
+
+        let signed = signer(Network::Testnet)
+            .sign_tx(FINGERPRINT, &psbt)
+            .await
+            .unwrap();
+        assert!(signed.inputs[0].partial_sigs.contains_key(&pubkey));
+        // Revault keys are the account extended public keys of the devices.
+        let account = master
+            .derive_priv(&secp, &DerivationPath::from_str("m/0'").unwrap())
+            .unwrap();
+        let our_keys = [ExtendedPubKey::from_private(&secp, &account)];
+        psbt::check_signed_psbt(&psbt, &signed, &TransactionKind::Spend, &our_keys).unwrap();
+
+        // A psbt given back as it is, like a device not signing, is rejected.
+        assert!(psbt::check_signed_psbt(&psbt, &psbt, &TransactionKind::Spend, &our_keys).is_err());
+    }
+
+    #[tokio::test]
+    async fn signer_errors() {
+        // The stand-in signer only knows the test chain.
+        match signer(Network::Bitcoin).enumerate().await {
+            Err(SignerError::DeviceError(e)) => assert_eq!(e, "Unsupported chain"),
+            res => panic!("unexpected answer: {:?}", res),
+        }
+
+        match signer(Network::Testnet)
+            .call::<serde_json::Value>(&["--chain", "test", "unknown"], None)
+            .await
+        {
+            Err(SignerError::DeviceError(e)) => assert!(e.contains("Unknown command")),
+            res => panic!("unexpected answer: {:?}", res),
+        }
+
+        let missing = ExternalSigner::new(PathBuf::from("/nonexistent/signer"), Network::Testnet);
+        match missing.enumerate().await {
+            Err(SignerError::IOError(_)) => {}
+            res => panic!("unexpected answer: {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+pub mod external;
+pub mod software;
+
+use std::str::FromStr;
+
+use bitcoin::util::bip32::{ChildNumber, DerivationPath, ExtendedPubKey, Fingerprint};
+use serde::Deserialize;
+
+#[derive(Debug, Clone)]
+pub enum SignerError {
+    /// The signer command could not be run.
+    IOError(String),
+    /// The signer or the device returned an error.
+    DeviceError(String),
+    /// The signing key cannot be loaded or used.
+    KeyError(String),
+    /// The signer answer does not follow the protocol.
+    UnexpectedError(String),
+}
+
+impl std::fmt::Display for SignerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "Signer io error: {}", e),
+            Self::DeviceError(e) => write!(f, "Signer device error: {}", e),
+            Self::KeyError(e) => write!(f, "Signer key error: {}", e),
+            Self::UnexpectedError(e) => write!(f, "Signer unexpected error: {}", e),
+        }
+    }
+}
+
+/// Device is a signing device detected by the signer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Device {
+    #[serde(rename = "type")]
+    pub device_type: String,
+    pub model: String,
+    /// Fingerprint of the device master key, it is unknown while
+    /// the device is locked.
+    pub fingerprint: Option<String>,
+    /// Reason why the device cannot be used yet.
+    pub error: Option<String>,
+}
+
+/// Descriptors are the output descriptors of the device standard accounts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Descriptors {
+    pub receive: Vec<String>,
+    pub internal: Vec<String>,
+}
+
+impl Descriptors {
+    /// holds_key returns true if the extended public key is the one of the device with the
+    /// given master fingerprint: either its master key or the key of one of the descriptors,
+    /// at the derivation path given by the key origin.
+    pub fn holds_key(&self, master_fingerprint: &Fingerprint, xpub: &ExtendedPubKey) -> bool {
+        if xpub.depth == 0 {
+            return xpub.fingerprint() == *master_fingerprint;
+        }
+        self.receive
+            .iter()
+            .chain(self.internal.iter())
+            .flat_map(|desc| key_origins(desc))
+            .any(|origin| {
+                origin.fingerprint == *master_fingerprint
+                    && origin.path.as_ref().len() == xpub.depth as usize
+                    && origin.path.as_ref().last() == Some(&xpub.child_number)
+                    && origin.xpub.public_key == xpub.public_key
+                    && origin.xpub.chain_code == xpub.chain_code
+            })
+    }
+}
+
+/// KeyOrigin is an extended public key of a descriptor with the fingerprint of
+/// the master key and the derivation path it was derived with.
+#[derive(Debug, Clone, PartialEq)]
+struct KeyOrigin {
+    fingerprint: Fingerprint,
+    path: DerivationPath,
+    xpub: ExtendedPubKey,
+}
+
+/// key_origins returns the keys of the descriptor written with their origin,
+/// like `[d34db33f/84h/1h/0h]tpub.../0/*`.
+fn key_origins(descriptor: &str) -> Vec<KeyOrigin> {
+    descriptor
+        .split('[')
+        .skip(1)
+        .filter_map(|key| {
+            let (origin, rest) = key.split_at(key.find(']')?);
+            let mut steps = origin.split('/');
+            let fingerprint = Fingerprint::from_str(steps.next()?).ok()?;
+            let path: Option<Vec<ChildNumber>> = steps.map(child_number).collect();
+            let xpub = rest[1..]
+                .split(|c| c == '/' || c == ')' || c == ',')
+                .next()?;
+            Some(KeyOrigin {
+                fingerprint,
+                path: DerivationPath::from(path?),
+                xpub: ExtendedPubKey::from_str(xpub).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// child_number parses a derivation step, hardened ones end with `h` or `'`.
+fn child_number(step: &str) -> Option<ChildNumber> {
+    match step.strip_suffix(|c| c == 'h' || c == '\'') {
+        Some(index) => ChildNumber::from_hardened_idx(index.parse().ok()?).ok(),
+        None => ChildNumber::from_normal_idx(step.parse().ok()?).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Master and m/0h extended public keys of the BIP32 test vector 1.
+    const MASTER: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    const ACCOUNT: &str = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw";
+
+    fn descriptors(origin: &str) -> Descriptors {
+        Descriptors {
+            receive: vec![format!("wpkh([{}]{}/0/*)#abcdefgh", origin, ACCOUNT)],
+            internal: vec![format!("wpkh([{}]{}/1/*)#abcdefgh", origin, ACCOUNT)],
+        }
+    }
+
+    #[test]
+    fn parse_key_origins() {
+        let origins = key_origins(&format!("wpkh([3442193e/0']{}/0/*)", ACCOUNT));
+        assert_eq!(
+            origins,
+            vec![KeyOrigin {
+                fingerprint: Fingerprint::from_str("3442193e").unwrap(),
+                path: DerivationPath::from_str("m/0'").unwrap(),
+                xpub: ExtendedPubKey::from_str(ACCOUNT).unwrap(),
+            }]
+        );
+        let multi = format!(
+            "wsh(multi(2,[3442193e/48h/1h/0h/2h]{},[deadbeef/0]{}))",
+            ACCOUNT, MASTER
+        );
+        assert_eq!(key_origins(&multi).len(), 2);
+        // Keys without origin or with an invalid one are skipped.
+        assert!(key_origins(&format!("wpkh({}/0/*)", ACCOUNT)).is_empty());
+        assert!(key_origins(&format!("wpkh([3442193e/0x]{}/0/*)", ACCOUNT)).is_empty());
+        assert!(key_origins("wpkh([3442193e/0h]notakey/0/*)").is_empty());
+    }
+
+    #[test]
+    fn holds_key() {
+        let fingerprint = Fingerprint::from_str("3442193e").unwrap();
+        let master = ExtendedPubKey::from_str(MASTER).unwrap();
+        let account = ExtendedPubKey::from_str(ACCOUNT).unwrap();
+
+        assert!(descriptors("3442193e/0h").holds_key(&fingerprint, &account));
+        assert!(descriptors("3442193e/0h").holds_key(&fingerprint, &master));
+        // Another device.
+        let other = Fingerprint::from_str("deadbeef").unwrap();
+        assert!(!descriptors("3442193e/0h").holds_key(&other, &account));
+        assert!(!descriptors("3442193e/0h").holds_key(&other, &master));
+        // The same key announced at another derivation path.
+        assert!(!descriptors("3442193e/1h").holds_key(&fingerprint, &account));
+        assert!(!descriptors("3442193e/0h/0h").holds_key(&fingerprint, &account));
+    }
+}
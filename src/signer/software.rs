@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    secp256k1::{Message, Secp256k1, Signing},
+    util::{
+        bip143::SigHashCache,
+        bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey, Fingerprint},
+        psbt::PartiallySignedTransaction as Psbt,
+    },
+    Network, PrivateKey, PublicKey, SigHashType,
+};
+
+use super::SignerError;
+
+/// SoftwareSigner signs psbts with a key held in memory.
+/// It is meant for test networks only and is refused on mainnet.
+#[derive(Debug, Clone)]
+pub struct SoftwareSigner {
+    xprv: ExtendedPrivKey,
+}
+
+impl SoftwareSigner {
+    /// new loads either an extended private key or a BIP39 mnemonic.
+    pub fn new(key: &str, network: Network) -> Result<Self, SignerError> {
+        if network == Network::Bitcoin {
+            return Err(SignerError::KeyError(
+                "Software signer cannot be used on mainnet".to_string(),
+            ));
+        }
+
+        let key = key.trim();
+        let xprv = if key.split_whitespace().count() > 1 {
+            let mnemonic = bip39::Mnemonic::parse(key)
+                .map_err(|e| SignerError::KeyError(format!("Invalid mnemonic: {}", e)))?;
+            ExtendedPrivKey::new_master(network, &mnemonic.to_seed(""))
+                .map_err(|e| SignerError::KeyError(e.to_string()))?
+        } else {
+            let xprv = ExtendedPrivKey::from_str(key)
+                .map_err(|e| SignerError::KeyError(format!("Invalid xprv: {}", e)))?;
+            // Testnet and regtest extended keys share the same encoding.
+            if xprv.network == Network::Bitcoin {
+                return Err(SignerError::KeyError(
+                    "Software signer cannot be used with a mainnet key".to_string(),
+                ));
+            }
+            xprv
+        };
+        Ok(Self { xprv })
+    }
+
+    /// derive returns the private key of the given public key, if it is derived from our
+    /// extended private key along the given path.
+    fn derive<C: Signing>(
+        &self,
+        secp: &Secp256k1<C>,
+        pubkey: &PublicKey,
+        fingerprint: &Fingerprint,
+        path: &DerivationPath,
+    ) -> Option<PrivateKey> {
+        let mut candidates: Vec<Vec<ChildNumber>> = Vec::new();
+        if self.xprv.fingerprint(secp) == *fingerprint {
+            candidates.push(path.as_ref().to_vec());
+        }
+        // Revault descriptors use xpub/* keys, the derivation path may only
+        // be known relatively to our extended key.
+        if let Some(child) = path.as_ref().last() {
+            candidates.push(vec![*child]);
+        }
+        candidates.into_iter().find_map(|path| {
+            let derived = self.xprv.derive_priv(secp, &path).ok()?;
+            if ExtendedPubKey::from_private(secp, &derived).public_key == *pubkey {
+                Some(derived.private_key)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// sign adds a signature to every psbt input spending a coin of one of our keys,
+    /// found with the inputs BIP32 derivations. An error is returned if none is found.
+    pub fn sign(&self, psbt: &Psbt, sighash_type: SigHashType) -> Result<Psbt, SignerError> {
+        let secp = Secp256k1::new();
+        let mut signed = psbt.clone();
+        let mut cache = SigHashCache::new(&psbt.global.unsigned_tx);
+        let mut signatures = 0;
+
+        for (index, input) in signed.inputs.iter_mut().enumerate() {
+            let keys: Vec<(PublicKey, PrivateKey)> = input
+                .hd_keypaths
+                .iter()
+                .filter_map(|(pubkey, (fingerprint, path))| {
+                    self.derive(&secp, pubkey, fingerprint, path)
+                        .map(|privkey| (*pubkey, privkey))
+                })
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+
+            let witness_script = input.witness_script.as_ref().ok_or_else(|| {
+                SignerError::UnexpectedError(format!("Input {} has no witness script", index))
+            })?;
+            let value = input
+                .witness_utxo
+                .as_ref()
+                .ok_or_else(|| {
+                    SignerError::UnexpectedError(format!("Input {} has no witness utxo", index))
+                })?
+                .value;
+            let sighash = cache.signature_hash(index, witness_script, value, sighash_type);
+            let msg = Message::from_slice(&sighash[..]).expect("sighash is 32 bytes");
+
+            for (pubkey, privkey) in keys {
+                let mut sig = secp.sign(&msg, &privkey.key).serialize_der().to_vec();
+                sig.push(sighash_type.as_u32() as u8);
+                input.partial_sigs.insert(pubkey, sig);
+                signatures += 1;
+            }
+        }
+
+        if signatures == 0 {
+            return Err(SignerError::KeyError(
+                "No input of the transaction can be signed with this key".to_string(),
+            ));
+        }
+        Ok(signed)
+    }
+}
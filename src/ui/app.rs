@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use copypasta::{ClipboardContext, ClipboardProvider};
 use iced::{executor, Application, Color, Command, Element, Settings, Subscription};
@@ -8,17 +9,31 @@ use tracing::error;
 
 use super::menu::Menu;
 use super::message::Message;
+use super::session::Session;
 use super::state::{
+    notification::{self, POLL_INTERVAL_SECS},
     ChargingState, DepositState, HistoryState, InstallingState, ManagerHomeState,
-    ManagerNetworkState, ManagerSendState, StakeholderACKFundsState, StakeholderHomeState,
+    ManagerNetworkState, ManagerSendState, MonitorState, NotificationsState,
+    StakeholderACKFundsState, StakeholderDelegateState, StakeholderHomeState,
     StakeholderNetworkState, State,
 };
 
-use crate::{conversion::Converter, revault::Role, revaultd::RevaultD, ui::view::Context};
+use crate::{
+    conversion::Converter,
+    notification::NotificationCenter,
+    policy::SpendingPolicy,
+    revault::Role,
+    revaultd::RevaultD,
+    signer::{software::SoftwareSigner, SignerError},
+    ui::view::Context,
+};
 
 pub struct App {
     config: Config,
     revaultd: Option<Arc<RevaultD>>,
+    session: Option<Arc<Session>>,
+    /// Events of the vaults and the kinds of them raising desktop notifications.
+    notifications: NotificationCenter,
     state: Box<dyn State>,
     clipboard: ClipboardContext,
     context: Context,
@@ -34,26 +49,41 @@ impl App {
         self.context.role = role;
         self.context.menu = menu;
         let revaultd = self.revaultd.clone().unwrap();
+        let session = self.session.clone().unwrap();
         self.state = match self.context.role {
             Role::Manager => match self.context.menu {
                 Menu::Deposit => DepositState::new(revaultd).into(),
                 Menu::Home => ManagerHomeState::new(revaultd).into(),
-                Menu::History => HistoryState::new(revaultd).into(),
+                Menu::History => HistoryState::new(revaultd, session).into(),
                 Menu::Network => ManagerNetworkState::new(revaultd).into(),
-                Menu::Send => ManagerSendState::new(revaultd).into(),
+                Menu::Send => ManagerSendState::new(revaultd, session).into(),
+                Menu::Notifications => NotificationsState::new(self.notifications.clone()).into(),
                 _ => unreachable!(),
             },
             Role::Stakeholder => match self.context.menu {
                 Menu::Deposit => DepositState::new(revaultd).into(),
                 Menu::Home => StakeholderHomeState::new(revaultd).into(),
-                Menu::History => HistoryState::new(revaultd).into(),
+                Menu::History => HistoryState::new(revaultd, session).into(),
                 Menu::Network => StakeholderNetworkState::new(revaultd).into(),
-                Menu::ACKFunds => StakeholderACKFundsState::new(revaultd).into(),
+                Menu::ACKFunds => StakeholderACKFundsState::new(revaultd, session).into(),
+                Menu::Delegate => StakeholderDelegateState::new(revaultd, session).into(),
+                Menu::Monitor => MonitorState::new(revaultd).into(),
+                Menu::Notifications => NotificationsState::new(self.notifications.clone()).into(),
                 _ => unreachable!(),
             },
         };
         self.state.load()
     }
+
+    /// notifications_changed updates the unread counter of the sidebar and the
+    /// notification center if it is displayed.
+    fn notifications_changed(&mut self) -> Command<Message> {
+        self.context.unread_notifications = self.notifications.unread();
+        self.state.update(
+            &self.context,
+            Message::NotificationCenter(self.notifications.clone()),
+        )
+    }
 }
 
 impl Application for App {
@@ -72,6 +102,8 @@ impl Application for App {
                 config,
                 state: std::boxed::Box::new(state),
                 revaultd: None,
+                session: None,
+                notifications: NotificationCenter::default(),
                 clipboard: ClipboardContext::new().expect("Failed to get clipboard provider"),
                 context: Context::default(),
             },
@@ -80,6 +112,13 @@ impl Application for App {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        if self.revaultd.is_some() {
+            return Subscription::batch(vec![
+                self.state.subscription(),
+                iced::time::every(Duration::from_secs(POLL_INTERVAL_SECS))
+                    .map(Message::NotificationTick),
+            ]);
+        }
         self.state.subscription()
     }
 
@@ -102,8 +141,106 @@ impl Application for App {
                     Menu::Home,
                 );
                 self.context.network_up = true;
+                let mut session = Session::new(revaultd.config().clone());
+                if let Some(dir) = &self.config.psbt_dir {
+                    session.psbt_dir = dir.clone();
+                }
+                session.signer_path = self.config.signer_path.clone();
+                if let Some(percent) = self.config.fee_warning_percent {
+                    session.fee_warning_percent = percent;
+                }
+                if let Some(path) = &self.config.signer_key_path {
+                    match std::fs::read_to_string(path)
+                        .map_err(|e| SignerError::IOError(e.to_string()))
+                        .and_then(|key| SoftwareSigner::new(&key, revaultd.network()))
+                    {
+                        Ok(signer) => session.software_signer = Some(signer),
+                        Err(e) => {
+                            error!("Failed to load software signer: {}", e);
+                            session.software_signer_error = Some(e.to_string());
+                        }
+                    }
+                }
+                if let Some(path) = &self.config.spending_policy_path {
+                    match SpendingPolicy::from_file(path) {
+                        Ok(policy) => session.spending_policy = Some(policy),
+                        Err(e) => {
+                            error!("Failed to load spending policy: {}", e);
+                            session.spending_policy_error = Some(e.to_string());
+                        }
+                    }
+                }
+                let cmd = Command::batch(vec![
+                    notification::load_preferences(session.data_dir.clone()),
+                    notification::poll(revaultd.clone()),
+                ]);
+                self.session = Some(Arc::new(session));
                 self.revaultd = Some(revaultd);
-                self.load_state(Role::Manager, Menu::Home)
+                Command::batch(vec![cmd, self.load_state(Role::Manager, Menu::Home)])
+            }
+            Message::NotificationTick(_) => match &self.revaultd {
+                Some(revaultd) => notification::poll(revaultd.clone()),
+                None => Command::none(),
+            },
+            Message::NotificationVaults(res) => {
+                let events = match res {
+                    Ok(vaults) => self.notifications.update_vaults(&vaults),
+                    Err(e) => self
+                        .notifications
+                        .disconnected(&e.to_string())
+                        .into_iter()
+                        .collect(),
+                };
+                let role = self.context.role;
+                let mut cmds: Vec<Command<Message>> = events
+                    .into_iter()
+                    .filter(|event| self.notifications.preferences.enabled(role, event.kind))
+                    .map(notification::show)
+                    .collect();
+                cmds.push(self.notifications_changed());
+                Command::batch(cmds)
+            }
+            Message::NotificationShown(res) => {
+                if let Err(e) = res {
+                    error!("Failed to show desktop notification: {}", e);
+                }
+                Command::none()
+            }
+            Message::NotificationPreferences(res) => {
+                match res {
+                    Ok(preferences) => self.notifications.preferences = preferences,
+                    Err(e) => error!("Failed to load notification preferences: {}", e),
+                }
+                self.notifications_changed()
+            }
+            Message::NotificationPreferencesSaved(res) => {
+                if let Err(e) = res {
+                    error!("Failed to save notification preferences: {}", e);
+                }
+                Command::none()
+            }
+            Message::ToggleNotification(kind) => {
+                let role = self.context.role;
+                self.notifications.preferences.toggle(role, kind);
+                let data_dir = match &self.session {
+                    Some(session) => session.data_dir.clone(),
+                    None => return Command::none(),
+                };
+                Command::batch(vec![
+                    notification::save_preferences(
+                        data_dir,
+                        self.notifications.preferences.clone(),
+                    ),
+                    self.notifications_changed(),
+                ])
+            }
+            Message::ToggleNotificationRead(id) => {
+                self.notifications.toggle_read(id);
+                self.notifications_changed()
+            }
+            Message::MarkNotificationsRead => {
+                self.notifications.mark_all_read();
+                self.notifications_changed()
             }
             Message::ChangeRole(role) => self.load_state(role, self.context.menu.to_owned()),
             Message::Menu(menu) => self.load_state(self.context.role, menu),
@@ -113,7 +250,7 @@ impl Application for App {
                 };
                 Command::none()
             }
-            _ => self.state.update(message),
+            _ => self.state.update(&self.context, message),
         }
     }
 
@@ -131,5 +268,15 @@ impl Application for App {
 pub struct Config {
     pub revaultd_config_path: Option<PathBuf>,
     pub revaultd_path: Option<PathBuf>,
+    /// Directory where PSBTs are exchanged with air-gapped signers.
+    pub psbt_dir: Option<PathBuf>,
+    /// Command driving the signing devices.
+    pub signer_path: Option<PathBuf>,
+    /// File with the xprv or mnemonic of the software signer.
+    pub signer_key_path: Option<PathBuf>,
+    /// Percentage of the spent amount above which the fee is reported.
+    pub fee_warning_percent: Option<u64>,
+    /// TOML file of the spending policy checked before a spend is submitted.
+    pub spending_policy_path: Option<PathBuf>,
     pub debug: bool,
 }
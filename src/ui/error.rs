@@ -5,6 +5,10 @@ use std::convert::From;
 pub enum Error {
     ConfigError(ConfigError),
     RevaultDError(RevaultDError),
+    /// Error while reading or writing a file.
+    FileError(String),
+    /// The spend transaction cannot be made.
+    SpendError(String),
     UnexpectedError(String),
 }
 
@@ -13,6 +17,8 @@ impl std::fmt::Display for Error {
         match self {
             Self::ConfigError(e) => write!(f, "Config error: {}", e),
             Self::RevaultDError(e) => write!(f, "RevaultD error: {}", e),
+            Self::FileError(e) => write!(f, "File error: {}", e),
+            Self::SpendError(e) => write!(f, "Spend error: {}", e),
             Self::UnexpectedError(e) => write!(f, "Unexpected error: {}", e),
         }
     }
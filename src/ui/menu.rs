@@ -6,4 +6,7 @@ pub enum Menu {
     Network,
     Send,
     ACKFunds,
+    Delegate,
+    Monitor,
+    Notifications,
 }
@@ -1,11 +1,28 @@
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
+
+use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
 
 use super::{error::Error, menu::Menu};
-use crate::revault::Role;
 use crate::revaultd::{
-    model::{RevocationTransactions, Vault, VaultTransactions},
+    model::{
+        FeeEstimates, FeePriority, RevocationTransactions, StoredSpendTransaction,
+        UnvaultTransaction, Vault, VaultStatus, VaultTransactions,
+    },
     RevaultD, RevaultDError,
 };
+use crate::{
+    address_book::AddressBook,
+    draft::Draft,
+    ledger,
+    notification::{EventKind, NotificationCenter, Preferences},
+    psbt::Format,
+    recipients,
+    revault::Role,
+    revocation::SignedRevocation,
+    signer::{Descriptors, Device, SignerError},
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -17,17 +34,111 @@ pub enum Message {
     DaemonStarted(Result<Arc<RevaultD>, Error>),
     Vaults(Result<Vec<Vault>, RevaultDError>),
     SelectVault(String),
+    /// Display the given page of the vault list.
+    VaultsPage(usize),
     Vault(VaultMessage),
+    History(HistoryMessage),
     BlockHeight(Result<u64, RevaultDError>),
     Connected(Result<Arc<RevaultD>, Error>),
     Menu(Menu),
     Next,
     Previous,
     DepositAddress(Result<bitcoin::Address, RevaultDError>),
+    /// Amount of the payment request of the deposit address.
+    RequestAmountEdited(String),
+    /// Label of the payment request of the deposit address.
+    RequestLabelEdited(String),
+    OnchainTransactions(Result<Vec<VaultTransactions>, RevaultDError>),
     Deposit(usize, DepositMessage),
+    Batch(BatchMessage),
+    Delegate(DelegateMessage),
     Recipient(usize, RecipientMessage),
     Input(usize, InputMessage),
     AddRecipient,
+    Bip21Edited(String),
+    /// Add the recipient of the pasted BIP21 payment URI.
+    AddBip21Recipient,
+    RecipientsPathEdited(String),
+    /// Import the recipients of the csv file at the edited path.
+    ImportRecipients,
+    RecipientsImported(Result<recipients::Import, Error>),
+    /// Export the recipients to a csv file at the edited path.
+    ExportRecipients,
+    RecipientsExported(Result<PathBuf, Error>),
+    AddressBook(Result<AddressBook, Error>),
+    AddressBookSaved(Result<(), Error>),
+    Drafts(Result<Vec<Draft>, Error>),
+    SpendTransactions(Result<Vec<StoredSpendTransaction>, RevaultDError>),
+    DraftSaved(Result<(), Error>),
+    /// Resume the pending spend with the given draft id.
+    ResumeDraft(String),
+    /// Delete the pending spend with the given draft id.
+    DeleteDraft(String),
+    DraftDeleted(Result<(), Error>),
+    /// Select the vaults to spend automatically.
+    AutoSelectInputs,
+    FeerateEdited(String),
+    FeeEstimates(Result<FeeEstimates, RevaultDError>),
+    FeePriority(FeePriority),
+    MemoEdited(String),
+    SpendTxPathEdited(String),
+    /// Export the spend transaction for the other managers.
+    ExportSpendTx,
+    SpendTxExported(Result<PathBuf, Error>),
+    /// Import the spend transaction signed by another manager.
+    ImportSpendTx,
+    SpendTxImported(Result<Psbt, Error>),
+    /// Vaults unvaulted or spent, to check the daily limit of the spending policy.
+    RecentUnvaults(Result<Vec<Vault>, RevaultDError>),
+    /// Spend transaction built by revaultd for the given fee rate.
+    SpendTransaction(u64, Result<Psbt, RevaultDError>),
+    Sign(SignMessage),
+    SpendTransactionSubmitted(Result<(), RevaultDError>),
+    /// Broadcast the cancel transaction of the vault with the given outpoint.
+    CancelVault(String),
+    VaultCanceled(String, Result<(), RevaultDError>),
+    Tick(Instant),
+    /// Time to list the vaults and look for new events.
+    NotificationTick(Instant),
+    NotificationVaults(Result<Vec<Vault>, RevaultDError>),
+    NotificationShown(Result<(), Error>),
+    NotificationPreferences(Result<Preferences, Error>),
+    NotificationPreferencesSaved(Result<(), Error>),
+    /// Enable or disable the desktop notifications of the event kind for the current role.
+    ToggleNotification(EventKind),
+    /// Mark the notification with the given identifier as read or unread.
+    ToggleNotificationRead(u64),
+    MarkNotificationsRead,
+    /// Updated notifications forwarded by the application to the notification center.
+    NotificationCenter(NotificationCenter),
+}
+
+#[derive(Debug, Clone)]
+pub enum HistoryMessage {
+    ToggleStatus(VaultStatus),
+    /// Vaults listed by the daemon with the given statuses.
+    Vaults(Vec<VaultStatus>, Result<Vec<Vault>, RevaultDError>),
+    /// Start date of the range of the last vault updates, as YYYY-MM-DD.
+    FromEdited(String),
+    /// End date of the range of the last vault updates, as YYYY-MM-DD.
+    ToEdited(String),
+    MinAmountEdited(String),
+    MaxAmountEdited(String),
+    SearchEdited(String),
+    /// Sort by the column, or reverse the order if the vaults are already sorted by it.
+    Sort(HistorySort),
+    ClearFilters,
+    ExportPathEdited(String),
+    /// Export the filtered vaults and their transactions to the edited path.
+    Export(ledger::Format),
+    Exported(Result<PathBuf, Error>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySort {
+    Date,
+    Amount,
+    Status,
 }
 
 #[derive(Debug, Clone)]
@@ -38,18 +149,84 @@ pub enum VaultMessage {
 #[derive(Debug, Clone)]
 pub enum SignMessage {
     ChangeMethod,
+    /// Import the pasted signed psbt.
     Sign,
+    /// Submit the combination of the imported signed psbts.
+    Confirm,
     Clipboard(String),
     PsbtEdited(String),
+    /// Save the psbt to sign in the PSBT exchange directory.
+    Save(Format),
+    Saved(Result<PathBuf, Error>),
+    PsbtPathEdited(String),
+    /// Load the signed psbt from the file at the edited path.
+    Load,
+    /// Load the signed psbt from the QR code images at the edited path.
+    LoadQrCodes,
+    Loaded(Result<Psbt, Error>),
+    ToggleQrCode,
+    /// Display the next part of the animated QR code.
+    NextQrCode,
+    /// List the devices connected to the signer.
+    ListDevices,
+    DevicesListed(Result<Vec<Device>, SignerError>),
+    SelectDevice(usize),
+    Descriptors(Result<Descriptors, SignerError>),
+    /// Sign the psbt with the selected device.
+    SignWithDevice,
+    DeviceSigned(Result<Psbt, SignerError>),
+    /// Sign the psbt with the software signer key.
+    SignWithKey,
 }
 
 #[derive(Debug, Clone)]
 pub enum DepositMessage {
     RevocationTransactions(Result<RevocationTransactions, RevaultDError>),
+    /// Revocation transactions signed before the GUI stopped.
+    SignedRevocation(Result<Option<SignedRevocation>, Error>),
+    SignedRevocationSaved(Result<(), Error>),
     Sign(SignMessage),
     Signed(Result<(), RevaultDError>),
     /// Message ask for Deposit State to retry connecting to revaultd.
     Retry,
+    /// Leave the deposit for later, the next deposit is signed.
+    Skip,
+    Resume,
+}
+
+/// DelegateMessage drives the signature of the unvault transactions
+/// of the vaults delegated to the managers.
+#[derive(Debug, Clone)]
+pub enum DelegateMessage {
+    /// Select or unselect the vault at the given index.
+    Select(usize),
+    Start,
+    UnvaultTransaction(usize, Result<UnvaultTransaction, RevaultDError>),
+    Sign(SignMessage),
+    Delegated(usize, Result<(), RevaultDError>),
+    /// Retry the submission of the signed unvault transaction.
+    Retry,
+}
+
+/// BatchMessage drives the signature of the revocation transactions
+/// of all the pending deposits at once.
+#[derive(Debug, Clone)]
+pub enum BatchMessage {
+    Start,
+    /// Revocation transactions of the vault with the given outpoint.
+    RevocationTransactions(String, Result<RevocationTransactions, RevaultDError>),
+    /// Save all the revocation transactions to sign in a new directory.
+    Export,
+    Exported(Result<PathBuf, Error>),
+    PathEdited(String),
+    /// Import the signed revocation transactions of the directory at the edited path.
+    Import,
+    Imported(Result<Vec<(PathBuf, Option<Psbt>)>, Error>),
+    SignWithKey,
+    Submit,
+    /// Result of the submission of the vault with the given outpoint.
+    Submitted(String, Result<(), RevaultDError>),
+    Close,
 }
 
 #[derive(Debug, Clone)]
@@ -62,4 +239,11 @@ pub enum RecipientMessage {
     Delete,
     AddressEdited(String),
     AmountEdited(String),
+    /// Select the address of an address book entry.
+    SelectEntry(String),
+    EntryLabelEdited(String),
+    EntryNotesEdited(String),
+    EntryWhitelisted(bool),
+    /// Save the recipient address in the address book.
+    SaveEntry,
 }
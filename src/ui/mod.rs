@@ -7,5 +7,6 @@ mod icon;
 pub mod image;
 mod menu;
 mod message;
+mod session;
 mod state;
 mod view;
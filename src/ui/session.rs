@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use crate::{policy::SpendingPolicy, revaultd::config::Config, signer::software::SoftwareSigner};
+
+/// Default percentage of the spent amount above which the fee is reported.
+const DEFAULT_FEE_WARNING_PERCENT: u64 = 5;
+
+/// Session stores what is loaded once revaultd is synced: its configuration,
+/// the user settings and the signers. It is owned by the application and
+/// shared with the states that need it.
+#[derive(Debug)]
+pub struct Session {
+    pub revaultd_config: Config,
+    /// Directory where PSBTs are saved and loaded from.
+    pub psbt_dir: PathBuf,
+    /// Directory of the GUI data files, next to the revaultd data.
+    pub data_dir: PathBuf,
+    /// Command following the HWI protocol to sign with devices.
+    pub signer_path: Option<PathBuf>,
+    /// Signer with an in memory key, only available on test networks.
+    pub software_signer: Option<SoftwareSigner>,
+    /// Reason why the configured software signer key could not be loaded.
+    pub software_signer_error: Option<String>,
+    /// Percentage of the spent amount above which the fee is reported.
+    pub fee_warning_percent: u64,
+    /// Rules checked against the spend transactions before they are submitted.
+    pub spending_policy: Option<SpendingPolicy>,
+    /// Reason why the configured spending policy could not be loaded,
+    /// spends cannot be signed nor submitted until it is fixed.
+    pub spending_policy_error: Option<String>,
+}
+
+impl Session {
+    pub fn new(revaultd_config: Config) -> Self {
+        Self {
+            data_dir: revaultd_config.gui_datadir().unwrap_or_default(),
+            revaultd_config,
+            psbt_dir: dirs::home_dir().unwrap_or_default(),
+            signer_path: None,
+            software_signer: None,
+            software_signer_error: None,
+            fee_warning_percent: DEFAULT_FEE_WARNING_PERCENT,
+            spending_policy: None,
+            spending_policy_error: None,
+        }
+    }
+}
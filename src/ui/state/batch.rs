@@ -0,0 +1,347 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
+use iced::{Command, Element};
+
+use crate::{
+    psbt::{self, check_signed_psbt},
+    revault::TransactionKind,
+    revaultd::{
+        model::{RevocationTransactions, Vault},
+        RevaultD,
+    },
+    ui::{
+        message::BatchMessage,
+        session::Session,
+        state::{
+            cmd::{get_revocation_txs, load_psbt_set, save_psbt_set, set_revocation_txs},
+            sign::our_keys,
+        },
+        view::{Context, RevocationBatchView},
+    },
+};
+
+/// Kinds of the revocation transactions of a vault, in the order they are signed.
+const REVOCATION_KINDS: [TransactionKind; 3] = [
+    TransactionKind::Emergency,
+    TransactionKind::EmergencyUnvault,
+    TransactionKind::Cancel,
+];
+
+/// RevocationBatch signs the revocation transactions of many deposits at once:
+/// they are exported as a set of psbt files, signed on the device and imported back.
+#[derive(Debug)]
+pub struct RevocationBatch {
+    session: Arc<Session>,
+    items: Vec<BatchItem>,
+    /// path of the directory of the signed psbts to import.
+    path: String,
+    exported: Option<PathBuf>,
+    warning: Option<String>,
+    /// number of imported signed psbts and the reasons of the rejected files.
+    import_report: Option<(usize, Vec<String>)>,
+    view: RevocationBatchView,
+}
+
+#[derive(Debug)]
+struct BatchItem {
+    vault: Vault,
+    /// revocation transactions to sign and their signed version,
+    /// in the order of REVOCATION_KINDS.
+    transactions: Vec<(Psbt, Option<Psbt>)>,
+    status: BatchStatus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum BatchStatus {
+    Fetching,
+    Ready,
+    Submitting,
+    Submitted,
+    Failed(String),
+}
+
+impl BatchItem {
+    fn signatures(&self) -> usize {
+        self.transactions
+            .iter()
+            .filter(|(_, signed)| signed.is_some())
+            .count()
+    }
+
+    fn signed(&self) -> bool {
+        !self.transactions.is_empty() && self.signatures() == self.transactions.len()
+    }
+}
+
+impl RevocationBatch {
+    pub fn new(session: Arc<Session>, vaults: Vec<Vault>) -> Self {
+        Self {
+            session,
+            items: vaults
+                .into_iter()
+                .map(|vault| BatchItem {
+                    vault,
+                    transactions: Vec::new(),
+                    status: BatchStatus::Fetching,
+                })
+                .collect(),
+            path: "".to_string(),
+            exported: None,
+            warning: None,
+            import_report: None,
+            view: RevocationBatchView::new(),
+        }
+    }
+
+    pub fn load(&self, revaultd: Arc<RevaultD>) -> Command<BatchMessage> {
+        Command::batch(self.items.iter().map(|item| {
+            let outpoint = item.vault.outpoint();
+            Command::perform(
+                get_revocation_txs(revaultd.clone(), outpoint.clone()),
+                move |res| BatchMessage::RevocationTransactions(outpoint.clone(), res),
+            )
+        }))
+    }
+
+    fn item_mut(&mut self, outpoint: &str) -> Option<&mut BatchItem> {
+        self.items
+            .iter_mut()
+            .find(|item| item.vault.outpoint() == outpoint)
+    }
+
+    fn set_transactions(&mut self, outpoint: &str, txs: RevocationTransactions) {
+        if let Some(item) = self.item_mut(outpoint) {
+            item.transactions = vec![
+                (txs.emergency_tx, None),
+                (txs.emergency_unvault_tx, None),
+                (txs.cancel_tx, None),
+            ];
+            item.status = BatchStatus::Ready;
+        }
+    }
+
+    /// add_signed finds the revocation transaction of the signed psbt and checks
+    /// its signatures before keeping it.
+    fn add_signed(&mut self, signed: Psbt) -> Result<(), String> {
+        let config = &self.session.revaultd_config;
+        let txid = signed.global.unsigned_tx.txid();
+        for item in &mut self.items {
+            for (kind, (original, current)) in
+                REVOCATION_KINDS.iter().zip(item.transactions.iter_mut())
+            {
+                if original.global.unsigned_tx.txid() != txid {
+                    continue;
+                }
+                check_signed_psbt(original, &signed, kind, &our_keys(config, kind))
+                    .map_err(|e| e.to_string())?;
+                let mut combined = current.take().unwrap_or_else(|| original.clone());
+                psbt::combine(&mut combined, &signed).map_err(|e| e.to_string())?;
+                *current = Some(combined);
+                return Ok(());
+            }
+        }
+        Err("it is not one of the revocation transactions to sign".to_string())
+    }
+
+    fn import(&mut self, files: Vec<(PathBuf, Option<Psbt>)>) {
+        let mut imported = 0;
+        let mut rejected = Vec::new();
+        for (path, psbt) in files {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let res = match psbt {
+                Some(psbt) => self.add_signed(psbt),
+                None => Err("it is not a PSBT".to_string()),
+            };
+            match res {
+                Ok(()) => imported += 1,
+                Err(e) => rejected.push(format!("{}: {}", name, e)),
+            }
+        }
+        self.import_report = Some((imported, rejected));
+    }
+
+    /// sign_with_key signs the revocation transactions with the software signer.
+    fn sign_with_key(&mut self) {
+        let session = self.session.clone();
+        let signer = match &session.software_signer {
+            Some(signer) => signer,
+            None => return,
+        };
+        let mut to_sign = Vec::new();
+        for item in &self.items {
+            for (kind, (original, signed)) in REVOCATION_KINDS.iter().zip(&item.transactions) {
+                if signed.is_none() {
+                    to_sign.push((*kind, original.clone()));
+                }
+            }
+        }
+        let mut rejected = Vec::new();
+        let mut imported = 0;
+        for (kind, original) in to_sign {
+            let res = signer
+                .sign(&original, kind.sighash_type())
+                .map_err(|e| e.to_string())
+                .and_then(|signed| self.add_signed(signed));
+            match res {
+                Ok(()) => imported += 1,
+                Err(e) => rejected.push(format!(
+                    "{} transaction {}: {}",
+                    kind,
+                    original.global.unsigned_tx.txid(),
+                    e
+                )),
+            }
+        }
+        self.import_report = Some((imported, rejected));
+    }
+
+    /// submit sends the revocation transactions of every fully signed vault
+    /// that is not submitted yet, failed submissions are retried.
+    fn submit(&mut self, revaultd: Arc<RevaultD>) -> Command<BatchMessage> {
+        let mut cmds = Vec::new();
+        for item in &mut self.items {
+            if !item.signed()
+                || matches!(
+                    item.status,
+                    BatchStatus::Submitting | BatchStatus::Submitted | BatchStatus::Fetching
+                )
+            {
+                continue;
+            }
+            item.status = BatchStatus::Submitting;
+            let outpoint = item.vault.outpoint();
+            let signed = |i: usize| item.transactions[i].1.clone().expect("item is signed");
+            cmds.push(Command::perform(
+                set_revocation_txs(
+                    revaultd.clone(),
+                    outpoint.clone(),
+                    signed(0),
+                    signed(1),
+                    signed(2),
+                ),
+                move |res| BatchMessage::Submitted(outpoint.clone(), res),
+            ));
+        }
+        Command::batch(cmds)
+    }
+
+    pub fn update(
+        &mut self,
+        revaultd: Arc<RevaultD>,
+        message: BatchMessage,
+    ) -> Command<BatchMessage> {
+        match message {
+            BatchMessage::RevocationTransactions(outpoint, res) => match res {
+                Ok(txs) => self.set_transactions(&outpoint, txs),
+                Err(e) => {
+                    if let Some(item) = self.item_mut(&outpoint) {
+                        item.status = BatchStatus::Failed(e.to_string());
+                    }
+                }
+            },
+            BatchMessage::Export => {
+                let mut psbts = Vec::new();
+                for item in &self.items {
+                    for (kind, (original, _)) in REVOCATION_KINDS.iter().zip(&item.transactions) {
+                        psbts.push((
+                            format!(
+                                "{}-{}.{}",
+                                item.vault.outpoint().replace(':', "_"),
+                                kind.to_string().replace(' ', "_"),
+                                psbt::Format::Binary.extension()
+                            ),
+                            original.clone(),
+                        ));
+                    }
+                }
+                let dir = self
+                    .session
+                    .psbt_dir
+                    .join(format!("revocation-{}", chrono::Utc::now().timestamp()));
+                return Command::perform(
+                    save_psbt_set(dir, psbts, psbt::Format::Binary),
+                    BatchMessage::Exported,
+                );
+            }
+            BatchMessage::Exported(res) => match res {
+                Ok(dir) => {
+                    self.warning = None;
+                    self.path = dir.to_string_lossy().to_string();
+                    self.exported = Some(dir);
+                }
+                Err(e) => self.warning = Some(e.to_string()),
+            },
+            BatchMessage::PathEdited(path) => {
+                self.warning = None;
+                self.path = path;
+            }
+            BatchMessage::Import => {
+                if !self.path.trim().is_empty() {
+                    // Relative paths are relative to the PSBT exchange directory.
+                    let dir = self.session.psbt_dir.join(self.path.trim());
+                    return Command::perform(load_psbt_set(dir), BatchMessage::Imported);
+                }
+            }
+            BatchMessage::Imported(res) => match res {
+                Ok(files) => {
+                    self.warning = None;
+                    self.import(files);
+                }
+                Err(e) => self.warning = Some(e.to_string()),
+            },
+            BatchMessage::SignWithKey => self.sign_with_key(),
+            BatchMessage::Submit => return self.submit(revaultd),
+            BatchMessage::Submitted(outpoint, res) => {
+                if let Some(item) = self.item_mut(&outpoint) {
+                    item.status = match res {
+                        Ok(()) => BatchStatus::Submitted,
+                        Err(e) => BatchStatus::Failed(e.to_string()),
+                    };
+                }
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    pub fn view(&mut self, ctx: &Context) -> Element<BatchMessage> {
+        let submitted = self
+            .items
+            .iter()
+            .filter(|item| item.status == BatchStatus::Submitted)
+            .count();
+        let items = self
+            .items
+            .iter()
+            .map(|item| {
+                let (status, ok) = match &item.status {
+                    BatchStatus::Fetching => ("Loading...".to_string(), None),
+                    BatchStatus::Ready => (
+                        format!("{}/{} signed", item.signatures(), item.transactions.len()),
+                        None,
+                    ),
+                    BatchStatus::Submitting => ("Submitting...".to_string(), None),
+                    BatchStatus::Submitted => ("Secured".to_string(), Some(true)),
+                    BatchStatus::Failed(e) => (format!("Failed: {}", e), Some(false)),
+                };
+                (&item.vault, status, ok)
+            })
+            .collect();
+        self.view.view(
+            ctx,
+            items,
+            submitted,
+            &self.path,
+            self.exported.as_ref(),
+            self.session.software_signer.is_some(),
+            self.session.software_signer_error.as_ref(),
+            self.warning.as_ref(),
+            self.import_report.as_ref(),
+        )
+    }
+}
@@ -124,7 +124,7 @@ impl ChargingState {
 }
 
 impl State for ChargingState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::Connected(res) => self.on_connect(res),
             Message::Syncing(res) => self.on_sync(res),
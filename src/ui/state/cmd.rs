@@ -1,9 +1,25 @@
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::revaultd::{
-    model::{RevocationTransactions, Vault, VaultStatus, VaultTransactions},
-    RevaultD, RevaultDError,
+use crate::{
+    address_book::AddressBook,
+    draft::Draft,
+    ledger::{self, Ledger},
+    notification::{self, Notification, Preferences},
+    psbt, recipients,
+    revaultd::{
+        model::{
+            FeeEstimates, RevocationTransactions, StoredSpendTransaction, UnvaultTransaction,
+            Vault, VaultStatus, VaultTransactions,
+        },
+        RevaultD, RevaultDError,
+    },
+    revocation::SignedRevocation,
+    signer::{external::ExternalSigner, Descriptors, Device, SignerError},
+    ui::error::Error,
+    ur,
 };
 
 /// retrieves a bitcoin address for deposit.
@@ -24,6 +40,18 @@ pub async fn list_vaults(
     revaultd.list_vaults(statuses).map(|res| res.vaults)
 }
 
+/// list_vaults_by_status lets the daemon filter the vaults with the given
+/// statuses, all the vaults are listed if there is none.
+pub async fn list_vaults_by_status(
+    revaultd: Arc<RevaultD>,
+    statuses: Vec<VaultStatus>,
+) -> Result<Vec<Vault>, RevaultDError> {
+    if statuses.is_empty() {
+        return list_vaults(revaultd, None).await;
+    }
+    list_vaults(revaultd, Some(&statuses)).await
+}
+
 pub async fn get_onchain_txs(
     revaultd: Arc<RevaultD>,
     outpoint: String,
@@ -38,6 +66,23 @@ pub async fn get_onchain_txs(
     Ok(list.onchain_transactions[0].to_owned())
 }
 
+pub async fn list_vaults_onchain_txs(
+    revaultd: Arc<RevaultD>,
+    outpoints: Vec<String>,
+) -> Result<Vec<VaultTransactions>, RevaultDError> {
+    revaultd
+        .list_onchain_transactions(Some(outpoints))
+        .map(|res| res.onchain_transactions)
+}
+
+pub async fn list_onchain_txs(
+    revaultd: Arc<RevaultD>,
+) -> Result<Vec<VaultTransactions>, RevaultDError> {
+    revaultd
+        .list_onchain_transactions(None)
+        .map(|res| res.onchain_transactions)
+}
+
 pub async fn get_revocation_txs(
     revaultd: Arc<RevaultD>,
     outpoint: String,
@@ -54,3 +99,386 @@ pub async fn set_revocation_txs(
 ) -> Result<(), RevaultDError> {
     revaultd.set_revocation_txs(&outpoint, &emergency_tx, &emergency_unvault_tx, &cancel_tx)
 }
+
+pub async fn get_unvault_tx(
+    revaultd: Arc<RevaultD>,
+    outpoint: String,
+) -> Result<UnvaultTransaction, RevaultDError> {
+    revaultd.get_unvault_tx(&outpoint)
+}
+
+pub async fn set_unvault_tx(
+    revaultd: Arc<RevaultD>,
+    outpoint: String,
+    unvault_tx: Psbt,
+) -> Result<(), RevaultDError> {
+    revaultd.set_unvault_tx(&outpoint, &unvault_tx)
+}
+
+/// writes the psbt to the given path and returns the path once done.
+pub async fn save_psbt(path: PathBuf, psbt: Psbt, format: psbt::Format) -> Result<PathBuf, Error> {
+    std::fs::write(&path, psbt::to_bytes(&psbt, format))
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))?;
+    Ok(path)
+}
+
+/// writes the psbts with their file names in a new directory and returns its path once done.
+pub async fn save_psbt_set(
+    dir: PathBuf,
+    psbts: Vec<(String, Psbt)>,
+    format: psbt::Format,
+) -> Result<PathBuf, Error> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| Error::FileError(format!("Failed to create {:?}: {}", dir, e)))?;
+    for (name, psbt) in psbts {
+        let path = dir.join(name);
+        std::fs::write(&path, psbt::to_bytes(&psbt, format))
+            .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))?;
+    }
+    Ok(dir)
+}
+
+/// reads the psbts of the files of a directory, a file that is not a psbt
+/// is returned without psbt.
+pub async fn load_psbt_set(dir: PathBuf) -> Result<Vec<(PathBuf, Option<Psbt>)>, Error> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", dir, e)))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    Ok(files
+        .into_iter()
+        .map(|path| {
+            let psbt = std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| psbt::from_bytes(&bytes));
+            (path, psbt)
+        })
+        .collect())
+}
+
+/// reads a binary or base64 encoded psbt from the given path.
+pub async fn load_psbt(path: PathBuf) -> Result<Psbt, Error> {
+    let bytes = std::fs::read(&path)
+        .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", path, e)))?;
+    psbt::from_bytes(&bytes)
+        .ok_or_else(|| Error::FileError(format!("{:?} does not contain a valid PSBT", path)))
+}
+
+/// reads the recipients of a csv file.
+pub async fn load_recipients(path: PathBuf) -> Result<recipients::Import, Error> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", path, e)))?;
+    Ok(recipients::from_csv(&content))
+}
+
+/// writes the recipients to a csv file and returns its path once done.
+pub async fn save_recipients(
+    path: PathBuf,
+    recipients: Vec<recipients::Recipient>,
+) -> Result<PathBuf, Error> {
+    std::fs::write(&path, recipients::to_csv(&recipients))
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))?;
+    Ok(path)
+}
+
+/// writes the ledger of the vaults and of their on-chain transactions to a
+/// file and returns its path once done.
+pub async fn export_ledger(
+    revaultd: Arc<RevaultD>,
+    path: PathBuf,
+    format: ledger::Format,
+    network: bitcoin::Network,
+    vaults: Vec<Vault>,
+) -> Result<PathBuf, Error> {
+    // listonchaintransactions lists the transactions of all the vaults if no
+    // outpoint is given.
+    let txs = if vaults.is_empty() {
+        Vec::new()
+    } else {
+        revaultd
+            .list_onchain_transactions(Some(vaults.iter().map(|v| v.outpoint()).collect()))?
+            .onchain_transactions
+    };
+    let ledger = Ledger::new(network, &vaults, &txs);
+    let content = match format {
+        ledger::Format::Csv => ledger.to_csv(),
+        ledger::Format::Json => ledger
+            .to_json()
+            .map_err(|e| Error::UnexpectedError(e.to_string()))?,
+    };
+    std::fs::write(&path, content)
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))?;
+    Ok(path)
+}
+
+/// reads the address book, a missing file is an empty address book.
+pub async fn load_address_book(path: PathBuf) -> Result<AddressBook, Error> {
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AddressBook::default()),
+        Err(e) => {
+            return Err(Error::FileError(format!(
+                "Failed to read {:?}: {}",
+                path, e
+            )))
+        }
+    };
+    serde_json::from_slice(&content)
+        .map_err(|e| Error::FileError(format!("Failed to parse {:?}: {}", path, e)))
+}
+
+/// writes the address book, creating its directory if needed.
+pub async fn save_address_book(path: PathBuf, address_book: AddressBook) -> Result<(), Error> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| Error::FileError(format!("Failed to create {:?}: {}", dir, e)))?;
+    }
+    let content = serde_json::to_vec_pretty(&address_book)
+        .map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    std::fs::write(&path, content)
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))
+}
+
+/// decodes a psbt from the UR QR codes of an image file or of all the image files
+/// of a directory.
+pub async fn load_psbt_from_qr_codes(path: PathBuf) -> Result<Psbt, Error> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&path)
+            .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", path, e)))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|file| {
+                file.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| {
+                        let ext = ext.to_lowercase();
+                        ext == "png" || ext == "jpg" || ext == "jpeg"
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.clone()]
+    };
+
+    let mut decoder = ur::Decoder::new();
+    let mut mixed_parts = 0;
+    for file in &files {
+        let img = image::open(file)
+            .map_err(|e| Error::FileError(format!("Failed to open {:?}: {}", file, e)))?
+            .to_luma8();
+        let mut img = rqrr::PreparedImage::prepare(img);
+        for grid in img.detect_grids() {
+            let (_, content) = grid
+                .decode()
+                .map_err(|e| Error::FileError(format!("Failed to decode {:?}: {}", file, e)))?;
+            match decoder.receive(&content) {
+                Ok(()) => {}
+                // The pure parts may still be enough to decode the psbt.
+                Err(ur::Error::UnsupportedMixedPart) => mixed_parts += 1,
+                Err(e) => return Err(Error::FileError(format!("{:?}: {}", file, e))),
+            }
+        }
+    }
+
+    let bytes = decoder.result().ok_or_else(|| {
+        let (received, total) = decoder.progress();
+        let mut msg = format!(
+            "Missing QR codes in {:?}: {} parts received out of {}",
+            path, received, total
+        );
+        if mixed_parts > 0 {
+            msg.push_str(&format!(
+                ", {} fountain-mixed parts are not supported",
+                mixed_parts
+            ));
+        }
+        Error::FileError(msg)
+    })?;
+    psbt::from_bytes(bytes)
+        .ok_or_else(|| Error::FileError("QR codes do not contain a valid PSBT".to_string()))
+}
+
+pub async fn get_fee_estimates(revaultd: Arc<RevaultD>) -> Result<FeeEstimates, RevaultDError> {
+    revaultd.get_fee_estimates()
+}
+
+pub async fn get_spend_tx(
+    revaultd: Arc<RevaultD>,
+    outpoints: Vec<String>,
+    outputs: HashMap<String, u64>,
+    feerate: u64,
+) -> Result<Psbt, RevaultDError> {
+    revaultd
+        .get_spend_tx(&outpoints, &outputs, feerate)
+        .map(|res| res.spend_tx)
+}
+
+/// submit_spend_tx stores the signed spend transaction and announces it.
+pub async fn submit_spend_tx(revaultd: Arc<RevaultD>, spend_tx: Psbt) -> Result<(), RevaultDError> {
+    revaultd.update_spend_tx(&spend_tx)?;
+    revaultd.set_spend_tx(&spend_tx.global.unsigned_tx.txid().to_string())
+}
+
+/// revault broadcasts the cancel transaction of the unvaulted vault.
+pub async fn revault(revaultd: Arc<RevaultD>, outpoint: String) -> Result<(), RevaultDError> {
+    revaultd.revault(&outpoint)
+}
+
+/// lists the spend transactions stored by revaultd.
+pub async fn list_spend_txs(
+    revaultd: Arc<RevaultD>,
+) -> Result<Vec<StoredSpendTransaction>, RevaultDError> {
+    revaultd.list_spend_txs().map(|res| res.spend_txs)
+}
+
+pub async fn delete_spend_tx(revaultd: Arc<RevaultD>, txid: String) -> Result<(), RevaultDError> {
+    revaultd.delete_spend_tx(&txid)
+}
+
+/// reads the drafts of the drafts directory, the files that cannot be parsed are skipped.
+pub async fn list_drafts(dir: PathBuf) -> Result<Vec<Draft>, Error> {
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::FileError(format!("Failed to read {:?}: {}", dir, e))),
+    };
+    let mut drafts = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+        match std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_slice(&content).map_err(|e| e.to_string()))
+        {
+            Ok(draft) => drafts.push(draft),
+            Err(e) => tracing::warn!("Failed to load draft {:?}: {}", path, e),
+        }
+    }
+    Ok(drafts)
+}
+
+/// writes the draft in the drafts directory, creating the directory if needed.
+pub async fn save_draft(dir: PathBuf, mut draft: Draft) -> Result<(), Error> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| Error::FileError(format!("Failed to create {:?}: {}", dir, e)))?;
+    draft.updated_at = chrono::Utc::now().timestamp();
+    let path = dir.join(Draft::file_name(&draft.id));
+    let content =
+        serde_json::to_vec_pretty(&draft).map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    std::fs::write(&path, content)
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))
+}
+
+pub async fn delete_draft(dir: PathBuf, id: String) -> Result<(), Error> {
+    let path = dir.join(Draft::file_name(&id));
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::FileError(format!(
+            "Failed to delete {:?}: {}",
+            path, e
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// reads the notification preferences, the defaults are used if none were saved.
+pub async fn load_notification_preferences(dir: PathBuf) -> Result<Preferences, Error> {
+    let path = dir.join(notification::FILE_NAME);
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Preferences::default()),
+        Err(e) => {
+            return Err(Error::FileError(format!(
+                "Failed to read {:?}: {}",
+                path, e
+            )))
+        }
+    };
+    serde_json::from_slice(&content)
+        .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", path, e)))
+}
+
+/// raises the desktop notification on a blocking thread, the D-Bus call waits for the
+/// notification server.
+pub async fn show_desktop_notification(notification: Notification) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || notification::show(&notification))
+        .await
+        .map_err(|e| Error::UnexpectedError(e.to_string()))?
+        .map_err(Error::UnexpectedError)
+}
+
+pub async fn save_notification_preferences(
+    dir: PathBuf,
+    preferences: Preferences,
+) -> Result<(), Error> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| Error::FileError(format!("Failed to create {:?}: {}", dir, e)))?;
+    let path = dir.join(notification::FILE_NAME);
+    let content = serde_json::to_vec_pretty(&preferences)
+        .map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    std::fs::write(&path, content)
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))
+}
+
+/// reads the revocation transactions of the deposit signed so far, if any were saved.
+pub async fn load_signed_revocation(
+    dir: PathBuf,
+    outpoint: String,
+) -> Result<Option<SignedRevocation>, Error> {
+    let path = dir.join(SignedRevocation::file_name(&outpoint));
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(Error::FileError(format!(
+                "Failed to read {:?}: {}",
+                path, e
+            )))
+        }
+    };
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| Error::FileError(format!("Failed to read {:?}: {}", path, e)))
+}
+
+/// writes the signed revocation transactions, creating the directory if needed.
+pub async fn save_signed_revocation(dir: PathBuf, signed: SignedRevocation) -> Result<(), Error> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| Error::FileError(format!("Failed to create {:?}: {}", dir, e)))?;
+    let path = dir.join(SignedRevocation::file_name(&signed.outpoint));
+    let content =
+        serde_json::to_vec_pretty(&signed).map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    std::fs::write(&path, content)
+        .map_err(|e| Error::FileError(format!("Failed to write {:?}: {}", path, e)))
+}
+
+pub async fn delete_signed_revocation(dir: PathBuf, outpoint: String) -> Result<(), Error> {
+    let path = dir.join(SignedRevocation::file_name(&outpoint));
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::FileError(format!(
+            "Failed to delete {:?}: {}",
+            path, e
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub async fn list_devices(signer: ExternalSigner) -> Result<Vec<Device>, SignerError> {
+    signer.enumerate().await
+}
+
+pub async fn get_device_descriptors(
+    signer: ExternalSigner,
+    fingerprint: String,
+) -> Result<Descriptors, SignerError> {
+    signer.get_descriptors(&fingerprint).await
+}
+
+pub async fn sign_with_device(
+    signer: ExternalSigner,
+    fingerprint: String,
+    psbt: Psbt,
+) -> Result<Psbt, SignerError> {
+    signer.sign_tx(&fingerprint, &psbt).await
+}
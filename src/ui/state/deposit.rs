@@ -5,7 +5,7 @@ use iced::{Command, Element};
 
 use super::{cmd::get_deposit_address, State};
 
-use crate::revaultd::RevaultD;
+use crate::{bip21, revaultd::RevaultD};
 
 use crate::ui::{
     error::Error,
@@ -22,6 +22,11 @@ pub struct DepositState {
     address: Option<bitcoin::Address>,
     warning: Option<Error>,
 
+    /// Optional amount and label turning the address into a BIP21 payment request.
+    request_amount: String,
+    request_label: String,
+    warning_amount: Option<String>,
+
     /// The deposit view is rendering the address.
     view: DepositView,
 }
@@ -33,29 +38,77 @@ impl DepositState {
             view: DepositView::new(),
             warning: None,
             address: None,
+            request_amount: "".to_string(),
+            request_label: "".to_string(),
+            warning_amount: None,
+        }
+    }
+
+    /// payment_request returns the BIP21 URI of the deposit address if an amount
+    /// or a label was given, the bare address otherwise.
+    fn payment_request(&mut self) -> Option<String> {
+        let address = self.address.clone()?;
+        self.warning_amount = None;
+
+        let mut uri = bip21::Uri::new(address);
+        if !self.request_amount.is_empty() {
+            match bitcoin::Amount::from_str_in(&self.request_amount, bitcoin::Denomination::Bitcoin)
+            {
+                Ok(amount) => uri.amount = Some(amount.as_sat()),
+                Err(_) => self.warning_amount = Some("Please enter a valid amount".to_string()),
+            }
+        }
+        if !self.request_label.is_empty() {
+            uri.label = Some(self.request_label.clone());
+        }
+
+        if uri.amount.is_none() && uri.label.is_none() {
+            Some(uri.address.to_string())
+        } else {
+            Some(uri.to_string())
+        }
+    }
+
+    fn reload(&mut self) {
+        // The payment request is loaded directly in the view in order to cache the created qrcode.
+        if let Some(request) = self.payment_request() {
+            self.view.load(request);
         }
     }
 }
 
 impl State for DepositState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::DepositAddress(res) => match res {
                 Ok(address) => {
-                    // Address is loaded directly in the view in order to cache the created qrcode.
-                    self.view.load(&address);
                     self.address = Some(address);
+                    self.reload();
                 }
                 Err(e) => self.warning = Some(Error::RevaultDError(e)),
             },
+            Message::RequestAmountEdited(amount) => {
+                self.request_amount = amount;
+                self.reload();
+            }
+            Message::RequestLabelEdited(label) => {
+                self.request_label = label;
+                self.reload();
+            }
             _ => {}
         }
         Command::none()
     }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
-        self.view
-            .view(ctx, self.warning.as_ref().into(), self.address.as_ref())
+        self.view.view(
+            ctx,
+            self.warning.as_ref().into(),
+            self.address.as_ref(),
+            &self.request_amount,
+            &self.request_label,
+            self.warning_amount.as_ref(),
+        )
     }
 
     fn load(&self) -> Command<Message> {
@@ -1,51 +1,226 @@
+use std::collections::HashMap;
 use std::convert::From;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use bitcoin::{Amount, Denomination, Network};
+use chrono::NaiveDate;
 use iced::{Command, Element};
 
 use super::{
-    cmd::{get_blockheight, list_vaults},
-    vault::{SelectedVault, VaultListItem},
+    cmd::{export_ledger, get_blockheight, list_vaults_by_status, load_address_book},
+    vault::{self, SelectedVault, VaultList, VaultListItem, VAULTS_PAGE_SIZE},
     State,
 };
 
-use crate::revaultd::{model::Vault, RevaultD};
+use crate::{
+    address_book::{self, AddressBook},
+    ledger,
+    revaultd::{
+        model::{Vault, VaultStatus},
+        RevaultD,
+    },
+};
 
 use crate::ui::{
     error::Error,
-    message::Message,
-    view::{Context, HistoryView},
+    message::{HistoryMessage, HistorySort, Message},
+    session::Session,
+    view::{Context, HistoryControls, HistoryView},
 };
 
 #[derive(Debug)]
 pub struct HistoryState {
     revaultd: Arc<RevaultD>,
     view: HistoryView,
+    session: Arc<Session>,
 
     blockheight: u64,
     warning: Option<Error>,
 
-    vaults: Vec<VaultListItem>,
+    /// vaults listed by the daemon with the selected statuses.
+    vaults: VaultList,
     selected_vault: Option<SelectedVault>,
+    /// labels of the addresses searched in the history.
+    address_book: AddressBook,
+    filter: HistoryFilter,
+    /// indexes of the vaults matching the filters in the selected order,
+    /// computed again only when the vaults, the labels or the filters change.
+    shown: Vec<usize>,
+
+    /// path of the exported ledger, relative to the PSBT exchange directory.
+    export_path: String,
+    export_warning: Option<String>,
+    exported: Option<PathBuf>,
+}
+
+/// HistoryFilter is the selection and the order of the vaults of the history,
+/// kept while a vault is opened.
+#[derive(Debug, Default)]
+struct HistoryFilter {
+    /// all the statuses are displayed if none is selected.
+    statuses: Vec<VaultStatus>,
+    from: String,
+    to: String,
+    min_amount: String,
+    max_amount: String,
+    search: String,
+    /// column and descending order, the daemon order is kept if none.
+    sort: Option<(HistorySort, bool)>,
+}
+
+impl HistoryFilter {
+    /// errors returns the explanation of the filters that cannot be parsed,
+    /// they are ignored.
+    fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (name, date) in &[("start date", &self.from), ("end date", &self.to)] {
+            if !date.is_empty() && parse_date(date).is_none() {
+                errors.push(format!("The {} must be formatted as YYYY-MM-DD", name));
+            }
+        }
+        for (name, amount) in &[
+            ("minimum amount", &self.min_amount),
+            ("maximum amount", &self.max_amount),
+        ] {
+            if !amount.is_empty() && parse_amount(amount).is_none() {
+                errors.push(format!("The {} is not a valid amount", name));
+            }
+        }
+        errors
+    }
+
+    fn matches(&self, vault: &Vault, label: Option<&str>) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&vault.status) {
+            return false;
+        }
+        if let Some(from) = parse_date(&self.from) {
+            if vault.updated_at < from.and_hms(0, 0, 0).timestamp() {
+                return false;
+            }
+        }
+        if let Some(to) = parse_date(&self.to) {
+            if vault.updated_at >= to.succ().and_hms(0, 0, 0).timestamp() {
+                return false;
+            }
+        }
+        if let Some(min) = parse_amount(&self.min_amount) {
+            if vault.amount < min {
+                return false;
+            }
+        }
+        if let Some(max) = parse_amount(&self.max_amount) {
+            if vault.amount > max {
+                return false;
+            }
+        }
+        let search = self.search.trim().to_lowercase();
+        if !search.is_empty() {
+            let found = vault.address.to_lowercase().contains(&search)
+                || vault.outpoint().contains(&search)
+                || label
+                    .map(|label| label.to_lowercase().contains(&search))
+                    .unwrap_or(false);
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn sort(&mut self, column: HistorySort) {
+        self.sort = match self.sort {
+            Some((current, descending)) if current == column => Some((column, !descending)),
+            _ => Some((column, true)),
+        };
+    }
+
+    /// apply returns the indexes of the items of the vaults matching the
+    /// filters in the selected order.
+    fn apply(
+        &self,
+        address_book: &AddressBook,
+        network: Network,
+        items: &[VaultListItem],
+    ) -> Vec<usize> {
+        let labels: HashMap<&str, &str> = address_book
+            .entries
+            .iter()
+            .filter(|entry| entry.network == network)
+            .map(|entry| (entry.address.as_str(), entry.label.as_str()))
+            .collect();
+        let mut indexes: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| self.matches(&v.vault, labels.get(v.vault.address.as_str()).copied()))
+            .map(|(i, _)| i)
+            .collect();
+        if let Some((column, descending)) = self.sort {
+            indexes.sort_by(|a, b| {
+                let (a, b) = (&items[*a].vault, &items[*b].vault);
+                let ordering = match column {
+                    HistorySort::Date => a.updated_at.cmp(&b.updated_at),
+                    HistorySort::Amount => a.amount.cmp(&b.amount),
+                    HistorySort::Status => status_rank(&a.status).cmp(&status_rank(&b.status)),
+                };
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        indexes
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+/// parse_amount returns the amount in satoshis of the amount in bitcoin.
+fn parse_amount(amount: &str) -> Option<u64> {
+    Amount::from_str_in(amount.trim(), Denomination::Bitcoin)
+        .ok()
+        .map(|amount| amount.as_sat())
+}
+
+fn status_rank(status: &VaultStatus) -> usize {
+    VaultStatus::ALL
+        .iter()
+        .position(|s| s == status)
+        .unwrap_or_default()
 }
 
 impl HistoryState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, session: Arc<Session>) -> Self {
         HistoryState {
             revaultd,
             view: HistoryView::new(),
+            session,
             blockheight: 0,
-            vaults: Vec::new(),
+            vaults: VaultList::default(),
             warning: None,
             selected_vault: None,
+            address_book: AddressBook::default(),
+            filter: HistoryFilter::default(),
+            shown: Vec::new(),
+            export_path: "".to_string(),
+            export_warning: None,
+            exported: None,
         }
     }
 
-    pub fn update_vaults(&mut self, vaults: Vec<Vault>) {
-        self.vaults = vaults
-            .into_iter()
-            .map(|vlt| VaultListItem::new(vlt))
-            .collect();
+    pub fn update_vaults(&mut self, network: Network, vaults: Vec<Vault>) {
+        self.vaults.update(vaults);
+        self.refresh(network);
+    }
+
+    /// refresh computes again the vaults shown with the current filters.
+    fn refresh(&mut self, network: Network) {
+        self.shown = self
+            .filter
+            .apply(&self.address_book, network, self.vaults.items());
     }
 
     pub fn on_vault_select(&mut self, outpoint: String) -> Command<Message> {
@@ -56,28 +231,130 @@ impl HistoryState {
             }
         }
 
-        if let Some(selected) = self
-            .vaults
-            .iter()
-            .find(|vlt| vlt.vault.outpoint() == outpoint)
-        {
-            let selected_vault = SelectedVault::new(selected.vault.clone());
+        if let Some(selected) = self.vaults.find(&outpoint) {
+            let selected_vault = SelectedVault::new(selected.clone());
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd;
         };
         Command::none()
     }
+
+    /// list_vaults asks the daemon for the vaults with the selected statuses,
+    /// the response is tagged with them.
+    fn list_vaults(&self) -> Command<Message> {
+        let statuses = self.filter.statuses.clone();
+        Command::perform(
+            list_vaults_by_status(self.revaultd.clone(), statuses.clone()),
+            move |res| Message::History(HistoryMessage::Vaults(statuses.clone(), res)),
+        )
+    }
+
+    /// export writes the vaults displayed with the current filters and their
+    /// on-chain transactions to the ledger file.
+    fn export(&mut self, ctx: &Context, format: ledger::Format) -> Command<Message> {
+        self.exported = None;
+        if self.export_path.trim().is_empty() {
+            self.export_warning = Some("Please enter the path of the export file".to_string());
+            return Command::none();
+        }
+        let path = self.session.psbt_dir.join(self.export_path.trim());
+        let items = self.vaults.items();
+        let vaults = self.shown.iter().map(|i| items[*i].vault.clone()).collect();
+        Command::perform(
+            export_ledger(self.revaultd.clone(), path, format, ctx.network, vaults),
+            |res| Message::History(HistoryMessage::Exported(res)),
+        )
+    }
+
+    fn on_history_message(&mut self, ctx: &Context, message: HistoryMessage) -> Command<Message> {
+        match message {
+            HistoryMessage::ExportPathEdited(path) => {
+                self.export_path = path;
+                self.export_warning = None;
+                return Command::none();
+            }
+            HistoryMessage::Export(format) => return self.export(ctx, format),
+            HistoryMessage::Exported(res) => {
+                match res {
+                    Ok(path) => self.exported = Some(path),
+                    Err(e) => self.export_warning = Some(e.to_string()),
+                }
+                return Command::none();
+            }
+            HistoryMessage::Vaults(statuses, res) => {
+                // the statuses were toggled again since the request, a more recent
+                // listing is on its way.
+                if statuses != self.filter.statuses {
+                    return Command::none();
+                }
+                match res {
+                    Ok(vaults) => self.update_vaults(ctx.network, vaults),
+                    Err(e) => self.warning = Error::from(e).into(),
+                }
+                return Command::none();
+            }
+            _ => {}
+        }
+
+        // the filtered list is displayed from its first page.
+        self.vaults.set_page(0);
+        let cmd = match message {
+            HistoryMessage::ToggleStatus(status) => {
+                match self.filter.statuses.iter().position(|s| *s == status) {
+                    Some(i) => {
+                        self.filter.statuses.remove(i);
+                    }
+                    None => self.filter.statuses.push(status),
+                }
+                self.list_vaults()
+            }
+            HistoryMessage::FromEdited(date) => {
+                self.filter.from = date;
+                Command::none()
+            }
+            HistoryMessage::ToEdited(date) => {
+                self.filter.to = date;
+                Command::none()
+            }
+            HistoryMessage::MinAmountEdited(amount) => {
+                self.filter.min_amount = amount;
+                Command::none()
+            }
+            HistoryMessage::MaxAmountEdited(amount) => {
+                self.filter.max_amount = amount;
+                Command::none()
+            }
+            HistoryMessage::SearchEdited(search) => {
+                self.filter.search = search;
+                Command::none()
+            }
+            HistoryMessage::Sort(column) => {
+                self.filter.sort(column);
+                Command::none()
+            }
+            HistoryMessage::ClearFilters => {
+                let statuses = !self.filter.statuses.is_empty();
+                self.filter = HistoryFilter::default();
+                if statuses {
+                    self.list_vaults()
+                } else {
+                    Command::none()
+                }
+            }
+            _ => Command::none(),
+        };
+        self.refresh(ctx.network);
+        cmd
+    }
 }
 
 impl State for HistoryState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::SelectVault(outpoint) => return self.on_vault_select(outpoint),
-            Message::Vaults(res) => match res {
-                Ok(vaults) => self.update_vaults(vaults),
-                Err(e) => self.warning = Error::from(e).into(),
-            },
+            Message::History(msg) => return self.on_history_message(ctx, msg),
+            Message::VaultsPage(page) => self.vaults.set_page(page),
             Message::Vault(msg) => {
                 if let Some(vault) = &mut self.selected_vault {
                     return vault.update(msg);
@@ -87,6 +364,13 @@ impl State for HistoryState {
                 Ok(height) => self.blockheight = height.into(),
                 Err(e) => self.warning = Error::from(e).into(),
             },
+            Message::AddressBook(res) => match res {
+                Ok(address_book) => {
+                    self.address_book = address_book;
+                    self.refresh(ctx.network);
+                }
+                Err(e) => tracing::warn!("Failed to load address book: {}", e),
+            },
             _ => {}
         };
         Command::none()
@@ -96,17 +380,53 @@ impl State for HistoryState {
         if let Some(v) = &mut self.selected_vault {
             return v.view(ctx);
         }
-        self.view.view(
-            ctx,
-            self.warning.as_ref().into(),
-            self.vaults.iter_mut().map(|v| v.view(ctx)).collect(),
-        )
+
+        let filter = &self.filter;
+        let total = self.vaults.len();
+        let shown = self.shown.len();
+        let pages = vault::pages(shown);
+        let page = std::cmp::min(self.vaults.page(), pages - 1);
+
+        let errors = filter.errors();
+        let controls = HistoryControls {
+            statuses: &filter.statuses,
+            from: &filter.from,
+            to: &filter.to,
+            min_amount: &filter.min_amount,
+            max_amount: &filter.max_amount,
+            search: &filter.search,
+            sort: filter.sort,
+            errors: &errors,
+            shown,
+            total,
+            page,
+            pages,
+            export_path: &self.export_path,
+            export_warning: self.export_warning.as_ref(),
+            exported: self.exported.as_ref(),
+        };
+        let mut items: Vec<Option<&mut VaultListItem>> =
+            self.vaults.items_mut().iter_mut().map(Some).collect();
+        let vaults = self
+            .shown
+            .iter()
+            .skip(page * VAULTS_PAGE_SIZE)
+            .take(VAULTS_PAGE_SIZE)
+            .filter_map(|i| items[*i].take())
+            .map(|v| v.view(ctx))
+            .collect();
+        self.view
+            .view(ctx, self.warning.as_ref().into(), controls, vaults)
     }
 
     fn load(&self) -> Command<Message> {
         Command::batch(vec![
             Command::perform(get_blockheight(self.revaultd.clone()), Message::BlockHeight),
-            Command::perform(list_vaults(self.revaultd.clone(), None), Message::Vaults),
+            self.list_vaults(),
+            Command::perform(
+                load_address_book(self.session.data_dir.join(address_book::FILE_NAME)),
+                Message::AddressBook,
+            ),
         ])
     }
 }
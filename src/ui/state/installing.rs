@@ -16,7 +16,7 @@ impl InstallingState {
 }
 
 impl State for InstallingState {
-    fn update(&mut self, _message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, _message: Message) -> Command<Message> {
         Command::none()
     }
 
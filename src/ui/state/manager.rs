@@ -1,24 +1,52 @@
+use std::collections::{HashMap, HashSet};
 use std::convert::From;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use iced::{Command, Element};
+use bitcoin::{
+    util::{address::Payload, bip32::ExtendedPubKey, psbt::PartiallySignedTransaction as Psbt},
+    Network, Script,
+};
+use iced::{Command, Element, Subscription};
 
 use super::{
-    cmd::{get_blockheight, list_vaults},
-    vault::{SelectedVault, VaultListItem},
+    cmd::{
+        delete_draft, delete_spend_tx, get_blockheight, get_deposit_address, get_fee_estimates,
+        get_spend_tx, list_drafts, list_onchain_txs, list_spend_txs, list_vaults,
+        load_address_book, load_psbt, load_recipients, save_address_book, save_draft, save_psbt,
+        save_recipients, submit_spend_tx,
+    },
+    sign::{SignState, QR_CODE_FRAME_MS},
+    vault::{self, SelectedVault, VaultList},
     State,
 };
 
+use crate::{
+    address_book::{self, AddressBook, Entry, Knowledge},
+    bip21,
+    coin_selection::{self, estimate_fee, Candidate},
+    draft::{self, Draft},
+    policy::{self, Spend, Violation},
+    psbt, recipients,
+    revault::TransactionKind,
+};
+
 use crate::revaultd::{
-    model::{Vault, VaultStatus},
+    model::{
+        FeeEstimates, FeePriority, StoredSpendTransaction, Vault, VaultStatus, VaultTransactions,
+    },
     RevaultD,
 };
 
 use crate::ui::{
     error::Error,
-    message::{InputMessage, Message, RecipientMessage},
-    view::manager::{manager_send_input_view, ManagerSendOutputView, ManagerSendView},
+    message::{InputMessage, Message, RecipientMessage, SignMessage},
+    session::Session,
+    view::manager::{
+        manager_send_input_view, ManagerSendOutputView, ManagerSendStep, ManagerSendView,
+    },
     view::Context,
     view::{ManagerHomeView, ManagerNetworkView},
 };
@@ -33,7 +61,7 @@ pub struct ManagerHomeState {
     blockheight: u64,
     warning: Option<Error>,
 
-    vaults: Vec<VaultListItem>,
+    vaults: VaultList,
     selected_vault: Option<SelectedVault>,
 }
 
@@ -44,7 +72,7 @@ impl ManagerHomeState {
             balance: (0, 0),
             view: ManagerHomeView::new(),
             blockheight: 0,
-            vaults: Vec::new(),
+            vaults: VaultList::default(),
             warning: None,
             selected_vault: None,
         }
@@ -52,10 +80,7 @@ impl ManagerHomeState {
 
     pub fn update_vaults(&mut self, vaults: Vec<Vault>) {
         self.calculate_balance(&vaults);
-        self.vaults = vaults
-            .into_iter()
-            .map(|vlt| VaultListItem::new(vlt))
-            .collect();
+        self.vaults.update(vaults);
     }
 
     pub fn on_vault_select(&mut self, outpoint: String) -> Command<Message> {
@@ -66,12 +91,8 @@ impl ManagerHomeState {
             }
         }
 
-        if let Some(selected) = self
-            .vaults
-            .iter()
-            .find(|vlt| vlt.vault.outpoint() == outpoint)
-        {
-            let selected_vault = SelectedVault::new(selected.vault.clone());
+        if let Some(selected) = self.vaults.find(&outpoint) {
+            let selected_vault = SelectedVault::new(selected.clone());
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd;
@@ -99,9 +120,10 @@ impl ManagerHomeState {
 }
 
 impl State for ManagerHomeState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::SelectVault(outpoint) => return self.on_vault_select(outpoint),
+            Message::VaultsPage(page) => self.vaults.set_page(page),
             Message::Vaults(res) => match res {
                 Ok(vaults) => self.update_vaults(vaults),
                 Err(e) => self.warning = Error::from(e).into(),
@@ -128,10 +150,14 @@ impl State for ManagerHomeState {
         if let Some(v) = &mut self.selected_vault {
             return v.view(ctx);
         }
+        let pages = vault::pages(self.vaults.len());
+        let page = std::cmp::min(self.vaults.page(), pages - 1);
         self.view.view(
             ctx,
             self.warning.as_ref().into(),
-            self.vaults.iter_mut().map(|v| v.view(ctx)).collect(),
+            self.vaults.view(ctx),
+            page,
+            pages,
             &self.balance,
         )
     }
@@ -150,6 +176,30 @@ impl From<ManagerHomeState> for Box<dyn State> {
     }
 }
 
+/// Maximum number of vaults selected automatically for a spend transaction.
+const MAX_SPEND_INPUTS: usize = 10;
+
+/// Statuses of the vaults counted in the daily limit of the spending policy
+/// if they were updated during the last 24 hours.
+const DAILY_LIMIT_STATUSES: &[VaultStatus] = &[
+    VaultStatus::Unvaulting,
+    VaultStatus::Unvaulted,
+    VaultStatus::Spending,
+    VaultStatus::Spent,
+];
+
+/// Statuses of the vaults listed as spend inputs, the vaults that cannot be spent yet
+/// are displayed but are not selectable. Vaults in a final state are not listed.
+const SPEND_INPUT_STATUSES: &[VaultStatus] = &[
+    VaultStatus::Active,
+    VaultStatus::Spendable,
+    VaultStatus::Unvaulting,
+    VaultStatus::Unvaulted,
+    VaultStatus::Secured,
+    VaultStatus::Funded,
+    VaultStatus::Unconfirmed,
+];
+
 #[derive(Debug)]
 pub struct ManagerSendState {
     revaultd: Arc<RevaultD>,
@@ -159,26 +209,404 @@ pub struct ManagerSendState {
 
     vaults: Vec<ManagerSendInput>,
     outputs: Vec<ManagerSendOutput>,
+    /// pasted BIP21 payment URI.
+    bip21: String,
+    bip21_warning: Option<String>,
+    /// path of the csv file to import the recipients from or to export them to.
+    recipients_path: String,
+    recipients_warning: Option<String>,
+    /// number of imported recipients and rejected rows of the last import.
+    recipients_import: Option<(usize, Vec<recipients::Rejected>)>,
+    recipients_exported: Option<PathBuf>,
+    /// GUI data directory, settings and signers.
+    session: Arc<Session>,
+    address_book: AddressBook,
+    /// id of the draft the spend is saved to.
+    draft_id: String,
+    /// last saved draft, the draft is saved again only if it changed.
+    saved_draft: Option<Draft>,
+    /// saved drafts and spend transactions stored by revaultd, listed as pending spends.
+    drafts: Vec<Draft>,
+    stored_spend_txs: Vec<StoredSpendTransaction>,
+    /// addresses of our deposits and unvaults.
+    own_addresses: HashSet<String>,
+    /// fee rate in sat/vbyte.
+    feerate: String,
+    fee_estimates: Option<FeeEstimates>,
+    /// priority of the selected estimate, none if the fee rate is custom.
+    fee_priority: Option<FeePriority>,
+    /// description of the spend, required by some spending policies.
+    memo: String,
+    /// amount of the vaults unvaulted during the last 24 hours.
+    unvaulted_last_day: u64,
+    /// path of the spend transaction signed by another manager to import.
+    coordination_path: String,
+    coordination_warning: Option<String>,
+    /// path of the last spend transaction exported for the other managers.
+    coordination_exported: Option<PathBuf>,
+
+    /// spend transaction built by revaultd and its fee.
+    spend_tx: Option<(Psbt, Option<u64>)>,
+    signer: Option<SignState>,
+    /// true while waiting for revaultd.
+    processing: bool,
 }
 
 impl ManagerSendState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, session: Arc<Session>) -> Self {
         ManagerSendState {
             revaultd,
             view: ManagerSendView::new(),
             warning: None,
             vaults: Vec::new(),
             outputs: vec![ManagerSendOutput::new()],
+            bip21: "".to_string(),
+            bip21_warning: None,
+            recipients_path: "".to_string(),
+            recipients_warning: None,
+            recipients_import: None,
+            recipients_exported: None,
+            session,
+            address_book: AddressBook::default(),
+            draft_id: Draft::new().id,
+            saved_draft: None,
+            drafts: Vec::new(),
+            stored_spend_txs: Vec::new(),
+            own_addresses: HashSet::new(),
+            feerate: "".to_string(),
+            fee_estimates: None,
+            fee_priority: None,
+            memo: "".to_string(),
+            unvaulted_last_day: 0,
+            coordination_path: "".to_string(),
+            coordination_warning: None,
+            coordination_exported: None,
+            spend_tx: None,
+            signer: None,
+            processing: false,
         }
     }
 
     pub fn update_vaults(&mut self, vaults: Vec<Vault>) {
+        let mut vaults: Vec<Vault> = vaults
+            .into_iter()
+            .filter(|vlt| SPEND_INPUT_STATUSES.contains(&vlt.status))
+            .collect();
+        // Vaults are grouped by status, spendable ones first.
+        vaults.sort_by_key(|vlt| {
+            SPEND_INPUT_STATUSES
+                .iter()
+                .position(|status| *status == vlt.status)
+        });
         self.vaults = vaults
             .into_iter()
             .map(|vlt| ManagerSendInput::new(vlt))
             .collect();
     }
 
+    /// check_outputs flags the recipients sharing the same address and
+    /// checks again every recipient against our addresses.
+    fn check_outputs(&mut self, ctx: &Context) {
+        let mut count: HashMap<String, usize> = HashMap::new();
+        for output in &self.outputs {
+            if !output.address.is_empty() {
+                *count.entry(output.address.clone()).or_insert(0) += 1;
+            }
+        }
+        for output in &mut self.outputs {
+            output.duplicate = count.get(&output.address).map_or(false, |n| *n > 1);
+            output.check(ctx, &self.own_addresses);
+        }
+    }
+
+    /// add_bip21_recipient parses the pasted payment URI into a recipient,
+    /// replacing the recipient form if it is still empty.
+    fn add_bip21_recipient(&mut self, ctx: &Context) {
+        let uri = match bip21::Uri::from_str(&self.bip21) {
+            Ok(uri) => uri,
+            Err(e) => {
+                self.bip21_warning = Some(e.to_string());
+                return;
+            }
+        };
+
+        let output = ManagerSendOutput::from_uri(uri);
+        if self.outputs.len() == 1 && self.outputs[0].is_empty() {
+            self.outputs[0] = output;
+        } else {
+            self.outputs.push(output);
+        }
+        self.bip21 = "".to_string();
+        self.bip21_warning = None;
+        self.check_outputs(ctx);
+    }
+
+    /// add_imported_recipients adds the valid recipients of the csv file and
+    /// keeps the rejected rows for the import summary.
+    fn add_imported_recipients(&mut self, ctx: &Context, import: recipients::Import) {
+        let mut rejected = import.rejected;
+        let mut imported = 0;
+        for (line, recipient) in import.recipients {
+            let mut output = ManagerSendOutput::new();
+            output.address = recipient.address;
+            output.amount = recipient.amount;
+            output.label = recipient.label;
+            output.check(ctx, &self.own_addresses);
+            if let Some(reason) = output
+                .warning_address
+                .take()
+                .or_else(|| output.warning_amount.take())
+            {
+                rejected.push(recipients::Rejected { line, reason });
+                continue;
+            }
+
+            if self.outputs.len() == 1 && self.outputs[0].is_empty() {
+                self.outputs[0] = output;
+            } else {
+                self.outputs.push(output);
+            }
+            imported += 1;
+        }
+        rejected.sort_by_key(|r| r.line);
+        self.recipients_import = Some((imported, rejected));
+        self.check_outputs(ctx);
+    }
+
+    /// save_address_book_entry adds the address of the recipient to the address book.
+    fn save_address_book_entry(&mut self, ctx: &Context, i: usize) -> Command<Message> {
+        let output = match self.outputs.get_mut(i) {
+            Some(output) => output,
+            None => return Command::none(),
+        };
+        if output.entry_label.trim().is_empty() {
+            output.entry_warning = Some("Please enter a label".to_string());
+            return Command::none();
+        }
+        output.entry_warning = None;
+        output.label = Some(output.entry_label.trim().to_string());
+        self.address_book.insert(Entry {
+            label: output.entry_label.trim().to_string(),
+            address: output.address.clone(),
+            network: ctx.network,
+            notes: output.entry_notes.clone(),
+            whitelisted: output.entry_whitelisted,
+        });
+        Command::perform(
+            save_address_book(
+                self.session.data_dir.join(address_book::FILE_NAME),
+                self.address_book.clone(),
+            ),
+            Message::AddressBookSaved,
+        )
+    }
+
+    fn drafts_dir(&self) -> PathBuf {
+        self.session.data_dir.join(draft::DIR_NAME)
+    }
+
+    /// draft returns the current state of the spend, the spend transaction
+    /// is part of the draft once it is being signed.
+    fn draft(&self) -> Draft {
+        let mut draft = Draft::new();
+        draft.id = self.draft_id.clone();
+        draft.recipients = self
+            .outputs
+            .iter()
+            .filter(|output| !output.is_empty())
+            .map(|output| recipients::Recipient {
+                address: output.address.clone(),
+                amount: output.amount.clone(),
+                label: output.label.clone(),
+            })
+            .collect();
+        draft.inputs = self
+            .vaults
+            .iter()
+            .filter(|input| input.selected)
+            .map(|input| input.vault.outpoint())
+            .collect();
+        draft.feerate = self.feerate.clone();
+        draft.fee_priority = self.fee_priority;
+        draft.memo = self.memo.clone();
+        if self.signer.is_some() {
+            draft.set_psbt(self.spend_tx.as_ref().map(|(psbt, _)| psbt));
+        }
+        draft
+    }
+
+    /// autosave saves the draft if it changed since the last save. An empty
+    /// spend or a submitted spend is not saved.
+    fn autosave(&mut self) -> Command<Message> {
+        if self.view.step == ManagerSendStep::Success {
+            return Command::none();
+        }
+        let draft = self.draft();
+        if draft.is_empty() || self.saved_draft.as_ref() == Some(&draft) {
+            return Command::none();
+        }
+        self.saved_draft = Some(draft.clone());
+        Command::perform(save_draft(self.drafts_dir(), draft), Message::DraftSaved)
+    }
+
+    /// pending_spends lists the drafts and the spend transactions stored by revaultd,
+    /// except the spend being edited.
+    fn pending_spends(&self, ctx: &Context) -> Vec<Draft> {
+        let txid = self.draft().txid();
+        draft::sync(
+            &self.drafts,
+            &self.stored_spend_txs,
+            ctx.network,
+            &self.own_addresses,
+        )
+        .into_iter()
+        .filter(|draft| draft.id != self.draft_id && (txid.is_none() || draft.txid() != txid))
+        .collect()
+    }
+
+    /// resume_draft replaces the spend being edited by the pending spend.
+    fn resume_draft(&mut self, ctx: &Context, id: &str) {
+        let draft = match self
+            .pending_spends(ctx)
+            .into_iter()
+            .find(|draft| draft.id == id)
+        {
+            Some(draft) => draft,
+            None => return,
+        };
+
+        self.draft_id = draft.id.clone();
+        self.outputs = draft
+            .recipients
+            .iter()
+            .map(|recipient| {
+                let mut output = ManagerSendOutput::new();
+                output.address = recipient.address.clone();
+                output.amount = recipient.amount.clone();
+                output.label = recipient.label.clone();
+                output
+            })
+            .collect();
+        if self.outputs.is_empty() {
+            self.outputs.push(ManagerSendOutput::new());
+        }
+        for input in &mut self.vaults {
+            input.selected = draft.inputs.contains(&input.vault.outpoint());
+        }
+        self.feerate = draft.feerate.clone();
+        self.fee_priority = draft.fee_priority;
+        self.memo = draft.memo.clone();
+        self.warning = None;
+        self.check_outputs(ctx);
+
+        self.view = ManagerSendView::new();
+        match draft.psbt() {
+            Some(psbt) => {
+                let fee = psbt::fee(&psbt);
+                self.signer = Some(SignState::new(
+                    self.session.clone(),
+                    psbt.clone(),
+                    TransactionKind::Spend,
+                ));
+                self.spend_tx = Some((psbt, fee));
+                self.view.step = ManagerSendStep::Sign;
+            }
+            None => {
+                self.signer = None;
+                self.spend_tx = None;
+            }
+        }
+    }
+
+    /// delete_draft deletes the draft file and the spend transaction stored by revaultd.
+    fn delete_draft(&mut self, ctx: &Context, id: String) -> Command<Message> {
+        let txid = self
+            .pending_spends(ctx)
+            .iter()
+            .find(|draft| draft.id == id)
+            .and_then(|draft| draft.txid());
+        self.drafts.retain(|draft| draft.id != id);
+
+        let mut cmds = vec![Command::perform(
+            delete_draft(self.drafts_dir(), id),
+            Message::DraftDeleted,
+        )];
+        if let Some(txid) = txid {
+            let stored = self
+                .stored_spend_txs
+                .iter()
+                .any(|tx| tx.psbt.global.unsigned_tx.txid().to_string() == txid);
+            if stored {
+                self.stored_spend_txs
+                    .retain(|tx| tx.psbt.global.unsigned_tx.txid().to_string() != txid);
+                cmds.push(Command::perform(
+                    delete_spend_tx(self.revaultd.clone(), txid),
+                    |res| Message::DraftDeleted(res.map_err(Error::from)),
+                ));
+            }
+        }
+        Command::batch(cmds)
+    }
+
+    /// recipients_path returns the path of the recipients csv file, relative paths
+    /// are relative to the PSBT exchange directory.
+    fn recipients_path(&mut self) -> Option<PathBuf> {
+        if self.recipients_path.trim().is_empty() {
+            self.recipients_warning = Some("Please enter the path of a csv file".to_string());
+            return None;
+        }
+        Some(self.session.psbt_dir.join(self.recipients_path.trim()))
+    }
+
+    /// add_own_addresses stores the addresses of the deposit and unvault outputs
+    /// of our vaults.
+    fn add_own_addresses(&mut self, ctx: &Context, txs: Vec<VaultTransactions>) {
+        for vault_txs in txs {
+            let deposit_vout = vault_txs
+                .vault_outpoint
+                .rsplit(':')
+                .next()
+                .and_then(|vout| usize::from_str(vout).ok());
+            let mut outputs = Vec::new();
+            if let Some(output) =
+                deposit_vout.and_then(|vout| vault_txs.deposit.tx.output.get(vout))
+            {
+                outputs.push(output.clone());
+            }
+            if let Some(unvault) = &vault_txs.unvault {
+                outputs.extend(unvault.tx.output.iter().cloned());
+            }
+            for output in outputs {
+                if let Some(address) =
+                    bitcoin::Address::from_script(&output.script_pubkey, ctx.network)
+                {
+                    self.own_addresses.insert(address.to_string());
+                }
+            }
+        }
+        self.check_outputs(ctx);
+    }
+
+    /// check_inputs returns an error if the selection cannot be spent.
+    fn check_inputs(&self) -> Result<(), Error> {
+        let selected: Vec<&ManagerSendInput> =
+            self.vaults.iter().filter(|input| input.selected).collect();
+        if selected.is_empty() {
+            return Err(Error::SpendError("No vault is selected".to_string()));
+        }
+        if let Some(input) = selected
+            .iter()
+            .find(|input| !input.vault.status.is_spendable())
+        {
+            return Err(Error::SpendError(format!(
+                "Vault {} cannot be spent: {}",
+                input.vault.outpoint(),
+                input.vault.status.spend_restriction().unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+
     pub fn input_amount(&self) -> u64 {
         let mut input_amount = 0;
         for input in &self.vaults {
@@ -198,21 +626,527 @@ impl ManagerSendState {
         }
         output_amount
     }
-}
 
-impl State for ManagerSendState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    /// estimated_feerate returns the fee rate used to estimate the fee before the
+    /// fee rate is selected.
+    fn estimated_feerate(&self) -> u64 {
+        self.feerate()
+            .or_else(|| self.fee_estimates.as_ref().map(|e| e.normal))
+            .unwrap_or(1)
+    }
+
+    /// estimated_fee returns an approximation of the fee of the spend transaction
+    /// of the selected vaults.
+    fn estimated_fee(&self) -> u64 {
+        let inputs = self.vaults.iter().filter(|input| input.selected).count();
+        estimate_fee(self.estimated_feerate(), inputs, self.outputs.len())
+    }
+
+    /// auto_select_inputs selects the spendable vaults paying the recipients with the
+    /// fewest unvaults and the least change, older vaults are preferred.
+    fn auto_select_inputs(&mut self) {
+        let spendable: Vec<usize> = self
+            .vaults
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| input.vault.status.is_spendable())
+            .map(|(i, _)| i)
+            .collect();
+        let candidates: Vec<Candidate> = spendable
+            .iter()
+            .map(|i| Candidate {
+                amount: self.vaults[*i].vault.amount,
+                timestamp: self.vaults[*i].vault.updated_at,
+            })
+            .collect();
+        let feerate = self.estimated_feerate();
+        let outputs = self.outputs.len();
+        match coin_selection::select(
+            &candidates,
+            self.output_amount(),
+            |inputs| estimate_fee(feerate, inputs, outputs),
+            MAX_SPEND_INPUTS,
+        ) {
+            Some(selected) => {
+                for input in &mut self.vaults {
+                    input.selected = false;
+                }
+                for i in selected {
+                    self.vaults[spendable[i]].selected = true;
+                }
+                self.warning = None;
+            }
+            None => {
+                self.warning = Some(Error::SpendError(format!(
+                    "No selection of at most {} vaults pays the recipients and the fee",
+                    MAX_SPEND_INPUTS
+                )))
+            }
+        }
+    }
+
+    fn feerate(&self) -> Option<u64> {
+        match u64::from_str(self.feerate.trim()) {
+            Ok(feerate) if feerate > 0 => Some(feerate),
+            _ => None,
+        }
+    }
+
+    /// build_spend_tx asks revaultd to build the spend transaction of the
+    /// selected vaults and recipients.
+    fn build_spend_tx(&mut self) -> Command<Message> {
+        let feerate = match self.feerate() {
+            Some(feerate) => feerate,
+            None => return Command::none(),
+        };
+        if let Err(e) = self.check_inputs() {
+            self.warning = Some(e);
+            return Command::none();
+        }
+        let outpoints = self
+            .vaults
+            .iter()
+            .filter(|input| input.selected)
+            .map(|input| input.vault.outpoint())
+            .collect();
+        let mut outputs: HashMap<String, u64> = HashMap::new();
+        for output in &self.outputs {
+            if let Ok(amount) = output.amount() {
+                *outputs.entry(output.address.clone()).or_insert(0) += amount;
+            }
+        }
+        self.warning = None;
+        self.spend_tx = None;
+        self.signer = None;
+        self.processing = true;
+        Command::perform(
+            get_spend_tx(self.revaultd.clone(), outpoints, outputs, feerate),
+            move |res| Message::SpendTransaction(feerate, res),
+        )
+    }
+
+    /// change returns the amount of the spend transaction outputs
+    /// that are not paying one of the recipients.
+    fn change(&self, ctx: &Context, psbt: &Psbt) -> u64 {
+        psbt.global
+            .unsigned_tx
+            .output
+            .iter()
+            .filter(|output| {
+                let address = bitcoin::Address::from_script(&output.script_pubkey, ctx.network)
+                    .map(|address| address.to_string());
+                !self
+                    .outputs
+                    .iter()
+                    .any(|recipient| Some(&recipient.address) == address.as_ref())
+            })
+            .map(|output| output.value)
+            .sum()
+    }
+
+    /// policy_error returns an error if the configured spending policy could not be
+    /// loaded: the spend is neither signed nor submitted without the policy checks.
+    fn policy_error(&self) -> Option<Error> {
+        self.session
+            .spending_policy_error
+            .as_ref()
+            .map(|e| Error::SpendError(format!("The spending policy could not be loaded: {}", e)))
+    }
+
+    /// policy_violations checks the spend transaction against the spending policy.
+    /// The destinations and the amount are the ones of the transaction outputs,
+    /// except the outputs paying our deposit and unvault addresses.
+    fn policy_violations(&self, ctx: &Context) -> Vec<Violation> {
+        if let Some(e) = self.policy_error() {
+            return vec![Violation {
+                action: policy::Action::Block,
+                reason: e.to_string(),
+            }];
+        }
+        let (policy, psbt) = match (&self.session.spending_policy, &self.spend_tx) {
+            (Some(policy), Some((psbt, _))) => (policy, psbt),
+            _ => return Vec::new(),
+        };
+        let mut amount = 0;
+        let mut destinations: Vec<String> = Vec::new();
+        for output in &psbt.global.unsigned_tx.output {
+            // Outputs without address are destinations too, named by their script.
+            let destination = bitcoin::Address::from_script(&output.script_pubkey, ctx.network)
+                .map(|address| address.to_string())
+                .unwrap_or_else(|| format!("{:x}", output.script_pubkey));
+            if self.own_addresses.contains(&destination) {
+                continue;
+            }
+            amount += output.value;
+            if !destinations.contains(&destination) {
+                destinations.push(destination);
+            }
+        }
+        policy.check(&Spend {
+            amount,
+            unvaulted_amount: self.input_amount(),
+            unvaulted_last_day: self.unvaulted_last_day,
+            destinations,
+            address_book_whitelist: self
+                .address_book
+                .entries
+                .iter()
+                .filter(|entry| entry.whitelisted && entry.network == ctx.network)
+                .map(|entry| entry.address.clone())
+                .collect(),
+            feerate: self.feerate().unwrap_or(0),
+            memo: &self.memo,
+        })
+    }
+
+    /// manager_signatures returns for each manager key if it signed the spend
+    /// transaction, and the number of manager signatures required.
+    fn manager_signatures(&self) -> (Vec<(ExtendedPubKey, bool)>, usize) {
+        let (keys, threshold) = self.session.revaultd_config.spend_signers();
+        let signatures = keys
+            .into_iter()
+            .map(|key| {
+                let signed = self
+                    .spend_tx
+                    .as_ref()
+                    .map(|(psbt, _)| psbt::signed_by(psbt, &key))
+                    .unwrap_or(false);
+                (key, signed)
+            })
+            .collect();
+        (signatures, threshold)
+    }
+
+    fn threshold_reached(&self) -> bool {
+        let (signatures, threshold) = self.manager_signatures();
+        signatures.iter().filter(|(_, signed)| *signed).count() >= threshold
+    }
+
+    /// add_signatures merges the signatures of the psbt in the spend transaction,
+    /// which is submitted once the managers threshold is reached.
+    fn add_signatures(&mut self, ctx: &Context, signed: &Psbt) -> Command<Message> {
+        if let Some((psbt, _)) = &mut self.spend_tx {
+            match psbt::combine(psbt, signed) {
+                Ok(conflicts) => {
+                    self.coordination_warning = if conflicts.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            conflicts
+                                .iter()
+                                .map(|c| c.to_string())
+                                .collect::<Vec<String>>()
+                                .join("\n"),
+                        )
+                    };
+                }
+                Err(e) => {
+                    self.coordination_warning = Some(e.to_string());
+                    return Command::none();
+                }
+            }
+        }
+        if self.threshold_reached() {
+            return self.submit_spend_tx(ctx);
+        }
+        Command::none()
+    }
+
+    /// import_signed_spend_tx checks the spend transaction signed by another manager
+    /// before merging its signatures.
+    fn import_signed_spend_tx(&mut self, ctx: &Context, signed: Psbt) -> Command<Message> {
+        if let Some((psbt, _)) = &self.spend_tx {
+            if let Err(e) = psbt::check_cosigned_psbt(psbt, &signed, &TransactionKind::Spend) {
+                self.coordination_warning = Some(e.to_string());
+                return Command::none();
+            }
+        }
+        self.coordination_path = "".to_string();
+        self.add_signatures(ctx, &signed)
+    }
+
+    fn submit_spend_tx(&mut self, ctx: &Context) -> Command<Message> {
+        if policy::is_blocked(&self.policy_violations(ctx)) {
+            self.warning = Some(Error::SpendError(
+                "The spend transaction breaks the spending policy".to_string(),
+            ));
+            return Command::none();
+        }
+        if !self.threshold_reached() {
+            self.warning = Some(Error::SpendError(
+                "The spend transaction is not signed by enough managers".to_string(),
+            ));
+            return Command::none();
+        }
+        if let Some((psbt, _)) = self.spend_tx.clone() {
+            self.warning = None;
+            self.processing = true;
+            return Command::perform(
+                submit_spend_tx(self.revaultd.clone(), psbt),
+                Message::SpendTransactionSubmitted,
+            );
+        }
+        Command::none()
+    }
+
+    fn on_message(&mut self, ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::Vaults(res) => match res {
                 Ok(vlts) => self.update_vaults(vlts),
                 Err(e) => self.warning = Some(Error::RevaultDError(e)),
             },
-            Message::Next => self.view = self.view.next(),
-            Message::Previous => self.view = self.view.previous(),
+
+            Message::Next => match self.view.step {
+                ManagerSendStep::SelectInputs => {
+                    if let Err(e) = self.check_inputs() {
+                        self.warning = Some(e);
+                    } else {
+                        self.warning = None;
+                        self.view.next();
+                        return self.build_spend_tx();
+                    }
+                }
+                ManagerSendStep::SelectFee => {
+                    if let Some(e) = self.policy_error() {
+                        self.warning = Some(e);
+                    } else if let Some((psbt, _)) = &self.spend_tx {
+                        self.signer = Some(SignState::new(
+                            self.session.clone(),
+                            psbt.clone(),
+                            TransactionKind::Spend,
+                        ));
+                        self.view.next();
+                    }
+                }
+                ManagerSendStep::Sign => return self.submit_spend_tx(ctx),
+                _ => self.view.next(),
+            },
+            Message::Previous => {
+                self.warning = None;
+                self.view.previous();
+            }
+            // A new fee rate invalidates the transaction being built, whose
+            // response is ignored, even if the new fee rate is not valid.
+            Message::FeerateEdited(feerate) => {
+                self.feerate = feerate;
+                self.fee_priority = None;
+                self.spend_tx = None;
+                self.processing = false;
+                return self.build_spend_tx();
+            }
+            Message::FeePriority(priority) => {
+                if let Some(estimates) = &self.fee_estimates {
+                    self.feerate = estimates.feerate(priority).to_string();
+                    self.fee_priority = Some(priority);
+                    self.processing = false;
+                    return self.build_spend_tx();
+                }
+            }
+            Message::MemoEdited(memo) => self.memo = memo,
+            Message::SpendTxPathEdited(path) => {
+                self.coordination_path = path;
+                self.coordination_warning = None;
+            }
+            Message::ExportSpendTx => {
+                if let Some((psbt, _)) = &self.spend_tx {
+                    let path = self.session.psbt_dir.join(psbt::file_name(
+                        psbt,
+                        &TransactionKind::Spend,
+                        psbt::Format::Base64,
+                    ));
+                    return Command::perform(
+                        save_psbt(path, psbt.clone(), psbt::Format::Base64),
+                        Message::SpendTxExported,
+                    );
+                }
+            }
+            Message::SpendTxExported(res) => match res {
+                Ok(path) => {
+                    self.coordination_warning = None;
+                    self.coordination_exported = Some(path);
+                }
+                Err(e) => self.coordination_warning = Some(e.to_string()),
+            },
+            Message::ImportSpendTx => {
+                if !self.coordination_path.trim().is_empty() {
+                    // Relative paths are relative to the PSBT exchange directory.
+                    let path = self.session.psbt_dir.join(self.coordination_path.trim());
+                    return Command::perform(load_psbt(path), Message::SpendTxImported);
+                }
+            }
+            Message::SpendTxImported(res) => match res {
+                Ok(psbt) => return self.import_signed_spend_tx(ctx, psbt),
+                Err(e) => self.coordination_warning = Some(e.to_string()),
+            },
+            Message::RecentUnvaults(res) => match res {
+                Ok(vaults) => {
+                    let since = chrono::Utc::now().timestamp() - 24 * 3600;
+                    self.unvaulted_last_day = vaults
+                        .iter()
+                        .filter(|vault| vault.updated_at >= since)
+                        .map(|vault| vault.amount)
+                        .sum();
+                }
+                Err(e) => tracing::warn!("Failed to list unvaulted vaults: {}", e),
+            },
+            Message::FeeEstimates(res) => match res {
+                Ok(estimates) => {
+                    let empty = self.feerate.is_empty();
+                    self.fee_estimates = Some(estimates);
+                    if empty {
+                        return self.on_message(ctx, Message::FeePriority(FeePriority::Normal));
+                    }
+                }
+                // Without estimates, the fee rate can still be entered manually.
+                Err(e) => tracing::warn!("Failed to get fee estimates: {}", e),
+            },
+            Message::SpendTransaction(feerate, res) => {
+                // Ignore the transactions built for a previous fee rate.
+                if self.feerate() == Some(feerate) {
+                    self.processing = false;
+                    match res {
+                        Ok(psbt) => {
+                            let fee = psbt::fee(&psbt);
+                            self.spend_tx = Some((psbt, fee));
+                        }
+                        Err(e) => self.warning = Some(Error::RevaultDError(e)),
+                    }
+                }
+            }
+            Message::Sign(msg) => {
+                if let Some(e) = self.policy_error() {
+                    self.warning = Some(e);
+                    return Command::none();
+                }
+                if let Some(signer) = &mut self.signer {
+                    let signed = signer.signed_psbt.is_some();
+                    let cmd = signer.update(ctx, msg).map(Message::Sign);
+                    if !signed {
+                        if let Some(signed_psbt) = signer.signed_psbt.clone() {
+                            return Command::batch(vec![
+                                cmd,
+                                self.add_signatures(ctx, &signed_psbt),
+                            ]);
+                        }
+                    }
+                    return cmd;
+                }
+            }
+            Message::SpendTransactionSubmitted(res) => {
+                self.processing = false;
+                match res {
+                    Ok(()) => {
+                        self.view.next();
+                        return Command::perform(
+                            delete_draft(self.drafts_dir(), self.draft_id.clone()),
+                            Message::DraftDeleted,
+                        );
+                    }
+                    Err(e) => self.warning = Some(Error::RevaultDError(e)),
+                }
+            }
+            Message::Tick(_) => {
+                if let Some(signer) = &mut self.signer {
+                    signer.update(ctx, SignMessage::NextQrCode);
+                }
+            }
             Message::AddRecipient => self.outputs.push(ManagerSendOutput::new()),
+            Message::Bip21Edited(uri) => {
+                self.bip21 = uri;
+                self.bip21_warning = None;
+            }
+            Message::AddBip21Recipient => self.add_bip21_recipient(ctx),
+            Message::RecipientsPathEdited(path) => {
+                self.recipients_path = path;
+                self.recipients_warning = None;
+            }
+            Message::ImportRecipients => {
+                if let Some(path) = self.recipients_path() {
+                    self.recipients_exported = None;
+                    return Command::perform(load_recipients(path), Message::RecipientsImported);
+                }
+            }
+            Message::RecipientsImported(res) => match res {
+                Ok(import) => self.add_imported_recipients(ctx, import),
+                Err(e) => self.recipients_warning = Some(e.to_string()),
+            },
+            Message::ExportRecipients => {
+                if let Some(path) = self.recipients_path() {
+                    let recipients = self
+                        .outputs
+                        .iter()
+                        .filter(|output| !output.is_empty())
+                        .map(|output| recipients::Recipient {
+                            address: output.address.clone(),
+                            amount: output.amount.clone(),
+                            label: output.label.clone(),
+                        })
+                        .collect();
+                    self.recipients_import = None;
+                    return Command::perform(
+                        save_recipients(path, recipients),
+                        Message::RecipientsExported,
+                    );
+                }
+            }
+            Message::RecipientsExported(res) => match res {
+                Ok(path) => self.recipients_exported = Some(path),
+                Err(e) => self.recipients_warning = Some(e.to_string()),
+            },
+            Message::AutoSelectInputs => self.auto_select_inputs(),
             Message::Recipient(i, RecipientMessage::Delete) => {
                 self.outputs.remove(i);
+                self.check_outputs(ctx);
             }
+            Message::Recipient(i, RecipientMessage::SaveEntry) => {
+                return self.save_address_book_entry(ctx, i);
+            }
+            Message::Recipient(i, RecipientMessage::SelectEntry(address)) => {
+                if let Some(output) = self.outputs.get_mut(i) {
+                    if let Knowledge::Known(entry) =
+                        self.address_book.knowledge(&address, ctx.network)
+                    {
+                        output.label = Some(entry.label.clone());
+                    }
+                    output.address = address;
+                }
+                self.check_outputs(ctx);
+            }
+            Message::Drafts(res) => match res {
+                Ok(drafts) => self.drafts = drafts,
+                Err(e) => tracing::warn!("Failed to load drafts: {}", e),
+            },
+            Message::SpendTransactions(res) => match res {
+                Ok(txs) => self.stored_spend_txs = txs,
+                Err(e) => tracing::warn!("Failed to list spend transactions: {}", e),
+            },
+            Message::ResumeDraft(id) => self.resume_draft(ctx, &id),
+            Message::DeleteDraft(id) => return self.delete_draft(ctx, id),
+            Message::DraftSaved(res) | Message::DraftDeleted(res) => {
+                if let Err(e) = res {
+                    tracing::warn!("Failed to update drafts: {}", e);
+                }
+            }
+            Message::AddressBook(res) => match res {
+                Ok(address_book) => self.address_book = address_book,
+                Err(e) => tracing::warn!("Failed to load address book: {}", e),
+            },
+            Message::AddressBookSaved(res) => {
+                if let Err(e) = res {
+                    self.warning = Some(e);
+                }
+            }
+            Message::DepositAddress(res) => match res {
+                Ok(address) => {
+                    self.own_addresses.insert(address.to_string());
+                    self.check_outputs(ctx);
+                }
+                Err(e) => tracing::warn!("Failed to get deposit address: {}", e),
+            },
+            Message::OnchainTransactions(res) => match res {
+                Ok(txs) => self.add_own_addresses(ctx, txs),
+                Err(e) => tracing::warn!("Failed to list onchain transactions: {}", e),
+            },
             Message::Input(i, msg) => {
                 if let Some(input) = self.vaults.get_mut(i) {
                     input.update(msg);
@@ -222,45 +1156,174 @@ impl State for ManagerSendState {
                 if let Some(output) = self.outputs.get_mut(i) {
                     output.update(msg);
                 }
+                self.check_outputs(ctx);
             }
             _ => {}
         };
         Command::none()
     }
+}
+
+impl State for ManagerSendState {
+    fn update(&mut self, ctx: &Context, message: Message) -> Command<Message> {
+        let cmd = self.on_message(ctx, message);
+        Command::batch(vec![cmd, self.autosave()])
+    }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
         let input_amount = self.input_amount();
         let output_amount = self.output_amount();
-        match &mut self.view {
-            ManagerSendView::SelectOutputs(v) => {
+        match self.view.step {
+            ManagerSendStep::SelectOutputs => {
                 let valid = !self.outputs.iter().any(|o| !o.valid());
-                v.view(
+                let pending_spends = self.pending_spends(ctx);
+                let address_book = &self.address_book;
+                self.view.select_outputs.view(
+                    ctx,
+                    &pending_spends,
                     self.outputs
                         .iter_mut()
                         .enumerate()
-                        .map(|(i, v)| v.view().map(move |msg| Message::Recipient(i, msg)))
+                        .map(|(i, v)| {
+                            v.view(ctx, address_book)
+                                .map(move |msg| Message::Recipient(i, msg))
+                        })
                         .collect(),
+                    &self.bip21,
+                    self.bip21_warning.as_ref(),
+                    &self.recipients_path,
+                    self.recipients_warning.as_ref(),
+                    self.recipients_import.as_ref(),
+                    self.recipients_exported.as_ref(),
                     valid,
                 )
             }
-            ManagerSendView::SelectInputs(v) => v.view(
-                self.vaults
-                    .iter_mut()
-                    .enumerate()
-                    .map(|(i, v)| v.view(ctx).map(move |msg| Message::Input(i, msg)))
-                    .collect(),
-                input_amount > output_amount,
-            ),
-            ManagerSendView::SelectFee(v) => v.view(false),
-            ManagerSendView::Sign(v) => v.view(false),
+            ManagerSendStep::SelectInputs => {
+                let fee = self.estimated_fee();
+                let mut groups: Vec<(String, Vec<Element<Message>>)> = Vec::new();
+                for (i, v) in self.vaults.iter_mut().enumerate() {
+                    let status = v.vault.status.to_string();
+                    let element = v.view(ctx).map(move |msg| Message::Input(i, msg));
+                    match groups.last_mut() {
+                        Some((title, elements)) if *title == status => elements.push(element),
+                        _ => groups.push((status, vec![element])),
+                    }
+                }
+                self.view.select_inputs.view(
+                    ctx,
+                    groups,
+                    input_amount,
+                    output_amount,
+                    fee,
+                    self.warning.as_ref(),
+                    input_amount >= output_amount + fee,
+                )
+            }
+            ManagerSendStep::SelectFee => {
+                let (fee, change) = match &self.spend_tx {
+                    Some((psbt, fee)) => (*fee, Some(self.change(ctx, psbt))),
+                    None => (None, None),
+                };
+                let percent = self.session.fee_warning_percent;
+                let fee_warning = fee
+                    .filter(|fee| fee * 100 > percent * output_amount)
+                    .map(|_| percent);
+                self.view.select_fee.view(
+                    ctx,
+                    &self.feerate,
+                    self.fee_estimates.as_ref(),
+                    self.fee_priority,
+                    fee,
+                    change,
+                    fee_warning,
+                    self.processing,
+                    self.warning.as_ref(),
+                    self.spend_tx.is_some() && !self.processing,
+                )
+            }
+            ManagerSendStep::Sign => {
+                let violations = self.policy_violations(ctx);
+                let (signatures, threshold) = self.manager_signatures();
+                let signed = self.threshold_reached();
+                let our_key = self
+                    .session
+                    .revaultd_config
+                    .manager_config
+                    .as_ref()
+                    .map(|c| c.xpub);
+                if let (Some((psbt, fee)), Some(signer)) = (&self.spend_tx, &mut self.signer) {
+                    self.view.sign.view(
+                        ctx,
+                        self.warning.as_ref(),
+                        psbt,
+                        *fee,
+                        &self.memo,
+                        &violations,
+                        self.view.signatures.view(
+                            ctx,
+                            &signatures,
+                            our_key,
+                            threshold,
+                            &self.coordination_path,
+                            self.coordination_warning.as_ref(),
+                            self.coordination_exported.as_ref(),
+                        ),
+                        signer.view(ctx).map(|msg| {
+                            if let SignMessage::Clipboard(psbt) = msg {
+                                return Message::Clipboard(psbt);
+                            }
+                            Message::Sign(msg)
+                        }),
+                        signed,
+                        self.processing,
+                    )
+                } else {
+                    self.view.success.view()
+                }
+            }
+            ManagerSendStep::Success => self.view.success.view(),
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        if self.signer.as_ref().map(|s| s.animated()).unwrap_or(false) {
+            return iced::time::every(Duration::from_millis(QR_CODE_FRAME_MS)).map(Message::Tick);
+        }
+        Subscription::none()
+    }
+
     fn load(&self) -> Command<Message> {
-        Command::batch(vec![Command::perform(
-            list_vaults(self.revaultd.clone(), None),
-            Message::Vaults,
-        )])
+        Command::batch(vec![
+            Command::perform(
+                list_vaults(self.revaultd.clone(), Some(SPEND_INPUT_STATUSES)),
+                Message::Vaults,
+            ),
+            Command::perform(
+                get_fee_estimates(self.revaultd.clone()),
+                Message::FeeEstimates,
+            ),
+            Command::perform(
+                get_deposit_address(self.revaultd.clone()),
+                Message::DepositAddress,
+            ),
+            Command::perform(
+                list_onchain_txs(self.revaultd.clone()),
+                Message::OnchainTransactions,
+            ),
+            Command::perform(
+                load_address_book(self.session.data_dir.join(address_book::FILE_NAME)),
+                Message::AddressBook,
+            ),
+            Command::perform(list_drafts(self.drafts_dir()), Message::Drafts),
+            Command::perform(
+                list_vaults(self.revaultd.clone(), Some(DAILY_LIMIT_STATUSES)),
+                Message::RecentUnvaults,
+            ),
+            Command::perform(
+                list_spend_txs(self.revaultd.clone()),
+                Message::SpendTransactions,
+            ),
+        ])
     }
 }
 
@@ -270,13 +1333,30 @@ impl From<ManagerSendState> for Box<dyn State> {
     }
 }
 
+/// Maximum number of address book entries suggested while typing a recipient.
+const MAX_ADDRESS_BOOK_SUGGESTIONS: usize = 5;
+
 #[derive(Debug)]
 struct ManagerSendOutput {
     address: String,
     amount: String,
+    /// label and message of the BIP21 payment URI, csv row or address book
+    /// entry the recipient comes from.
+    label: Option<String>,
+    message: Option<String>,
 
-    warning_address: bool,
-    warning_amount: bool,
+    /// new address book entry of the recipient address.
+    entry_label: String,
+    entry_notes: String,
+    entry_whitelisted: bool,
+    entry_warning: Option<String>,
+
+    warning_address: Option<String>,
+    warning_amount: Option<String>,
+    /// the address is also the address of another recipient.
+    duplicate: bool,
+    /// the address belongs to one of our vaults.
+    own_address: bool,
 
     view: ManagerSendOutputView,
 }
@@ -286,12 +1366,36 @@ impl ManagerSendOutput {
         Self {
             address: "".to_string(),
             amount: "".to_string(),
-            warning_address: false,
-            warning_amount: false,
+            label: None,
+            message: None,
+            entry_label: "".to_string(),
+            entry_notes: "".to_string(),
+            entry_whitelisted: false,
+            entry_warning: None,
+            warning_address: None,
+            warning_amount: None,
+            duplicate: false,
+            own_address: false,
             view: ManagerSendOutputView::new(),
         }
     }
 
+    fn from_uri(uri: bip21::Uri) -> Self {
+        let mut output = Self::new();
+        output.address = uri.address.to_string();
+        if let Some(amount) = uri.amount {
+            output.amount =
+                bitcoin::Amount::from_sat(amount).to_string_in(bitcoin::Denomination::Bitcoin);
+        }
+        output.label = uri.label;
+        output.message = uri.message;
+        output
+    }
+
+    fn is_empty(&self) -> bool {
+        self.address.is_empty() && self.amount.is_empty()
+    }
+
     fn amount(&self) -> Result<u64, Error> {
         if self.amount.is_empty() {
             return Ok(0);
@@ -302,41 +1406,137 @@ impl ManagerSendOutput {
         Ok(amount.as_sat())
     }
 
+    /// valid does not check the duplicate flag: recipients sharing an address
+    /// are only warned about, their amounts are merged in the spend transaction.
     fn valid(&self) -> bool {
         !self.address.is_empty()
-            && !self.warning_address
+            && self.warning_address.is_none()
             && !self.amount.is_empty()
-            && !self.warning_amount
+            && self.warning_amount.is_none()
     }
 
-    fn update(&mut self, message: RecipientMessage) {
-        match message {
-            RecipientMessage::AddressEdited(address) => {
-                self.address = address;
-                if !self.address.is_empty() {
-                    self.warning_address = bitcoin::Address::from_str(&self.address).is_err();
+    /// check validates the address against the network and the amount against the
+    /// dust limit of the address.
+    fn check(&mut self, ctx: &Context, own_addresses: &HashSet<String>) {
+        self.warning_address = None;
+        self.warning_amount = None;
+        self.own_address = false;
+
+        let mut address = None;
+        if !self.address.is_empty() {
+            match bitcoin::Address::from_str(&self.address) {
+                Err(_) => {
+                    self.warning_address = Some("Please enter a valid bitcoin address".to_string())
+                }
+                Ok(addr) if !is_address_of_network(&addr, ctx.network) => {
+                    self.warning_address = Some(format!(
+                        "This address is not a {} address",
+                        network_name(ctx.network)
+                    ))
+                }
+                Ok(addr) => {
+                    self.own_address = own_addresses.contains(&addr.to_string());
+                    address = Some(addr);
                 }
             }
-            RecipientMessage::AmountEdited(amount) => {
-                self.amount = amount;
-                if !self.amount.is_empty() {
-                    self.warning_amount = self.amount().is_err();
+        }
+
+        if !self.amount.is_empty() {
+            match self.amount() {
+                Err(_) => self.warning_amount = Some("Please enter a valid amount".to_string()),
+                Ok(amount) => {
+                    if let Some(address) = &address {
+                        let dust = dust_limit(&address.script_pubkey());
+                        if amount < dust {
+                            self.warning_amount = Some(format!(
+                                "Amount is below the dust limit of {} {}",
+                                ctx.converter.converts(dust),
+                                ctx.converter.unit
+                            ));
+                        }
+                    }
                 }
             }
+        }
+    }
+
+    fn update(&mut self, message: RecipientMessage) {
+        match message {
+            RecipientMessage::AddressEdited(address) => self.address = address,
+            RecipientMessage::AmountEdited(amount) => self.amount = amount,
+            RecipientMessage::EntryLabelEdited(label) => self.entry_label = label,
+            RecipientMessage::EntryNotesEdited(notes) => self.entry_notes = notes,
+            RecipientMessage::EntryWhitelisted(whitelisted) => self.entry_whitelisted = whitelisted,
             _ => {}
         };
     }
 
-    fn view(&mut self) -> Element<RecipientMessage> {
+    fn view(&mut self, ctx: &Context, address_book: &AddressBook) -> Element<RecipientMessage> {
+        let (knowledge, suggestions) = if self.address.is_empty() {
+            (None, Vec::new())
+        } else if bitcoin::Address::from_str(&self.address).is_err() {
+            let mut suggestions = address_book.search(&self.address, ctx.network);
+            suggestions.truncate(MAX_ADDRESS_BOOK_SUGGESTIONS);
+            (None, suggestions)
+        } else {
+            (
+                Some(address_book.knowledge(&self.address, ctx.network)),
+                Vec::new(),
+            )
+        };
         self.view.view(
             &self.address,
             &self.amount,
-            &self.warning_address,
-            &self.warning_amount,
+            knowledge,
+            &suggestions,
+            &self.entry_label,
+            &self.entry_notes,
+            self.entry_whitelisted,
+            self.entry_warning.as_ref(),
+            self.label.as_ref(),
+            self.message.as_ref(),
+            self.warning_address.as_ref(),
+            self.warning_amount.as_ref(),
+            self.duplicate,
+            self.own_address,
         )
     }
 }
 
+/// is_address_of_network returns true if the address can be used on the network.
+/// Testnet and regtest share the same base58 prefixes.
+fn is_address_of_network(address: &bitcoin::Address, network: Network) -> bool {
+    match (address.network, network) {
+        (a, b) if a == b => true,
+        (Network::Testnet, Network::Regtest) | (Network::Regtest, Network::Testnet) => {
+            !matches!(address.payload, Payload::WitnessProgram { .. })
+        }
+        _ => false,
+    }
+}
+
+fn network_name(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "mainnet",
+        Network::Testnet => "testnet",
+        Network::Regtest => "regtest",
+    }
+}
+
+/// dust_limit returns the minimum amount of an output with the given script
+/// to be relayed with the default bitcoind dust relay fee of 3 sat/vbyte.
+fn dust_limit(script: &Script) -> u64 {
+    // serialized output: amount, script length and script.
+    let output_size = 8 + 1 + script.len() as u64;
+    // size of the input spending the output.
+    let input_size = if script.is_witness_program() {
+        32 + 4 + 1 + (107 / 4) + 4
+    } else {
+        32 + 4 + 1 + 107 + 4
+    };
+    3 * (output_size + input_size)
+}
+
 #[derive(Debug)]
 struct ManagerSendInput {
     vault: Vault,
@@ -357,12 +1557,15 @@ impl ManagerSendInput {
             &self.vault.outpoint(),
             &self.vault.amount,
             self.selected,
+            self.vault.status.spend_restriction(),
         )
     }
 
     pub fn update(&mut self, msg: InputMessage) {
         match msg {
-            InputMessage::Selected(selected) => self.selected = selected,
+            InputMessage::Selected(selected) => {
+                self.selected = selected && self.vault.status.is_spendable()
+            }
         }
     }
 }
@@ -389,7 +1592,7 @@ impl ManagerNetworkState {
 }
 
 impl State for ManagerNetworkState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::BlockHeight(b) => {
                 match b {
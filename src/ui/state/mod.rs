@@ -1,9 +1,12 @@
+mod batch;
 pub mod charging;
 mod cmd;
 mod deposit;
 mod history;
 pub mod installing;
 pub mod manager;
+mod monitor;
+pub mod notification;
 mod sign;
 pub mod stakeholder;
 mod vault;
@@ -15,13 +18,18 @@ pub use deposit::DepositState;
 pub use history::HistoryState;
 pub use installing::InstallingState;
 pub use manager::{ManagerHomeState, ManagerNetworkState, ManagerSendState};
-pub use stakeholder::{StakeholderACKFundsState, StakeholderHomeState, StakeholderNetworkState};
+pub use monitor::MonitorState;
+pub use notification::NotificationsState;
+pub use stakeholder::{
+    StakeholderACKFundsState, StakeholderDelegateState, StakeholderHomeState,
+    StakeholderNetworkState,
+};
 
 use super::{message::Message, view::Context};
 
 pub trait State {
     fn view(&mut self, ctx: &Context) -> Element<Message>;
-    fn update(&mut self, message: Message) -> Command<Message>;
+    fn update(&mut self, ctx: &Context, message: Message) -> Command<Message>;
     fn subscription(&self) -> Subscription<Message> {
         Subscription::none()
     }
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::convert::From;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitcoin::{Address, Transaction, Txid};
+use iced::{Command, Element, Subscription};
+
+use super::{
+    cmd::{get_blockheight, list_spend_txs, list_vaults, list_vaults_onchain_txs, revault},
+    State,
+};
+
+use crate::revaultd::{
+    model::{StoredSpendTransaction, Vault, VaultStatus, VaultTransactions},
+    RevaultD,
+};
+
+use crate::ui::{
+    error::Error,
+    message::Message,
+    view::{Context, MonitorView, MonitoredVault},
+};
+
+/// Statuses of the vaults being unvaulted, the canceled vaults are kept
+/// until their cancel transaction is confirmed.
+const MONITOR_STATUSES: &[VaultStatus] = &[
+    VaultStatus::Unvaulting,
+    VaultStatus::Unvaulted,
+    VaultStatus::Canceling,
+];
+
+/// Interval between two refreshes of the monitored vaults.
+const REFRESH_INTERVAL_SECS: u64 = 10;
+
+/// MonitorState lists the vaults being unvaulted with the destination of their
+/// spend transaction and the blocks left to cancel it.
+#[derive(Debug)]
+pub struct MonitorState {
+    revaultd: Arc<RevaultD>,
+    view: MonitorView,
+
+    blockheight: Option<u64>,
+    warning: Option<Error>,
+
+    vaults: Vec<Vault>,
+    transactions: Vec<VaultTransactions>,
+    spend_txs: Vec<StoredSpendTransaction>,
+    /// errors of the cancel requests by vault outpoint.
+    cancel_errors: HashMap<String, String>,
+}
+
+impl MonitorState {
+    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+        MonitorState {
+            revaultd,
+            view: MonitorView::new(),
+            blockheight: None,
+            warning: None,
+            vaults: Vec::new(),
+            transactions: Vec::new(),
+            spend_txs: Vec::new(),
+            cancel_errors: HashMap::new(),
+        }
+    }
+
+    /// spend_tx returns the spend transaction of the unvault transaction: the broadcast one
+    /// or the one stored by revaultd.
+    fn spend_tx<'a>(
+        &'a self,
+        txs: &'a VaultTransactions,
+        unvault_txid: &Txid,
+    ) -> Option<&'a Transaction> {
+        if let Some(spend) = &txs.spend {
+            return Some(&spend.tx);
+        }
+        self.spend_txs
+            .iter()
+            .map(|spend_tx| &spend_tx.psbt.global.unsigned_tx)
+            .find(|tx| {
+                tx.input
+                    .iter()
+                    .any(|input| input.previous_output.txid == *unvault_txid)
+            })
+    }
+
+    fn monitored_vault(&self, network: bitcoin::Network, vault: &Vault) -> MonitoredVault {
+        let outpoint = vault.outpoint();
+        let mut monitored = MonitoredVault {
+            vault: vault.clone(),
+            destinations: Vec::new(),
+            remaining_blocks: None,
+            unvault_confirmed: false,
+            cancel_error: self.cancel_errors.get(&outpoint).cloned(),
+        };
+        let txs = match self
+            .transactions
+            .iter()
+            .find(|txs| txs.vault_outpoint == outpoint)
+        {
+            Some(txs) => txs,
+            None => return monitored,
+        };
+        let unvault = match &txs.unvault {
+            Some(unvault) => unvault,
+            None => return monitored,
+        };
+        let unvault_txid = unvault.tx.txid();
+        monitored.unvault_confirmed = unvault.blockheight.is_some();
+        if let Some(spend_tx) = self.spend_tx(txs, &unvault_txid) {
+            monitored.destinations = spend_tx
+                .output
+                .iter()
+                .map(|output| {
+                    let address = Address::from_script(&output.script_pubkey, network)
+                        .map(|address| address.to_string())
+                        .unwrap_or_else(|| output.script_pubkey.to_string());
+                    (address, output.value)
+                })
+                .collect();
+            if let Some(csv) = unvault_csv(spend_tx, &unvault_txid) {
+                monitored.remaining_blocks = Some(match (unvault.blockheight, self.blockheight) {
+                    (Some(height), Some(current)) => (height + csv as u64).saturating_sub(current),
+                    _ => csv as u64,
+                });
+            }
+        }
+        monitored
+    }
+}
+
+/// unvault_csv returns the relative timelock in blocks of the spend transaction input
+/// spending the unvault transaction, which is the CSV of the unvault output.
+fn unvault_csv(spend_tx: &Transaction, unvault_txid: &Txid) -> Option<u32> {
+    // BIP68: the timelock is disabled by the bit 31 and in time units with the bit 22.
+    const DISABLE_FLAG: u32 = 1 << 31;
+    const TYPE_FLAG: u32 = 1 << 22;
+    spend_tx
+        .input
+        .iter()
+        .find(|input| input.previous_output.txid == *unvault_txid)
+        .map(|input| input.sequence)
+        .filter(|sequence| sequence & (DISABLE_FLAG | TYPE_FLAG) == 0)
+        .map(|sequence| sequence & 0xffff)
+}
+
+impl State for MonitorState {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
+        match message {
+            Message::Tick(_) => return self.load(),
+            Message::BlockHeight(b) => match b {
+                Ok(height) => self.blockheight = height.into(),
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::Vaults(res) => match res {
+                Ok(vaults) => {
+                    self.warning = None;
+                    let outpoints: Vec<String> = vaults.iter().map(|v| v.outpoint()).collect();
+                    self.vaults = vaults;
+                    if !outpoints.is_empty() {
+                        return Command::perform(
+                            list_vaults_onchain_txs(self.revaultd.clone(), outpoints),
+                            Message::OnchainTransactions,
+                        );
+                    }
+                }
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::OnchainTransactions(res) => match res {
+                Ok(txs) => self.transactions = txs,
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            // Only the managers can list the spend transactions, the destinations
+            // are otherwise known once the spend transaction is broadcast.
+            Message::SpendTransactions(res) => {
+                if let Ok(spend_txs) = res {
+                    self.spend_txs = spend_txs;
+                }
+            }
+            Message::CancelVault(outpoint) => {
+                self.cancel_errors.remove(&outpoint);
+                return Command::perform(
+                    revault(self.revaultd.clone(), outpoint.clone()),
+                    move |res| Message::VaultCanceled(outpoint.clone(), res),
+                );
+            }
+            Message::VaultCanceled(outpoint, res) => match res {
+                Ok(()) => return self.load(),
+                Err(e) => {
+                    self.cancel_errors.insert(outpoint, e.to_string());
+                }
+            },
+            _ => {}
+        };
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(Duration::from_secs(REFRESH_INTERVAL_SECS)).map(Message::Tick)
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let network = ctx.network;
+        let vaults = self
+            .vaults
+            .iter()
+            .map(|vault| self.monitored_vault(network, vault))
+            .collect();
+        self.view.view(
+            ctx,
+            self.warning.as_ref(),
+            self.blockheight.as_ref(),
+            vaults,
+        )
+    }
+
+    fn load(&self) -> Command<Message> {
+        Command::batch(vec![
+            Command::perform(get_blockheight(self.revaultd.clone()), Message::BlockHeight),
+            Command::perform(
+                list_vaults(self.revaultd.clone(), Some(MONITOR_STATUSES)),
+                Message::Vaults,
+            ),
+            Command::perform(
+                list_spend_txs(self.revaultd.clone()),
+                Message::SpendTransactions,
+            ),
+        ])
+    }
+}
+
+impl From<MonitorState> for Box<dyn State> {
+    fn from(s: MonitorState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
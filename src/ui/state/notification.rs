@@ -0,0 +1,93 @@
+use std::convert::From;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iced::{Command, Element};
+
+use super::{
+    cmd::{
+        list_vaults, load_notification_preferences, save_notification_preferences,
+        show_desktop_notification,
+    },
+    State,
+};
+
+use crate::{
+    notification::{Notification, NotificationCenter, Preferences, WATCHED_STATUSES},
+    revaultd::RevaultD,
+};
+
+use crate::ui::{
+    message::Message,
+    view::{Context, NotificationsView},
+};
+
+/// Interval in seconds between two listings of the vaults looking for new events.
+pub const POLL_INTERVAL_SECS: u64 = 30;
+
+/// poll lists the vaults having a status raising an event to compare their statuses
+/// with the previous ones.
+pub fn poll(revaultd: Arc<RevaultD>) -> Command<Message> {
+    Command::perform(
+        list_vaults(revaultd, Some(WATCHED_STATUSES)),
+        Message::NotificationVaults,
+    )
+}
+
+/// show raises the desktop notification without blocking the update of the application.
+pub fn show(notification: Notification) -> Command<Message> {
+    Command::perform(
+        show_desktop_notification(notification),
+        Message::NotificationShown,
+    )
+}
+
+pub fn load_preferences(data_dir: PathBuf) -> Command<Message> {
+    Command::perform(
+        load_notification_preferences(data_dir),
+        Message::NotificationPreferences,
+    )
+}
+
+pub fn save_preferences(data_dir: PathBuf, preferences: Preferences) -> Command<Message> {
+    Command::perform(
+        save_notification_preferences(data_dir, preferences),
+        Message::NotificationPreferencesSaved,
+    )
+}
+
+/// NotificationsState displays the notification center, the notifications
+/// themselves are kept by the application which sends each of their updates.
+#[derive(Debug)]
+pub struct NotificationsState {
+    notifications: NotificationCenter,
+    view: NotificationsView,
+}
+
+impl NotificationsState {
+    pub fn new(notifications: NotificationCenter) -> Self {
+        NotificationsState {
+            notifications,
+            view: NotificationsView::new(),
+        }
+    }
+}
+
+impl State for NotificationsState {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
+        if let Message::NotificationCenter(notifications) = message {
+            self.notifications = notifications;
+        }
+        Command::none()
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        self.view.view(ctx, &self.notifications)
+    }
+}
+
+impl From<NotificationsState> for Box<dyn State> {
+    fn from(s: NotificationsState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
@@ -1,21 +1,64 @@
-use bitcoin::{base64, consensus::encode, util::psbt::PartiallySignedTransaction as Psbt};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bitcoin::{
+    consensus::encode,
+    util::{
+        bip32::{ExtendedPubKey, Fingerprint},
+        psbt::PartiallySignedTransaction as Psbt,
+    },
+};
 
 use iced::{Command, Element};
 
 use crate::{
+    psbt::{self, check_cosigned_psbt, check_signed_psbt, Conflict},
     revault::TransactionKind,
+    revaultd::config::Config,
+    signer::{external::ExternalSigner, Device},
     ui::{
         message::SignMessage,
+        session::Session,
+        state::cmd::{
+            get_device_descriptors, list_devices, load_psbt, load_psbt_from_qr_codes, save_psbt,
+            sign_with_device,
+        },
         view::{
             sign::{DirectSignatureView, IndirectSignatureView},
             Context,
         },
     },
+    ur,
 };
 
+/// Maximum size in bytes of the psbt fragment displayed by a QR code.
+const QR_CODE_MAX_FRAGMENT_LEN: usize = 120;
+
+/// Display duration of each part of an animated QR code.
+pub const QR_CODE_FRAME_MS: u64 = 500;
+
+/// our_keys returns the extended public keys of the revaultd configuration
+/// that are expected to sign a transaction of the given kind.
+pub fn our_keys(config: &Config, transaction_kind: &TransactionKind) -> Vec<ExtendedPubKey> {
+    match transaction_kind {
+        TransactionKind::Spend => config
+            .manager_config
+            .as_ref()
+            .map(|cfg| vec![cfg.xpub])
+            .unwrap_or_default(),
+        _ => config
+            .stakeholder_config
+            .as_ref()
+            .map(|cfg| vec![cfg.xpub])
+            .unwrap_or_default(),
+    }
+}
+
 /// SignState is a general widget to handle the signature of a Psbt.
 #[derive(Debug)]
 pub struct SignState {
+    session: Arc<Session>,
     pub original_psbt: Psbt,
     pub signed_psbt: Option<Psbt>,
     pub transaction_kind: TransactionKind,
@@ -27,30 +70,171 @@ pub struct SignState {
 pub enum SignMethod {
     /// DirectSignature means that a hard module directly
     /// connect to the GUI and signs the given PSBT.
-    DirectSignature { view: DirectSignatureView },
+    DirectSignature {
+        processing: bool,
+        warning: Option<String>,
+        devices: Vec<Device>,
+        /// index of the device selected to sign.
+        selected: Option<usize>,
+        /// true if the selected device holds one of our keys,
+        /// unknown until the device descriptors are retrieved.
+        holds_our_key: Option<bool>,
+        view: DirectSignatureView,
+    },
     /// IndirectSignature means that the PSBT is exported and
     /// then imported once signed on a air gapped device for example.
     IndirectSignature {
         processing: bool,
         warning: Option<String>,
         psbt_input: String,
+        /// original psbt with the signatures of all the imported psbts.
+        combined: Psbt,
+        /// number of signed psbts merged in the combined psbt.
+        imported: usize,
+        conflicts: Vec<Conflict>,
+        /// path of the signed psbt file to load.
+        psbt_path: String,
+        /// path of the last saved psbt file.
+        saved: Option<PathBuf>,
         view: IndirectSignatureView,
     },
 }
 
+impl SignMethod {
+    fn new_direct_signature() -> Self {
+        Self::DirectSignature {
+            processing: false,
+            warning: None,
+            devices: Vec::new(),
+            selected: None,
+            holds_our_key: None,
+            view: DirectSignatureView::new(),
+        }
+    }
+
+    fn set_warning(&mut self, message: String) {
+        match self {
+            Self::DirectSignature { warning, .. } => *warning = Some(message),
+            Self::IndirectSignature { warning, .. } => *warning = Some(message),
+        }
+    }
+}
+
 impl SignState {
-    pub fn new(original_psbt: Psbt, transaction_kind: TransactionKind) -> Self {
+    pub fn new(
+        session: Arc<Session>,
+        original_psbt: Psbt,
+        transaction_kind: TransactionKind,
+    ) -> Self {
         SignState {
+            session,
             original_psbt,
             transaction_kind,
             signed_psbt: None,
-            method: SignMethod::DirectSignature {
-                view: DirectSignatureView::new(),
-            },
+            method: SignMethod::new_direct_signature(),
+        }
+    }
+
+    fn our_keys(&self) -> Vec<ExtendedPubKey> {
+        our_keys(&self.session.revaultd_config, &self.transaction_kind)
+    }
+
+    /// signer returns the external signer driving the devices if one is configured.
+    fn signer(&self, ctx: &Context) -> Option<ExternalSigner> {
+        self.session
+            .signer_path
+            .as_ref()
+            .map(|path| ExternalSigner::new(path.clone(), ctx.network))
+    }
+
+    fn list_devices(&mut self, ctx: &Context) -> Command<SignMessage> {
+        let signer = self.signer(ctx);
+        if let SignMethod::DirectSignature { warning, .. } = &mut self.method {
+            match signer {
+                Some(signer) => {
+                    *warning = None;
+                    return Command::perform(list_devices(signer), SignMessage::DevicesListed);
+                }
+                None => {
+                    *warning =
+                        Some("No signer configured, please set REVAULTGUI_SIGNER".to_string())
+                }
+            }
         }
+        Command::none()
     }
 
-    pub fn update(&mut self, message: SignMessage) -> Command<SignMessage> {
+    /// animated returns true if the psbt is displayed with an animated QR code,
+    /// the state must then receive SignMessage::NextQrCode periodically.
+    pub fn animated(&self) -> bool {
+        if let SignMethod::IndirectSignature { view, .. } = &self.method {
+            return view.animated();
+        }
+        false
+    }
+
+    /// import checks the given signed psbt and merges its signatures with the ones
+    /// of the previously imported psbts.
+    fn import(&mut self, psbt: Psbt) {
+        if let SignMethod::IndirectSignature {
+            warning,
+            psbt_input,
+            combined,
+            imported,
+            conflicts,
+            ..
+        } = &mut self.method
+        {
+            // Signatures of co-signers are accepted, the signature of one of our
+            // keys is only required for the combined psbt.
+            let signatures = psbt::signature_counts(combined);
+            match check_cosigned_psbt(&self.original_psbt, &psbt, &self.transaction_kind)
+                .and_then(|_| psbt::combine(combined, &psbt))
+            {
+                Ok(new_conflicts) => {
+                    *psbt_input = "".to_string();
+                    conflicts.extend(new_conflicts);
+                    // The same psbt may be imported twice, only count the ones
+                    // bringing new signatures.
+                    if psbt::signature_counts(combined) == signatures {
+                        *warning = Some(psbt::Error::NoNewSignature.to_string());
+                    } else {
+                        *warning = None;
+                        *imported += 1;
+                    }
+                }
+                Err(e) => *warning = Some(e.to_string()),
+            }
+        }
+    }
+
+    /// confirm checks the combined psbt and stores it as the signed psbt if it is valid.
+    fn confirm(&mut self) {
+        let our_keys = self.our_keys();
+        if let SignMethod::IndirectSignature {
+            warning,
+            processing,
+            combined,
+            ..
+        } = &mut self.method
+        {
+            match check_signed_psbt(
+                &self.original_psbt,
+                combined,
+                &self.transaction_kind,
+                &our_keys,
+            ) {
+                Ok(()) => {
+                    *warning = None;
+                    self.signed_psbt = Some(combined.clone());
+                    *processing = true;
+                }
+                Err(e) => *warning = Some(e.to_string()),
+            }
+        }
+    }
+
+    pub fn update(&mut self, ctx: &Context, message: SignMessage) -> Command<SignMessage> {
         match message {
             SignMessage::PsbtEdited(psbt) => {
                 if let SignMethod::IndirectSignature {
@@ -63,38 +247,256 @@ impl SignState {
                     *psbt_input = psbt;
                 }
             }
+            SignMessage::PsbtPathEdited(path) => {
+                if let SignMethod::IndirectSignature {
+                    psbt_path, warning, ..
+                } = &mut self.method
+                {
+                    *warning = None;
+                    *psbt_path = path;
+                }
+            }
             SignMessage::Sign => {
                 if let SignMethod::IndirectSignature {
                     psbt_input,
                     warning,
-                    processing,
                     ..
                 } = &mut self.method
                 {
                     if !psbt_input.is_empty() {
-                        self.signed_psbt = base64::decode(&psbt_input)
-                            .ok()
-                            .and_then(|bytes| encode::deserialize(&bytes).ok());
-                        if self.signed_psbt.is_some() {
-                            *processing = true;
-                        } else {
-                            *warning = Some("Please enter valid PSBT".to_string());
+                        match psbt::from_bytes(psbt_input.as_bytes()) {
+                            None => *warning = Some("Please enter valid PSBT".to_string()),
+                            Some(psbt) => self.import(psbt),
                         }
                     }
                 }
             }
+            SignMessage::Confirm => self.confirm(),
+            SignMessage::Save(format) => {
+                let path = self.session.psbt_dir.join(psbt::file_name(
+                    &self.original_psbt,
+                    &self.transaction_kind,
+                    format,
+                ));
+                return Command::perform(
+                    save_psbt(path, self.original_psbt.clone(), format),
+                    SignMessage::Saved,
+                );
+            }
+            SignMessage::Saved(res) => {
+                if let SignMethod::IndirectSignature { warning, saved, .. } = &mut self.method {
+                    match res {
+                        Ok(path) => {
+                            *warning = None;
+                            *saved = Some(path);
+                        }
+                        Err(e) => *warning = Some(e.to_string()),
+                    }
+                }
+            }
+            SignMessage::Load => {
+                if let SignMethod::IndirectSignature { psbt_path, .. } = &self.method {
+                    if !psbt_path.is_empty() {
+                        // Relative paths are relative to the PSBT exchange directory.
+                        let path = self.session.psbt_dir.join(psbt_path.trim());
+                        return Command::perform(load_psbt(path), SignMessage::Loaded);
+                    }
+                }
+            }
+            SignMessage::LoadQrCodes => {
+                if let SignMethod::IndirectSignature { psbt_path, .. } = &self.method {
+                    if !psbt_path.is_empty() {
+                        let path = self.session.psbt_dir.join(psbt_path.trim());
+                        return Command::perform(
+                            load_psbt_from_qr_codes(path),
+                            SignMessage::Loaded,
+                        );
+                    }
+                }
+            }
+            SignMessage::ToggleQrCode => {
+                if let SignMethod::IndirectSignature { view, .. } = &mut self.method {
+                    if view.has_qr_codes() {
+                        view.clear_qr_codes();
+                    } else {
+                        view.load_qr_codes(&ur::encode(
+                            &encode::serialize(&self.original_psbt),
+                            QR_CODE_MAX_FRAGMENT_LEN,
+                        ));
+                    }
+                }
+            }
+            SignMessage::NextQrCode => {
+                if let SignMethod::IndirectSignature { view, .. } = &mut self.method {
+                    view.next_qr_code();
+                }
+            }
+            SignMessage::Loaded(res) => match res {
+                Ok(psbt) => self.import(psbt),
+                Err(e) => {
+                    if let SignMethod::IndirectSignature { warning, .. } = &mut self.method {
+                        *warning = Some(e.to_string());
+                    }
+                }
+            },
+            SignMessage::ListDevices => return self.list_devices(ctx),
+            SignMessage::DevicesListed(res) => {
+                if let SignMethod::DirectSignature {
+                    warning,
+                    devices,
+                    selected,
+                    holds_our_key,
+                    ..
+                } = &mut self.method
+                {
+                    *selected = None;
+                    *holds_our_key = None;
+                    match res {
+                        Ok(list) => *devices = list,
+                        Err(e) => *warning = Some(e.to_string()),
+                    }
+                }
+            }
+            SignMessage::SelectDevice(i) => {
+                let signer = self.signer(ctx);
+                if let SignMethod::DirectSignature {
+                    warning,
+                    devices,
+                    selected,
+                    holds_our_key,
+                    ..
+                } = &mut self.method
+                {
+                    if let Some(device) = devices.get(i) {
+                        *holds_our_key = None;
+                        match (&device.fingerprint, signer) {
+                            (Some(fingerprint), Some(signer)) => {
+                                *warning = None;
+                                *selected = Some(i);
+                                return Command::perform(
+                                    get_device_descriptors(signer, fingerprint.clone()),
+                                    SignMessage::Descriptors,
+                                );
+                            }
+                            _ => {
+                                *selected = None;
+                                *warning = Some(device.error.clone().unwrap_or_else(|| {
+                                    "Device is not ready, please unlock it".to_string()
+                                }));
+                            }
+                        }
+                    }
+                }
+            }
+            SignMessage::Descriptors(res) => {
+                let our_keys = self.our_keys();
+                if let SignMethod::DirectSignature {
+                    holds_our_key,
+                    devices,
+                    selected,
+                    ..
+                } = &mut self.method
+                {
+                    let fingerprint = selected
+                        .and_then(|i| devices.get(i))
+                        .and_then(|device| device.fingerprint.as_ref())
+                        .and_then(|fingerprint| Fingerprint::from_str(fingerprint).ok());
+                    // Devices not supporting descriptors can still sign.
+                    *holds_our_key = match (res, fingerprint) {
+                        (Ok(descriptors), Some(fingerprint)) => Some(
+                            our_keys
+                                .iter()
+                                .any(|key| descriptors.holds_key(&fingerprint, key)),
+                        ),
+                        _ => None,
+                    };
+                }
+            }
+            SignMessage::SignWithDevice => {
+                let signer = self.signer(ctx);
+                if let SignMethod::DirectSignature {
+                    processing,
+                    warning,
+                    devices,
+                    selected,
+                    ..
+                } = &mut self.method
+                {
+                    let fingerprint = selected
+                        .and_then(|i| devices.get(i))
+                        .and_then(|device| device.fingerprint.clone());
+                    if let (Some(fingerprint), Some(signer)) = (fingerprint, signer) {
+                        *warning = None;
+                        *processing = true;
+                        return Command::perform(
+                            sign_with_device(signer, fingerprint, self.original_psbt.clone()),
+                            SignMessage::DeviceSigned,
+                        );
+                    }
+                }
+            }
+            SignMessage::DeviceSigned(res) => {
+                let our_keys = self.our_keys();
+                let original_psbt = &self.original_psbt;
+                let transaction_kind = &self.transaction_kind;
+                let res = res.map_err(|e| e.to_string()).and_then(|psbt| {
+                    check_signed_psbt(original_psbt, &psbt, transaction_kind, &our_keys)
+                        .map(|_| psbt)
+                        .map_err(|e| e.to_string())
+                });
+                if let SignMethod::DirectSignature {
+                    processing,
+                    warning,
+                    ..
+                } = &mut self.method
+                {
+                    match res {
+                        Ok(psbt) => self.signed_psbt = Some(psbt),
+                        Err(e) => {
+                            *processing = false;
+                            *warning = Some(e);
+                        }
+                    }
+                }
+            }
+            SignMessage::SignWithKey => {
+                if let Some(signer) = &self.session.software_signer {
+                    let our_keys = self.our_keys();
+                    let res = signer
+                        .sign(&self.original_psbt, self.transaction_kind.sighash_type())
+                        .map_err(|e| e.to_string())
+                        .and_then(|psbt| {
+                            check_signed_psbt(
+                                &self.original_psbt,
+                                &psbt,
+                                &self.transaction_kind,
+                                &our_keys,
+                            )
+                            .map(|_| psbt)
+                            .map_err(|e| e.to_string())
+                        });
+                    match res {
+                        Ok(psbt) => self.signed_psbt = Some(psbt),
+                        Err(e) => self.method.set_warning(e),
+                    }
+                }
+            }
             SignMessage::ChangeMethod => {
                 if let SignMethod::DirectSignature { .. } = self.method {
                     self.method = SignMethod::IndirectSignature {
                         processing: false,
                         warning: None,
                         psbt_input: "".to_string(),
+                        combined: self.original_psbt.clone(),
+                        imported: 0,
+                        conflicts: Vec::new(),
+                        psbt_path: "".to_string(),
+                        saved: None,
                         view: IndirectSignatureView::new(),
                     }
                 } else {
-                    self.method = SignMethod::DirectSignature {
-                        view: DirectSignatureView::new(),
-                    }
+                    self.method = SignMethod::new_direct_signature();
+                    return self.list_devices(ctx);
                 }
             }
             _ => {}
@@ -104,10 +506,32 @@ impl SignState {
 
     pub fn view(&mut self, ctx: &Context) -> Element<SignMessage> {
         match &mut self.method {
-            SignMethod::DirectSignature { view } => view.view(ctx, &self.transaction_kind),
+            SignMethod::DirectSignature {
+                processing,
+                warning,
+                devices,
+                selected,
+                holds_our_key,
+                view,
+            } => view.view(
+                ctx,
+                &processing,
+                &self.transaction_kind,
+                &devices,
+                *selected,
+                *holds_our_key,
+                self.session.software_signer.is_some(),
+                self.session.software_signer_error.as_ref(),
+                warning.as_ref(),
+            ),
             SignMethod::IndirectSignature {
                 processing,
                 psbt_input,
+                combined,
+                imported,
+                conflicts,
+                psbt_path,
+                saved,
                 view,
                 warning,
             } => view.view(
@@ -116,6 +540,12 @@ impl SignState {
                 &self.transaction_kind,
                 &self.original_psbt,
                 &psbt_input,
+                combined,
+                *imported,
+                conflicts,
+                &psbt_path,
+                &self.session.psbt_dir,
+                saved.as_ref(),
                 warning.as_ref(),
             ),
         }
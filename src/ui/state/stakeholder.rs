@@ -1,10 +1,14 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
 
-use iced::{Command, Element};
+use iced::{Command, Element, Subscription};
 
-use crate::revault::TransactionKind;
+use crate::{
+    revault::TransactionKind,
+    revocation::{self, SignedRevocation},
+};
 
 use crate::revaultd::{
     model::{RevocationTransactions, Vault, VaultStatus},
@@ -13,16 +17,22 @@ use crate::revaultd::{
 
 use crate::ui::{
     error::Error,
-    message::{DepositMessage, Message, SignMessage},
+    message::{BatchMessage, DelegateMessage, DepositMessage, Message, SignMessage},
+    session::Session,
     state::{
-        cmd::{get_blockheight, get_revocation_txs, list_vaults, set_revocation_txs},
-        sign::SignState,
+        batch::RevocationBatch,
+        cmd::{
+            delete_signed_revocation, get_blockheight, get_revocation_txs, get_unvault_tx,
+            list_vaults, load_signed_revocation, save_signed_revocation, set_revocation_txs,
+            set_unvault_tx,
+        },
+        sign::{our_keys, SignState, QR_CODE_FRAME_MS},
         State,
     },
     view::{
         stakeholder::{stakeholder_deposit_pending, stakeholder_deposit_signed},
-        Context, StakeholderACKDepositView, StakeholderACKFundsView, StakeholderHomeView,
-        StakeholderNetworkView,
+        Context, StakeholderACKDepositView, StakeholderACKFundsView, StakeholderDelegateView,
+        StakeholderHomeView, StakeholderNetworkView,
     },
 };
 
@@ -33,6 +43,8 @@ pub struct StakeholderHomeState {
 
     /// funds without presigned revocation transactions.
     unsecured_fund_balance: u64,
+    /// funds with presigned revocation transactions that are not delegated yet.
+    secured_fund_balance: u64,
     /// balance as active and inactive tuple.
     balance: (u64, u64),
     view: StakeholderHomeView,
@@ -45,6 +57,7 @@ impl StakeholderHomeState {
             warning: None,
             view: StakeholderHomeView::new(),
             unsecured_fund_balance: 0,
+            secured_fund_balance: 0,
             balance: (0, 0),
         }
     }
@@ -57,6 +70,7 @@ impl StakeholderHomeState {
         let mut active_amount: u64 = 0;
         let mut inactive_amount: u64 = 0;
         let mut unsecured_amount: u64 = 0;
+        let mut secured_amount: u64 = 0;
         for vault in vaults {
             match vault.status {
                 VaultStatus::Active | VaultStatus::Unvaulting | VaultStatus::Unvaulted => {
@@ -68,6 +82,7 @@ impl StakeholderHomeState {
                 }
                 VaultStatus::Secured => {
                     inactive_amount += vault.amount;
+                    secured_amount += vault.amount;
                 }
                 _ => {}
             }
@@ -75,11 +90,12 @@ impl StakeholderHomeState {
 
         self.balance = (active_amount, inactive_amount);
         self.unsecured_fund_balance = unsecured_amount;
+        self.secured_fund_balance = secured_amount;
     }
 }
 
 impl State for StakeholderHomeState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         if let Message::Vaults(res) = message {
             match res {
                 Ok(vaults) => self.update_vaults(vaults),
@@ -96,6 +112,7 @@ impl State for StakeholderHomeState {
             Vec::new(),
             &self.balance,
             &self.unsecured_fund_balance,
+            &self.secured_fund_balance,
         )
     }
 
@@ -135,7 +152,7 @@ impl StakeholderNetworkState {
 }
 
 impl State for StakeholderNetworkState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, _ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::BlockHeight(b) => {
                 match b {
@@ -177,30 +194,44 @@ impl From<StakeholderNetworkState> for Box<dyn State> {
 #[derive(Debug)]
 pub struct StakeholderACKFundsState {
     revaultd: Arc<RevaultD>,
+    session: Arc<Session>,
     warning: Option<Error>,
 
     balance: u64,
     deposits: Vec<Deposit>,
+    /// batch signing of the revocation transactions of all the pending deposits.
+    batch: Option<RevocationBatch>,
     view: StakeholderACKFundsView,
 }
 
 impl StakeholderACKFundsState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, session: Arc<Session>) -> Self {
         StakeholderACKFundsState {
             revaultd,
+            session,
             warning: None,
             deposits: Vec::new(),
+            batch: None,
             view: StakeholderACKFundsView::new(),
             balance: 0,
         }
     }
 
+    /// start_signing_deposit starts the signature of the first pending deposit
+    /// from the given index, unless a deposit is already being signed.
     fn start_signing_deposit(&mut self, index: usize) -> Command<Message> {
-        if let Some(Deposit::Pending { vault }) = self.deposits.get_mut(index) {
-            return Command::perform(
-                get_revocation_txs(self.revaultd.clone(), vault.outpoint()),
-                move |res| Message::Deposit(index, DepositMessage::RevocationTransactions(res)),
-            );
+        if self.deposits.iter().any(|deposit| deposit.is_signing()) {
+            return Command::none();
+        }
+        // Deposits resumed before the index are signed after the following ones.
+        let len = self.deposits.len();
+        if let Some(index) = (0..len)
+            .map(|i| (index + i) % len)
+            .find(|i| matches!(self.deposits[*i], Deposit::Pending { .. }))
+        {
+            return self.deposits[index]
+                .fetch(self.revaultd.clone())
+                .map(move |msg| Message::Deposit(index, msg));
         }
         Command::none()
     }
@@ -224,20 +255,49 @@ impl StakeholderACKFundsState {
 }
 
 impl State for StakeholderACKFundsState {
-    fn update(&mut self, message: Message) -> Command<Message> {
+    fn update(&mut self, ctx: &Context, message: Message) -> Command<Message> {
         match message {
             Message::Deposit(i, msg) => {
+                let resume = matches!(msg, DepositMessage::Resume);
                 if let Some(deposit) = self.deposits.get_mut(i) {
                     let cmd = deposit
-                        .update(self.revaultd.clone(), msg)
+                        .update(ctx, self.revaultd.clone(), &self.session, msg)
                         .map(move |msg| Message::Deposit(i, msg));
-                    if deposit.signed() {
+                    if deposit.done() {
                         return Command::batch(vec![cmd, self.start_signing_deposit(i + 1)]);
                     }
+                    if resume {
+                        return Command::batch(vec![cmd, self.start_signing_deposit(i)]);
+                    }
                     return cmd;
                 }
                 Command::none()
             }
+            Message::Batch(BatchMessage::Start) => {
+                let vaults: Vec<Vault> = self
+                    .deposits
+                    .iter()
+                    .filter_map(|deposit| match deposit {
+                        Deposit::Signed { .. } => None,
+                        _ => Some(deposit.vault().clone()),
+                    })
+                    .filter(|vault| vault.status == VaultStatus::Funded)
+                    .collect();
+                let batch = RevocationBatch::new(self.session.clone(), vaults);
+                let cmd = batch.load(self.revaultd.clone()).map(Message::Batch);
+                self.batch = Some(batch);
+                cmd
+            }
+            Message::Batch(BatchMessage::Close) => {
+                self.batch = None;
+                self.load()
+            }
+            Message::Batch(msg) => {
+                if let Some(batch) = &mut self.batch {
+                    return batch.update(self.revaultd.clone(), msg).map(Message::Batch);
+                }
+                Command::none()
+            }
             Message::Vaults(res) => match res {
                 Ok(vaults) => self.update_deposits(vaults),
                 Err(e) => {
@@ -245,11 +305,40 @@ impl State for StakeholderACKFundsState {
                     Command::none()
                 }
             },
+            Message::Tick(_) => {
+                let mut cmds = Vec::new();
+                for (i, deposit) in self.deposits.iter_mut().enumerate() {
+                    if deposit.animated() {
+                        cmds.push(
+                            deposit
+                                .update(
+                                    ctx,
+                                    self.revaultd.clone(),
+                                    &self.session,
+                                    DepositMessage::Sign(SignMessage::NextQrCode),
+                                )
+                                .map(move |msg| Message::Deposit(i, msg)),
+                        );
+                    }
+                }
+                Command::batch(cmds)
+            }
             _ => Command::none(),
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        if self.deposits.iter().any(|deposit| deposit.animated()) {
+            return iced::time::every(Duration::from_millis(QR_CODE_FRAME_MS)).map(Message::Tick);
+        }
+        Subscription::none()
+    }
+
     fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let batch = self
+            .batch
+            .as_mut()
+            .map(|batch| batch.view(ctx).map(Message::Batch));
         self.view.view(
             ctx,
             self.deposits
@@ -264,6 +353,7 @@ impl State for StakeholderACKFundsState {
                     })
                 })
                 .collect(),
+            batch,
         )
     }
 
@@ -292,6 +382,17 @@ pub enum Deposit {
     Pending {
         vault: Vault,
     },
+    /// The revocation transactions could not be retrieved from revaultd.
+    Failed {
+        vault: Vault,
+        error: String,
+        view: StakeholderACKDepositView,
+    },
+    /// The deposit was left for later by the user.
+    Skipped {
+        vault: Vault,
+        view: StakeholderACKDepositView,
+    },
 }
 
 impl Deposit {
@@ -299,37 +400,172 @@ impl Deposit {
         Deposit::Pending { vault }
     }
 
-    fn signed(&self) -> bool {
-        matches!(self, Self::Signed { .. })
+    fn vault(&self) -> &Vault {
+        match self {
+            Self::Signed { vault }
+            | Self::Signing { vault, .. }
+            | Self::Pending { vault }
+            | Self::Failed { vault, .. }
+            | Self::Skipped { vault, .. } => vault,
+        }
+    }
+
+    fn is_signing(&self) -> bool {
+        matches!(self, Self::Signing { .. })
+    }
+
+    /// done returns true if the deposit does not wait for a signature anymore:
+    /// it is signed, skipped or its revocation transactions cannot be retrieved.
+    fn done(&self) -> bool {
+        matches!(
+            self,
+            Self::Signed { .. } | Self::Failed { .. } | Self::Skipped { .. }
+        )
+    }
+
+    fn animated(&self) -> bool {
+        if let Self::Signing { signer, .. } = self {
+            return signer.animated();
+        }
+        false
+    }
+
+    /// fetch asks revaultd for the revocation transactions of the deposit to sign.
+    fn fetch(&mut self, revaultd: Arc<RevaultD>) -> Command<DepositMessage> {
+        let vault = self.vault().clone();
+        let outpoint = vault.outpoint();
+        *self = Deposit::Pending { vault };
+        Command::perform(
+            get_revocation_txs(revaultd, outpoint),
+            DepositMessage::RevocationTransactions,
+        )
+    }
+
+    fn submit(&self, revaultd: Arc<RevaultD>) -> Command<DepositMessage> {
+        if let Deposit::Signing {
+            vault,
+            emergency_tx,
+            emergency_unvault_tx,
+            cancel_tx,
+            ..
+        } = self
+        {
+            return Command::perform(
+                set_revocation_txs(
+                    revaultd,
+                    vault.outpoint(),
+                    emergency_tx.0.clone(),
+                    emergency_unvault_tx.0.clone(),
+                    cancel_tx.0.clone(),
+                ),
+                DepositMessage::Signed,
+            );
+        }
+        Command::none()
+    }
+
+    /// save writes the revocation transactions signed so far in the GUI data directory.
+    fn save(&self, session: &Session) -> Command<DepositMessage> {
+        if let Deposit::Signing {
+            vault,
+            emergency_tx,
+            emergency_unvault_tx,
+            cancel_tx,
+            ..
+        } = self
+        {
+            let mut signed = SignedRevocation::new(vault.outpoint());
+            for (kind, (psbt, is_signed)) in &[
+                (TransactionKind::Emergency, emergency_tx),
+                (TransactionKind::EmergencyUnvault, emergency_unvault_tx),
+                (TransactionKind::Cancel, cancel_tx),
+            ] {
+                if *is_signed {
+                    signed.set(kind, psbt);
+                }
+            }
+            return Command::perform(
+                save_signed_revocation(session.data_dir.join(revocation::DIR_NAME), signed),
+                DepositMessage::SignedRevocationSaved,
+            );
+        }
+        Command::none()
+    }
+
+    /// next_signature prepares the signature of the first revocation transaction
+    /// not signed yet, or submits them all once they are signed.
+    fn next_signature(
+        &mut self,
+        ctx: &Context,
+        revaultd: Arc<RevaultD>,
+        session: &Arc<Session>,
+    ) -> Command<DepositMessage> {
+        if let Deposit::Signing {
+            signer,
+            emergency_tx,
+            emergency_unvault_tx,
+            cancel_tx,
+            ..
+        } = self
+        {
+            let next = if !emergency_tx.1 {
+                Some((emergency_tx, TransactionKind::Emergency))
+            } else if !emergency_unvault_tx.1 {
+                Some((emergency_unvault_tx, TransactionKind::EmergencyUnvault))
+            } else if !cancel_tx.1 {
+                Some((cancel_tx, TransactionKind::Cancel))
+            } else {
+                None
+            };
+            match next {
+                Some((tx, kind)) => *signer = SignState::new(session.clone(), tx.0.clone(), kind),
+                None => return self.submit(revaultd),
+            }
+            // Without hardware in the loop, revocation transactions are signed
+            // as soon as they are received.
+            if session.software_signer.is_some() {
+                return self.update(
+                    ctx,
+                    revaultd,
+                    session,
+                    DepositMessage::Sign(SignMessage::SignWithKey),
+                );
+            }
+        }
+        Command::none()
     }
 
     fn update(
         &mut self,
+        ctx: &Context,
         revaultd: Arc<RevaultD>,
+        session: &Arc<Session>,
         message: DepositMessage,
     ) -> Command<DepositMessage> {
         match message {
-            DepositMessage::Retry => {
-                if let Deposit::Signing {
-                    warning,
-                    vault,
-                    emergency_tx,
-                    emergency_unvault_tx,
-                    cancel_tx,
-                    ..
-                } = self
-                {
+            DepositMessage::Retry => match self {
+                Deposit::Signing { warning, .. } => {
                     *warning = None;
-                    return Command::perform(
-                        set_revocation_txs(
-                            revaultd,
-                            vault.outpoint(),
-                            emergency_tx.0.clone(),
-                            emergency_unvault_tx.0.clone(),
-                            cancel_tx.0.clone(),
-                        ),
-                        DepositMessage::Signed,
-                    );
+                    return self.submit(revaultd);
+                }
+                Deposit::Failed { .. } => return self.fetch(revaultd),
+                _ => {}
+            },
+            DepositMessage::Skip => {
+                if matches!(self, Deposit::Signing { .. } | Deposit::Failed { .. }) {
+                    *self = Deposit::Skipped {
+                        vault: self.vault().clone(),
+                        view: StakeholderACKDepositView::new(),
+                    };
+                }
+            }
+            // The resumed deposit is pending again, it is fetched by the funds
+            // state once no other deposit is being signed.
+            DepositMessage::Resume => {
+                if let Deposit::Skipped { vault, .. } = self {
+                    *self = Deposit::Pending {
+                        vault: vault.clone(),
+                    };
                 }
             }
             DepositMessage::Signed(res) => {
@@ -337,15 +573,81 @@ impl Deposit {
                     if let Err(e) = res {
                         *warning = Some(format!("Error: {}", e));
                     } else {
+                        let outpoint = vault.outpoint();
                         *self = Deposit::Signed {
                             vault: vault.clone(),
                         };
+                        return Command::perform(
+                            delete_signed_revocation(
+                                session.data_dir.join(revocation::DIR_NAME),
+                                outpoint,
+                            ),
+                            DepositMessage::SignedRevocationSaved,
+                        );
                     }
                 }
             }
             DepositMessage::RevocationTransactions(res) => {
-                if let Ok(txs) = res {
-                    self.signing(txs)
+                if let Deposit::Pending { vault } = self {
+                    match res {
+                        Ok(txs) => {
+                            let outpoint = vault.outpoint();
+                            self.signing(session, txs);
+                            return Command::perform(
+                                load_signed_revocation(
+                                    session.data_dir.join(revocation::DIR_NAME),
+                                    outpoint,
+                                ),
+                                DepositMessage::SignedRevocation,
+                            );
+                        }
+                        Err(e) => {
+                            *self = Deposit::Failed {
+                                vault: vault.clone(),
+                                error: e.to_string(),
+                                view: StakeholderACKDepositView::new(),
+                            };
+                        }
+                    }
+                }
+            }
+            DepositMessage::SignedRevocation(res) => {
+                if let Deposit::Signing {
+                    emergency_tx,
+                    emergency_unvault_tx,
+                    cancel_tx,
+                    ..
+                } = self
+                {
+                    match res {
+                        Ok(Some(saved)) => {
+                            // The transactions are signed in order, a saved signature
+                            // is only kept if the previous transactions are signed.
+                            for (kind, tx) in vec![
+                                (TransactionKind::Emergency, emergency_tx),
+                                (TransactionKind::EmergencyUnvault, emergency_unvault_tx),
+                                (TransactionKind::Cancel, cancel_tx),
+                            ] {
+                                let keys = our_keys(&session.revaultd_config, &kind);
+                                match saved.signed(&kind, &tx.0, &keys) {
+                                    Ok(Some(psbt)) => *tx = (psbt, true),
+                                    Ok(None) => break,
+                                    Err(e) => {
+                                        tracing::warn!("Saved signed {} rejected: {}", kind, e);
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("{}", e),
+                    }
+                    return self.next_signature(ctx, revaultd, session);
+                }
+            }
+            DepositMessage::SignedRevocationSaved(res) => {
+                if let Err(e) = res {
+                    tracing::warn!("{}", e);
                 }
             }
             DepositMessage::Sign(msg) => {
@@ -354,50 +656,44 @@ impl Deposit {
                     emergency_tx,
                     emergency_unvault_tx,
                     cancel_tx,
-                    vault,
                     ..
                 } = self
                 {
-                    signer.update(msg);
-                    if let Some(psbt) = &signer.signed_psbt {
+                    let signed = signer.signed_psbt.is_some();
+                    let cmd = signer.update(ctx, msg).map(DepositMessage::Sign);
+                    // Only a new signature is saved and moves on to the next transaction,
+                    // the QR code frames keep showing the already signed psbt.
+                    if signed {
+                        return cmd;
+                    }
+                    if let Some(psbt) = signer.signed_psbt.clone() {
                         match signer.transaction_kind {
-                            TransactionKind::Emergency => {
-                                *emergency_tx = (psbt.clone(), true);
-                                *signer = SignState::new(
-                                    emergency_unvault_tx.0.clone(),
-                                    TransactionKind::EmergencyUnvault,
-                                );
-                            }
+                            TransactionKind::Emergency => *emergency_tx = (psbt, true),
                             TransactionKind::EmergencyUnvault => {
-                                *emergency_unvault_tx = (psbt.clone(), true);
-                                *signer =
-                                    SignState::new(cancel_tx.0.clone(), TransactionKind::Cancel);
-                            }
-                            TransactionKind::Cancel => {
-                                *cancel_tx = (psbt.clone(), true);
-                                return Command::perform(
-                                    set_revocation_txs(
-                                        revaultd,
-                                        vault.outpoint(),
-                                        emergency_tx.0.clone(),
-                                        emergency_unvault_tx.0.clone(),
-                                        cancel_tx.0.clone(),
-                                    ),
-                                    DepositMessage::Signed,
-                                );
+                                *emergency_unvault_tx = (psbt, true)
                             }
+                            TransactionKind::Cancel => *cancel_tx = (psbt, true),
                             _ => {}
                         }
+                        return Command::batch(vec![
+                            self.save(session),
+                            self.next_signature(ctx, revaultd, session),
+                        ]);
                     }
+                    return cmd;
                 }
             }
         }
         Command::none()
     }
 
-    fn signing(&mut self, txs: RevocationTransactions) {
+    fn signing(&mut self, session: &Arc<Session>, txs: RevocationTransactions) {
         if let Deposit::Pending { vault } = self {
-            let signer = SignState::new(txs.emergency_tx.clone(), TransactionKind::Emergency);
+            let signer = SignState::new(
+                session.clone(),
+                txs.emergency_tx.clone(),
+                TransactionKind::Emergency,
+            );
             *self = Deposit::Signing {
                 warning: None,
                 vault: vault.to_owned(),
@@ -414,6 +710,8 @@ impl Deposit {
         match self {
             Self::Signed { vault } => stakeholder_deposit_signed(ctx, vault),
             Self::Pending { vault } => stakeholder_deposit_pending(ctx, vault),
+            Self::Failed { vault, error, view } => view.failed(ctx, vault, error),
+            Self::Skipped { vault, view } => view.skipped(ctx, vault),
             Self::Signing {
                 warning,
                 vault,
@@ -435,6 +733,231 @@ impl Deposit {
     }
 }
 
+/// Statuses of the vaults that can be delegated to the managers.
+const DELEGATE_STATUSES: &[VaultStatus] = &[VaultStatus::Secured];
+
+/// StakeholderDelegateState lets the stakeholder sign the unvault transactions
+/// of the selected secured vaults, which then become active and spendable
+/// by the managers.
+#[derive(Debug)]
+pub struct StakeholderDelegateState {
+    revaultd: Arc<RevaultD>,
+    session: Arc<Session>,
+    warning: Option<Error>,
+
+    vaults: Vec<DelegateVault>,
+    /// index of the vault whose unvault transaction is being signed.
+    signing: Option<usize>,
+    signer: Option<SignState>,
+    /// error of the submission of the signed unvault transaction.
+    submit_warning: Option<String>,
+    /// the selected vaults are all delegated.
+    done: bool,
+    view: StakeholderDelegateView,
+}
+
+#[derive(Debug)]
+struct DelegateVault {
+    vault: Vault,
+    selected: bool,
+    delegated: bool,
+}
+
+impl StakeholderDelegateState {
+    pub fn new(revaultd: Arc<RevaultD>, session: Arc<Session>) -> Self {
+        StakeholderDelegateState {
+            revaultd,
+            session,
+            warning: None,
+            vaults: Vec::new(),
+            signing: None,
+            signer: None,
+            submit_warning: None,
+            done: false,
+            view: StakeholderDelegateView::new(),
+        }
+    }
+
+    /// next_vault fetches the unvault transaction of the next selected vault
+    /// that is not delegated yet.
+    fn next_vault(&mut self) -> Command<DelegateMessage> {
+        self.signer = None;
+        self.submit_warning = None;
+        let next = self.vaults.iter().position(|v| v.selected && !v.delegated);
+        self.signing = next;
+        match next {
+            Some(i) => Command::perform(
+                get_unvault_tx(self.revaultd.clone(), self.vaults[i].vault.outpoint()),
+                move |res| DelegateMessage::UnvaultTransaction(i, res),
+            ),
+            None => {
+                self.done = true;
+                Command::none()
+            }
+        }
+    }
+
+    fn submit(&self, index: usize, psbt: Psbt) -> Command<DelegateMessage> {
+        Command::perform(
+            set_unvault_tx(
+                self.revaultd.clone(),
+                self.vaults[index].vault.outpoint(),
+                psbt,
+            ),
+            move |res| DelegateMessage::Delegated(index, res),
+        )
+    }
+
+    fn on_message(&mut self, ctx: &Context, message: DelegateMessage) -> Command<DelegateMessage> {
+        match message {
+            DelegateMessage::Select(i) => {
+                if self.signing.is_none() {
+                    if let Some(v) = self.vaults.get_mut(i) {
+                        v.selected = !v.selected;
+                    }
+                }
+            }
+            DelegateMessage::Start => return self.next_vault(),
+            DelegateMessage::UnvaultTransaction(i, res) => match res {
+                Ok(tx) => {
+                    self.signer = Some(SignState::new(
+                        self.session.clone(),
+                        tx.unvault_tx,
+                        TransactionKind::Unvault,
+                    ));
+                    // Without hardware in the loop, the unvault transaction is signed
+                    // as soon as it is received.
+                    if self.session.software_signer.is_some() {
+                        return self
+                            .on_message(ctx, DelegateMessage::Sign(SignMessage::SignWithKey));
+                    }
+                }
+                Err(e) => {
+                    self.signing = None;
+                    self.warning = Error::from(e).into();
+                    if let Some(v) = self.vaults.get_mut(i) {
+                        v.selected = false;
+                    }
+                }
+            },
+            DelegateMessage::Sign(msg) => {
+                if let (Some(i), Some(signer)) = (self.signing, &mut self.signer) {
+                    let was_signed = signer.signed_psbt.is_some();
+                    let cmd = signer.update(ctx, msg).map(DelegateMessage::Sign);
+                    // The unvault transaction is submitted once, when it gets signed.
+                    if !was_signed {
+                        if let Some(psbt) = signer.signed_psbt.clone() {
+                            return self.submit(i, psbt);
+                        }
+                    }
+                    return cmd;
+                }
+            }
+            DelegateMessage::Retry => {
+                if let (Some(i), Some(signer)) = (self.signing, &self.signer) {
+                    if let Some(psbt) = signer.signed_psbt.clone() {
+                        self.submit_warning = None;
+                        return self.submit(i, psbt);
+                    }
+                }
+            }
+            DelegateMessage::Delegated(i, res) => match res {
+                Ok(()) => {
+                    if let Some(v) = self.vaults.get_mut(i) {
+                        v.delegated = true;
+                    }
+                    return self.next_vault();
+                }
+                Err(e) => self.submit_warning = Some(format!("Error: {}", e)),
+            },
+        }
+        Command::none()
+    }
+}
+
+impl State for StakeholderDelegateState {
+    fn update(&mut self, ctx: &Context, message: Message) -> Command<Message> {
+        match message {
+            Message::Delegate(msg) => self.on_message(ctx, msg).map(Message::Delegate),
+            Message::Vaults(res) => {
+                match res {
+                    Ok(vaults) => {
+                        self.vaults = vaults
+                            .into_iter()
+                            .map(|vault| DelegateVault {
+                                vault,
+                                selected: false,
+                                delegated: false,
+                            })
+                            .collect()
+                    }
+                    Err(e) => self.warning = Error::from(e).into(),
+                };
+                Command::none()
+            }
+            Message::Tick(_) => {
+                if let Some(signer) = &mut self.signer {
+                    if signer.animated() {
+                        signer.update(ctx, SignMessage::NextQrCode);
+                    }
+                }
+                Command::none()
+            }
+            _ => Command::none(),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if let Some(signer) = &self.signer {
+            if signer.animated() {
+                return iced::time::every(Duration::from_millis(QR_CODE_FRAME_MS))
+                    .map(Message::Tick);
+            }
+        }
+        Subscription::none()
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let vaults: Vec<(&Vault, bool, bool)> = self
+            .vaults
+            .iter()
+            .map(|v| (&v.vault, v.selected, v.delegated))
+            .collect();
+        let all = &self.vaults;
+        let signing = self.signing.map(|i| &all[i].vault);
+        let signer = self.signer.as_mut().map(|signer| {
+            signer.view(ctx).map(|msg| {
+                if let SignMessage::Clipboard(psbt) = msg {
+                    return Message::Clipboard(psbt);
+                }
+                Message::Delegate(DelegateMessage::Sign(msg))
+            })
+        });
+        self.view.view(
+            ctx,
+            self.warning.as_ref(),
+            vaults,
+            signing,
+            signer,
+            self.submit_warning.as_ref(),
+            self.done,
+        )
+    }
+
+    fn load(&self) -> Command<Message> {
+        Command::perform(
+            list_vaults(self.revaultd.clone(), Some(DELEGATE_STATUSES)),
+            Message::Vaults,
+        )
+    }
+}
+
+impl From<StakeholderDelegateState> for Box<dyn State> {
+    fn from(s: StakeholderDelegateState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
+
 impl From<StakeholderACKFundsState> for Box<dyn State> {
     fn from(s: StakeholderACKFundsState) -> Box<dyn State> {
         Box::new(s)
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::ui::{
@@ -17,6 +18,86 @@ use crate::revaultd::{
     RevaultD,
 };
 
+/// Number of vaults displayed by page of a vault list.
+pub const VAULTS_PAGE_SIZE: usize = 50;
+
+/// pages returns the number of pages needed to display the given number of
+/// vaults.
+pub fn pages(count: usize) -> usize {
+    std::cmp::max(1, (count + VAULTS_PAGE_SIZE - 1) / VAULTS_PAGE_SIZE)
+}
+
+/// VaultList keeps the items of the listed vaults and their view state
+/// between two listings of the daemon. Only the vaults of the current page
+/// are rendered.
+#[derive(Debug, Default)]
+pub struct VaultList {
+    items: Vec<VaultListItem>,
+    page: usize,
+}
+
+impl VaultList {
+    /// update replaces the vaults with the new listing, the items of the
+    /// vaults already listed are reused instead of being rebuilt.
+    pub fn update(&mut self, vaults: Vec<Vault>) {
+        let mut previous: HashMap<String, VaultListItem> = self
+            .items
+            .drain(..)
+            .map(|item| (item.vault.outpoint(), item))
+            .collect();
+        self.items = vaults
+            .into_iter()
+            .map(|vault| match previous.remove(&vault.outpoint()) {
+                Some(mut item) => {
+                    item.vault = vault;
+                    item
+                }
+                None => VaultListItem::new(vault),
+            })
+            .collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn find(&self, outpoint: &str) -> Option<&Vault> {
+        self.items
+            .iter()
+            .map(|item| &item.vault)
+            .find(|vault| vault.outpoint() == outpoint)
+    }
+
+    pub fn items(&self) -> &[VaultListItem] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut [VaultListItem] {
+        &mut self.items
+    }
+
+    /// page returns the current page, the last page must be displayed
+    /// instead if the list is now shorter.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn set_page(&mut self, page: usize) {
+        self.page = page;
+    }
+
+    /// view renders the vaults of the current page.
+    pub fn view(&mut self, ctx: &Context) -> Vec<Element<Message>> {
+        let page = std::cmp::min(self.page, pages(self.items.len()) - 1);
+        self.items
+            .iter_mut()
+            .skip(page * VAULTS_PAGE_SIZE)
+            .take(VAULTS_PAGE_SIZE)
+            .map(|item| item.view(ctx))
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct VaultListItem {
     pub vault: Vault,
@@ -1,4 +1,7 @@
-use iced::{scrollable, Align, Column, Container, Element, Length, QRCode, Row, Scrollable};
+use iced::{
+    scrollable, text_input, Align, Column, Container, Element, Length, QRCode, Row, Scrollable,
+    TextInput,
+};
 
 use crate::ui::{
     component::{button, card, navbar, text},
@@ -13,23 +16,31 @@ use crate::ui::{
 pub struct DepositView {
     sidebar: Sidebar,
     qr_code: Option<iced::qr_code::State>,
+    /// content of the qrcode, the address or its payment request.
+    payment_request: String,
     scroll: scrollable::State,
     copy_button: iced::button::State,
+    amount_input: text_input::State,
+    label_input: text_input::State,
 }
 
 impl DepositView {
     pub fn new() -> Self {
         DepositView {
             qr_code: None,
+            payment_request: "".to_string(),
             sidebar: Sidebar::new(),
             scroll: scrollable::State::new(),
             copy_button: iced::button::State::default(),
+            amount_input: text_input::State::new(),
+            label_input: text_input::State::new(),
         }
     }
 
-    // Address is loaded directly in the view in order to cache the created qrcode.
-    pub fn load(&mut self, address: &bitcoin::Address) {
-        self.qr_code = iced::qr_code::State::new(address.to_string()).ok();
+    // Payment request is loaded directly in the view in order to cache the created qrcode.
+    pub fn load(&mut self, payment_request: String) {
+        self.qr_code = iced::qr_code::State::new(&payment_request).ok();
+        self.payment_request = payment_request;
     }
 
     pub fn view<'a>(
@@ -37,6 +48,9 @@ impl DepositView {
         ctx: &Context,
         warning: Option<&Error>,
         address: Option<&bitcoin::Address>,
+        amount: &str,
+        label: &str,
+        warning_amount: Option<&String>,
     ) -> Element<'a, Message> {
         let mut col = Column::new().align_items(Align::Center).spacing(20);
         if address.is_some() {
@@ -54,12 +68,42 @@ impl DepositView {
                     .push(
                         button::clipboard(
                             &mut self.copy_button,
-                            Message::Clipboard(addr.to_string()),
+                            Message::Clipboard(self.payment_request.clone()),
                         )
                         .width(Length::Shrink),
                     )
                     .align_items(Align::Center),
             ));
+
+            let mut request = Column::new()
+                .spacing(10)
+                .max_width(400)
+                .push(text::small(
+                    "Optionally, request an amount and a label in the QR code:",
+                ))
+                .push(
+                    TextInput::new(
+                        &mut self.amount_input,
+                        "Amount",
+                        amount,
+                        Message::RequestAmountEdited,
+                    )
+                    .padding(10),
+                );
+            if let Some(message) = warning_amount {
+                request = request.push(card::alert_warning(Container::new(text::simple(message))));
+            }
+            col = col.push(
+                request.push(
+                    TextInput::new(
+                        &mut self.label_input,
+                        "Label",
+                        label,
+                        Message::RequestLabelEdited,
+                    )
+                    .padding(10),
+                ),
+            );
         }
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
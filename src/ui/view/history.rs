@@ -1,16 +1,58 @@
-use iced::{scrollable, Column, Container, Element, Scrollable};
+use std::path::PathBuf;
+
+use iced::{
+    scrollable, text_input, Align, Column, Container, Element, Length, Row, Scrollable, TextInput,
+};
+
+use crate::{ledger, revaultd::model::VaultStatus};
 
 use crate::ui::{
-    component::navbar,
+    component::{button, card, navbar, text},
     error::Error,
-    message::Message,
-    view::{layout, sidebar::Sidebar, Context},
+    message::{HistoryMessage, HistorySort, Message},
+    view::{layout, sidebar::Sidebar, vault::VaultListPagination, Context},
 };
 
+/// HistoryControls are the current filters and order of the history.
+#[derive(Debug)]
+pub struct HistoryControls<'a> {
+    pub statuses: &'a [VaultStatus],
+    pub from: &'a str,
+    pub to: &'a str,
+    pub min_amount: &'a str,
+    pub max_amount: &'a str,
+    pub search: &'a str,
+    /// column and descending order.
+    pub sort: Option<(HistorySort, bool)>,
+    /// explanations of the filters that cannot be parsed.
+    pub errors: &'a [String],
+    /// number of vaults matching the filters.
+    pub shown: usize,
+    /// number of vaults before filtering.
+    pub total: usize,
+    pub page: usize,
+    pub pages: usize,
+    pub export_path: &'a str,
+    pub export_warning: Option<&'a String>,
+    pub exported: Option<&'a PathBuf>,
+}
+
 #[derive(Debug)]
 pub struct HistoryView {
     scroll: scrollable::State,
     sidebar: Sidebar,
+    search_input: text_input::State,
+    from_input: text_input::State,
+    to_input: text_input::State,
+    min_amount_input: text_input::State,
+    max_amount_input: text_input::State,
+    status_buttons: Vec<iced::button::State>,
+    sort_buttons: Vec<iced::button::State>,
+    clear_button: iced::button::State,
+    pagination: VaultListPagination,
+    export_path_input: text_input::State,
+    export_csv_button: iced::button::State,
+    export_json_button: iced::button::State,
 }
 
 impl HistoryView {
@@ -18,6 +60,18 @@ impl HistoryView {
         HistoryView {
             sidebar: Sidebar::new(),
             scroll: scrollable::State::new(),
+            search_input: text_input::State::new(),
+            from_input: text_input::State::new(),
+            to_input: text_input::State::new(),
+            min_amount_input: text_input::State::new(),
+            max_amount_input: text_input::State::new(),
+            status_buttons: Vec::new(),
+            sort_buttons: Vec::new(),
+            clear_button: iced::button::State::new(),
+            pagination: VaultListPagination::new(),
+            export_path_input: text_input::State::new(),
+            export_csv_button: iced::button::State::new(),
+            export_json_button: iced::button::State::new(),
         }
     }
 
@@ -25,15 +79,195 @@ impl HistoryView {
         &'a mut self,
         ctx: &Context,
         warning: Option<&Error>,
+        controls: HistoryControls,
         vaults: Vec<Element<'a, Message>>,
     ) -> Element<'a, Message> {
+        let mut col_statuses = Column::new().spacing(5);
+        let mut row_statuses = Row::new().spacing(5);
+        self.status_buttons
+            .resize_with(VaultStatus::ALL.len(), iced::button::State::new);
+        for (i, (status, state)) in VaultStatus::ALL
+            .iter()
+            .zip(self.status_buttons.iter_mut())
+            .enumerate()
+        {
+            if i > 0 && i % 7 == 0 {
+                col_statuses = col_statuses.push(row_statuses);
+                row_statuses = Row::new().spacing(5);
+            }
+            let content = Container::new(text::small(&status.to_string())).padding(5);
+            let button = if controls.statuses.contains(status) {
+                button::primary(state, content)
+            } else {
+                button::transparent(state, content)
+            };
+            row_statuses = row_statuses.push(button.on_press(Message::History(
+                HistoryMessage::ToggleStatus(status.clone()),
+            )));
+        }
+        col_statuses = col_statuses.push(row_statuses);
+
+        let mut row_sort = Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(text::small("Sort by:"));
+        self.sort_buttons.resize_with(3, iced::button::State::new);
+        for ((column, name), state) in [
+            (HistorySort::Date, "Date"),
+            (HistorySort::Amount, "Amount"),
+            (HistorySort::Status, "Status"),
+        ]
+        .iter()
+        .zip(self.sort_buttons.iter_mut())
+        {
+            let button = match controls.sort {
+                Some((sorted, descending)) if sorted == *column => button::primary(
+                    state,
+                    Container::new(text::small(&format!(
+                        "{} {}",
+                        name,
+                        if descending { "v" } else { "^" }
+                    )))
+                    .padding(5),
+                ),
+                _ => button::transparent(state, Container::new(text::small(name)).padding(5)),
+            };
+            row_sort =
+                row_sort.push(button.on_press(Message::History(HistoryMessage::Sort(*column))));
+        }
+
+        let mut col_filters = Column::new()
+            .spacing(10)
+            .push(
+                TextInput::new(
+                    &mut self.search_input,
+                    "Search an address, a transaction id, an outpoint or a label",
+                    controls.search,
+                    |s| Message::History(HistoryMessage::SearchEdited(s)),
+                )
+                .padding(10),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(text::small("Updated from"))
+                    .push(
+                        TextInput::new(&mut self.from_input, "YYYY-MM-DD", controls.from, |s| {
+                            Message::History(HistoryMessage::FromEdited(s))
+                        })
+                        .padding(5)
+                        .width(Length::Units(120)),
+                    )
+                    .push(text::small("to"))
+                    .push(
+                        TextInput::new(&mut self.to_input, "YYYY-MM-DD", controls.to, |s| {
+                            Message::History(HistoryMessage::ToEdited(s))
+                        })
+                        .padding(5)
+                        .width(Length::Units(120)),
+                    )
+                    .push(text::small(&format!(
+                        "Amount ({}) from",
+                        ctx.converter.unit
+                    )))
+                    .push(
+                        TextInput::new(
+                            &mut self.min_amount_input,
+                            "0.0",
+                            controls.min_amount,
+                            |s| Message::History(HistoryMessage::MinAmountEdited(s)),
+                        )
+                        .padding(5)
+                        .width(Length::Units(100)),
+                    )
+                    .push(text::small("to"))
+                    .push(
+                        TextInput::new(
+                            &mut self.max_amount_input,
+                            "0.0",
+                            controls.max_amount,
+                            |s| Message::History(HistoryMessage::MaxAmountEdited(s)),
+                        )
+                        .padding(5)
+                        .width(Length::Units(100)),
+                    ),
+            )
+            .push(col_statuses)
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(row_sort.width(Length::Fill))
+                    .push(text::small(&format!(
+                        "{} of {} vaults",
+                        controls.shown, controls.total
+                    )))
+                    .push(
+                        button::cancel(
+                            &mut self.clear_button,
+                            Container::new(text::small("Clear filters")).padding(5),
+                        )
+                        .on_press(Message::History(HistoryMessage::ClearFilters)),
+                    )
+                    .spacing(20),
+            );
+        for error in controls.errors {
+            col_filters = col_filters.push(text::danger(text::small(error)));
+        }
+
+        let mut col_export = Column::new().spacing(10).push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.export_path_input,
+                        "Export the displayed vaults and their transactions to a file",
+                        controls.export_path,
+                        |s| Message::History(HistoryMessage::ExportPathEdited(s)),
+                    )
+                    .padding(10),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.export_csv_button,
+                        Container::new(text::simple("Export CSV")).padding(10),
+                    )
+                    .on_press(Message::History(HistoryMessage::Export(
+                        ledger::Format::Csv,
+                    ))),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.export_json_button,
+                        Container::new(text::simple("Export JSON")).padding(10),
+                    )
+                    .on_press(Message::History(HistoryMessage::Export(
+                        ledger::Format::Json,
+                    ))),
+                ),
+        );
+        if let Some(message) = controls.export_warning {
+            col_export =
+                col_export.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+        if let Some(path) = controls.exported {
+            col_export = col_export.push(card::simple(Container::new(text::simple(&format!(
+                "History exported to {}",
+                path.to_string_lossy()
+            )))));
+        }
+
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
             self.sidebar.view(ctx),
             layout::main_section(Container::new(
                 Scrollable::new(&mut self.scroll).push(Container::new(
                     Column::new()
+                        .push(card::simple(Container::new(col_filters)))
+                        .push(col_export)
                         .push(Column::with_children(vaults))
+                        .push(self.pagination.view(controls.page, controls.pages))
                         .spacing(20),
                 )),
             )),
@@ -5,13 +5,14 @@ use crate::ui::{
     error::Error,
     menu::Menu,
     message::Message,
-    view::{layout, sidebar::Sidebar, Context},
+    view::{layout, sidebar::Sidebar, vault::VaultListPagination, Context},
 };
 
 #[derive(Debug)]
 pub struct ManagerHomeView {
     sidebar: Sidebar,
     scroll: scrollable::State,
+    pagination: VaultListPagination,
 }
 
 impl ManagerHomeView {
@@ -19,6 +20,7 @@ impl ManagerHomeView {
         ManagerHomeView {
             scroll: scrollable::State::new(),
             sidebar: Sidebar::new(),
+            pagination: VaultListPagination::new(),
         }
     }
 
@@ -27,6 +29,8 @@ impl ManagerHomeView {
         ctx: &Context,
         warning: Option<&Error>,
         vaults: Vec<Element<'a, Message>>,
+        page: usize,
+        pages: usize,
         balance: &(u64, u64),
     ) -> Element<'a, Message> {
         layout::dashboard(
@@ -41,6 +45,7 @@ impl ManagerHomeView {
                                 .push(balance_view(ctx, balance).width(Length::FillPortion(1))),
                         )
                         .push(Column::with_children(vaults))
+                        .push(self.pagination.view(page, pages))
                         .spacing(20),
                 )),
             )),
@@ -54,6 +59,7 @@ pub struct StakeholderHomeView {
     sidebar: Sidebar,
     scroll: scrollable::State,
     ack_fund_button: iced::button::State,
+    delegate_button: iced::button::State,
 }
 
 impl StakeholderHomeView {
@@ -62,6 +68,7 @@ impl StakeholderHomeView {
             scroll: scrollable::State::new(),
             sidebar: Sidebar::new(),
             ack_fund_button: iced::button::State::default(),
+            delegate_button: iced::button::State::default(),
         }
     }
 
@@ -72,30 +79,37 @@ impl StakeholderHomeView {
         vaults: Vec<Element<'a, Message>>,
         balance: &(u64, u64),
         unsecured_fund_balance: &u64,
+        secured_fund_balance: &u64,
     ) -> Element<'a, Message> {
+        let mut col = Column::new()
+            .push(
+                Row::new()
+                    .push(
+                        unsecured_fund_view(
+                            ctx,
+                            &mut self.ack_fund_button,
+                            &unsecured_fund_balance,
+                        )
+                        .max_width(400)
+                        .width(Length::Fill),
+                    )
+                    .push(balance_view(ctx, balance).width(Length::Fill))
+                    .spacing(20),
+            )
+            .spacing(20);
+        if *secured_fund_balance > 0 {
+            col = col.push(
+                secured_fund_view(ctx, &mut self.delegate_button, secured_fund_balance)
+                    .max_width(400)
+                    .width(Length::Fill),
+            );
+        }
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
             self.sidebar.view(ctx),
             layout::main_section(Container::new(
-                Scrollable::new(&mut self.scroll).push(Container::new(
-                    Column::new()
-                        .push(
-                            Row::new()
-                                .push(
-                                    unsecured_fund_view(
-                                        ctx,
-                                        &mut self.ack_fund_button,
-                                        &unsecured_fund_balance,
-                                    )
-                                    .max_width(400)
-                                    .width(Length::Fill),
-                                )
-                                .push(balance_view(ctx, balance).width(Length::Fill))
-                                .spacing(20),
-                        )
-                        .push(Column::with_children(vaults))
-                        .spacing(20),
-                )),
+                Scrollable::new(&mut self.scroll)
+                    .push(Container::new(col.push(Column::with_children(vaults)))),
             )),
         )
         .into()
@@ -145,6 +159,51 @@ fn unsecured_fund_view<'a>(
     ))
 }
 
+/// secured_fund_view invites the stakeholder to delegate the secured funds
+/// to the managers by signing their unvault transactions.
+fn secured_fund_view<'a>(
+    ctx: &Context,
+    button_state: &'a mut iced::button::State,
+    fund: &u64,
+) -> Container<'a, Message> {
+    card::simple(Container::new(
+        Row::new()
+            .align_items(iced::Align::Center)
+            .push(badge::shield())
+            .push(
+                Column::new()
+                    .push(
+                        Container::new(
+                            Row::new()
+                                .push(text::bold(text::simple(&format!(
+                                    "{}",
+                                    ctx.converter.converts(*fund),
+                                ))))
+                                .push(text::simple(&format!(
+                                    "  {} secured, not delegated to the managers",
+                                    ctx.converter.unit
+                                ))),
+                        )
+                        .width(Length::Fill)
+                        .align_x(iced::Align::End),
+                    )
+                    .push(
+                        Container::new(
+                            button::primary(
+                                button_state,
+                                button::button_content(None, "Delegate funds"),
+                            )
+                            .on_press(Message::Menu(Menu::Delegate)),
+                        )
+                        .width(Length::Fill)
+                        .align_x(iced::Align::End),
+                    )
+                    .spacing(20)
+                    .width(Length::Fill),
+            ),
+    ))
+}
+
 /// render balance card from a tuple: (active, inactive)
 fn balance_view<'a, T: 'a>(ctx: &Context, balance: &(u64, u64)) -> Container<'a, T> {
     let active_balance = ctx.converter.converts(balance.0);
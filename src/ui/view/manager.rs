@@ -1,43 +1,76 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
 use iced::{
     scrollable, text_input, Checkbox, Column, Container, Element, Length, Row, Scrollable,
     TextInput,
 };
 
+use bitcoin::util::{bip32::ExtendedPubKey, psbt::PartiallySignedTransaction as Psbt};
+
+use crate::address_book::{Entry, Knowledge};
+use crate::draft::Draft;
+use crate::policy::{self, Violation};
+use crate::recipients::Rejected;
+use crate::revaultd::model::{FeeEstimates, FeePriority};
 use crate::ui::{
     component::{button, card, separation, text},
+    error::Error,
     menu::Menu,
     message::{InputMessage, Message, RecipientMessage},
     view::Context,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManagerSendStep {
+    SelectOutputs,
+    SelectInputs,
+    SelectFee,
+    Sign,
+    Success,
+}
+
+/// ManagerSendView keeps the views of every step of the spend wizard,
+/// going back and forth between the steps does not reset them.
 #[derive(Debug)]
-pub enum ManagerSendView {
-    SelectOutputs(ManagerSelectOutputsView),
-    SelectInputs(ManagerSelectInputsView),
-    SelectFee(ManagerSelectFeeView),
-    Sign(ManagerSignView),
+pub struct ManagerSendView {
+    pub step: ManagerSendStep,
+    pub select_outputs: ManagerSelectOutputsView,
+    pub select_inputs: ManagerSelectInputsView,
+    pub select_fee: ManagerSelectFeeView,
+    pub sign: ManagerSignView,
+    pub signatures: ManagerSignaturesView,
+    pub success: ManagerSpendSuccessView,
 }
 
 impl ManagerSendView {
     pub fn new() -> Self {
-        Self::SelectOutputs(ManagerSelectOutputsView::new())
+        Self {
+            step: ManagerSendStep::SelectOutputs,
+            select_outputs: ManagerSelectOutputsView::new(),
+            select_inputs: ManagerSelectInputsView::new(),
+            select_fee: ManagerSelectFeeView::new(),
+            sign: ManagerSignView::new(),
+            signatures: ManagerSignaturesView::new(),
+            success: ManagerSpendSuccessView::new(),
+        }
     }
 
-    pub fn next(&self) -> ManagerSendView {
-        match self {
-            Self::SelectOutputs(_) => Self::SelectInputs(ManagerSelectInputsView::new()),
-            Self::SelectInputs(_) => Self::SelectFee(ManagerSelectFeeView::new()),
-            Self::SelectFee(_) => Self::Sign(ManagerSignView::new()),
-            _ => Self::new(),
+    pub fn next(&mut self) {
+        self.step = match self.step {
+            ManagerSendStep::SelectOutputs => ManagerSendStep::SelectInputs,
+            ManagerSendStep::SelectInputs => ManagerSendStep::SelectFee,
+            ManagerSendStep::SelectFee => ManagerSendStep::Sign,
+            ManagerSendStep::Sign | ManagerSendStep::Success => ManagerSendStep::Success,
         }
     }
 
-    pub fn previous(&self) -> ManagerSendView {
-        match self {
-            Self::SelectInputs(_) => Self::SelectOutputs(ManagerSelectOutputsView::new()),
-            Self::SelectFee(_) => Self::SelectInputs(ManagerSelectInputsView::new()),
-            Self::Sign(_) => Self::SelectFee(ManagerSelectFeeView::new()),
-            _ => Self::new(),
+    pub fn previous(&mut self) {
+        self.step = match self.step {
+            ManagerSendStep::SelectInputs => ManagerSendStep::SelectOutputs,
+            ManagerSendStep::SelectFee => ManagerSendStep::SelectInputs,
+            ManagerSendStep::Sign => ManagerSendStep::SelectFee,
+            step => step,
         }
     }
 }
@@ -48,6 +81,13 @@ pub struct ManagerSelectOutputsView {
     cancel_button: iced::button::State,
     next_button: iced::button::State,
     new_output_button: iced::button::State,
+    bip21_input: text_input::State,
+    bip21_button: iced::button::State,
+    recipients_path_input: text_input::State,
+    import_button: iced::button::State,
+    export_button: iced::button::State,
+    /// resume and delete buttons of the pending spends.
+    pending_buttons: Vec<(iced::button::State, iced::button::State)>,
 }
 
 impl ManagerSelectOutputsView {
@@ -57,18 +97,165 @@ impl ManagerSelectOutputsView {
             next_button: iced::button::State::new(),
             scroll: scrollable::State::new(),
             new_output_button: iced::button::State::new(),
+            bip21_input: text_input::State::new(),
+            bip21_button: iced::button::State::new(),
+            recipients_path_input: text_input::State::new(),
+            import_button: iced::button::State::new(),
+            export_button: iced::button::State::new(),
+            pending_buttons: Vec::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
+        ctx: &Context,
+        pending_spends: &[Draft],
         selected_outputs: Vec<Element<'a, Message>>,
+        bip21: &str,
+        bip21_warning: Option<&String>,
+        recipients_path: &str,
+        recipients_warning: Option<&String>,
+        recipients_import: Option<&(usize, Vec<Rejected>)>,
+        recipients_exported: Option<&PathBuf>,
         valid: bool,
     ) -> Element<'a, Message> {
+        let mut col_bip21 = Column::new().spacing(10).push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.bip21_input,
+                        "Paste a payment URI (bitcoin:...)",
+                        bip21,
+                        Message::Bip21Edited,
+                    )
+                    .padding(10),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.bip21_button,
+                        Container::new(text::simple("Add")).padding(10),
+                    )
+                    .on_press(Message::AddBip21Recipient),
+                ),
+        );
+        if let Some(message) = bip21_warning {
+            col_bip21 = col_bip21.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+
+        let mut col_file = Column::new().spacing(10).push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.recipients_path_input,
+                        "Recipients CSV file (address,amount,label)",
+                        recipients_path,
+                        Message::RecipientsPathEdited,
+                    )
+                    .padding(10),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.import_button,
+                        Container::new(text::simple("Import")).padding(10),
+                    )
+                    .on_press(Message::ImportRecipients),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.export_button,
+                        Container::new(text::simple("Export")).padding(10),
+                    )
+                    .on_press(Message::ExportRecipients),
+                ),
+        );
+        if let Some(message) = recipients_warning {
+            col_file = col_file.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+        if let Some((imported, rejected)) = recipients_import {
+            let mut summary = Column::new().spacing(5).push(text::simple(&format!(
+                "{} recipients imported, {} rows rejected",
+                imported,
+                rejected.len()
+            )));
+            for row in rejected {
+                summary = summary.push(text::small(&row.to_string()));
+            }
+            if rejected.is_empty() {
+                col_file = col_file.push(card::simple(Container::new(summary)));
+            } else {
+                col_file = col_file.push(card::alert_warning(Container::new(summary)));
+            }
+        }
+        if let Some(path) = recipients_exported {
+            col_file = col_file.push(card::simple(Container::new(text::simple(&format!(
+                "Recipients exported to {}",
+                path.to_string_lossy()
+            )))));
+        }
+
+        let mut col_pending = Column::new().spacing(10);
+        if !pending_spends.is_empty() {
+            self.pending_buttons.resize_with(pending_spends.len(), || {
+                (iced::button::State::new(), iced::button::State::new())
+            });
+            col_pending = col_pending.push(text::bold(text::simple("Pending spends")));
+            for (draft, (resume_button, delete_button)) in
+                pending_spends.iter().zip(self.pending_buttons.iter_mut())
+            {
+                let mut description = Column::new()
+                    .push(text::bold(text::simple(&format!(
+                        "{} {} to {} recipients",
+                        ctx.converter.converts(draft.amount()),
+                        ctx.converter.unit,
+                        draft.recipients.len()
+                    ))))
+                    .push(text::small(&format!("{} vaults", draft.inputs.len())));
+                if draft.updated_at > 0 {
+                    description = description.push(text::small(&format!(
+                        "Saved on {}",
+                        NaiveDateTime::from_timestamp(draft.updated_at, 0)
+                    )));
+                }
+                if let Some(signatures) = draft.signatures() {
+                    description = description
+                        .push(text::small(&format!("{} signatures collected", signatures)));
+                }
+                col_pending = col_pending.push(card::white(Container::new(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(iced::Align::Center)
+                        .push(description.width(Length::Fill))
+                        .push(
+                            button::primary(
+                                resume_button,
+                                Container::new(text::simple("Resume")).padding(5),
+                            )
+                            .on_press(Message::ResumeDraft(draft.id.clone())),
+                        )
+                        .push(
+                            button::transparent(
+                                delete_button,
+                                Container::new(text::simple("Delete")).padding(5),
+                            )
+                            .on_press(Message::DeleteDraft(draft.id.clone())),
+                        ),
+                )));
+            }
+        }
+
         let mut col_outputs = Column::new()
             .spacing(20)
             .width(Length::Fill)
-            .align_items(iced::Align::Center);
+            .align_items(iced::Align::Center)
+            .push(col_pending)
+            .push(col_bip21)
+            .push(col_file)
+            .push(separation().width(Length::Fill));
         for (i, element) in selected_outputs.into_iter().enumerate() {
             if i > 0 {
                 col_outputs = col_outputs.push(separation().width(Length::Fill));
@@ -140,6 +327,10 @@ pub struct ManagerSendOutputView {
     address_input: text_input::State,
     amount_input: text_input::State,
     delete_button: iced::button::State,
+    suggestion_buttons: Vec<iced::button::State>,
+    entry_label_input: text_input::State,
+    entry_notes_input: text_input::State,
+    save_entry_button: iced::button::State,
 }
 
 impl ManagerSendOutputView {
@@ -148,14 +339,29 @@ impl ManagerSendOutputView {
             address_input: text_input::State::focused(),
             amount_input: text_input::State::new(),
             delete_button: iced::button::State::new(),
+            suggestion_buttons: Vec::new(),
+            entry_label_input: text_input::State::new(),
+            entry_notes_input: text_input::State::new(),
+            save_entry_button: iced::button::State::new(),
         }
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn view(
         &mut self,
         address: &str,
         amount: &str,
-        warning_address: &bool,
-        warning_amount: &bool,
+        knowledge: Option<Knowledge>,
+        suggestions: &[&Entry],
+        entry_label: &str,
+        entry_notes: &str,
+        entry_whitelisted: bool,
+        entry_warning: Option<&String>,
+        label: Option<&String>,
+        message: Option<&String>,
+        warning_address: Option<&String>,
+        warning_amount: Option<&String>,
+        duplicate: bool,
+        own_address: bool,
     ) -> Element<RecipientMessage> {
         let address = TextInput::new(
             &mut self.address_input,
@@ -179,9 +385,105 @@ impl ManagerSendOutputView {
             Container::new(address).into(),
         ]);
 
-        if *warning_address {
+        if !suggestions.is_empty() {
+            self.suggestion_buttons
+                .resize_with(suggestions.len(), iced::button::State::new);
+            let mut col_suggestions = Column::new().spacing(5);
+            for (entry, button_state) in suggestions.iter().zip(self.suggestion_buttons.iter_mut())
+            {
+                col_suggestions = col_suggestions.push(
+                    button::white_card_button(
+                        button_state,
+                        Container::new(
+                            Column::new()
+                                .push(text::bold(text::simple(&entry.label)))
+                                .push(text::small(&entry.address)),
+                        ),
+                    )
+                    .on_press(RecipientMessage::SelectEntry(entry.address.clone())),
+                );
+            }
+            col = col.push(col_suggestions);
+        } else if let Some(message) = warning_address {
+            col = col.push(card::alert_warning(Container::new(text::simple(message))))
+        }
+
+        match knowledge {
+            Some(Knowledge::Known(entry)) => {
+                let mut known = Row::new()
+                    .spacing(10)
+                    .push(text::success(text::simple(&format!(
+                        "Known recipient: {}",
+                        entry.label
+                    ))));
+                if entry.whitelisted {
+                    known = known.push(text::small("(whitelisted)"));
+                }
+                col = col.push(known);
+                if !entry.notes.is_empty() {
+                    col = col.push(text::small(&entry.notes));
+                }
+            }
+            Some(Knowledge::Similar(entry)) => {
+                col = col.push(card::alert_warning(Container::new(text::simple(&format!(
+                    "This address looks like the address of {} ({}) but is different, please check it carefully",
+                    entry.label, entry.address
+                )))))
+            }
+            Some(Knowledge::New) if warning_address.is_none() => {
+                let mut entry = Column::new()
+                    .spacing(10)
+                    .push(text::simple("New address, save it to the address book:"))
+                    .push(
+                        Row::new()
+                            .spacing(10)
+                            .push(
+                                TextInput::new(
+                                    &mut self.entry_label_input,
+                                    "Label",
+                                    entry_label,
+                                    RecipientMessage::EntryLabelEdited,
+                                )
+                                .padding(10),
+                            )
+                            .push(
+                                button::cancel(
+                                    &mut self.save_entry_button,
+                                    Container::new(text::simple("Save")).padding(10),
+                                )
+                                .on_press(RecipientMessage::SaveEntry),
+                            ),
+                    )
+                    .push(
+                        TextInput::new(
+                            &mut self.entry_notes_input,
+                            "Notes",
+                            entry_notes,
+                            RecipientMessage::EntryNotesEdited,
+                        )
+                        .padding(10),
+                    )
+                    .push(Checkbox::new(
+                        entry_whitelisted,
+                        "Whitelisted counterparty",
+                        RecipientMessage::EntryWhitelisted,
+                    ));
+                if let Some(message) = entry_warning {
+                    entry = entry.push(card::alert_warning(Container::new(text::simple(message))));
+                }
+                col = col.push(card::simple(Container::new(entry)));
+            }
+            _ => {}
+        }
+
+        if duplicate {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                "This address is already a recipient, the amounts will be paid in a single output",
+            ))))
+        }
+        if own_address {
             col = col.push(card::alert_warning(Container::new(text::simple(
-                "Please enter a valid bitcoin address",
+                "This address belongs to one of your vaults",
             ))))
         }
         col = col.push(text::bold(text::simple("Enter amount:"))).push(
@@ -194,10 +496,14 @@ impl ManagerSendOutputView {
             .padding(10),
         );
 
-        if *warning_amount {
-            col = col.push(card::alert_warning(Container::new(text::simple(
-                "Please enter a valid amount",
-            ))))
+        if let Some(message) = warning_amount {
+            col = col.push(card::alert_warning(Container::new(text::simple(message))))
+        }
+        if let Some(label) = label {
+            col = col.push(text::small(&format!("Label: {}", label)));
+        }
+        if let Some(message) = message {
+            col = col.push(text::small(&format!("Message: {}", message)));
         }
         Container::new(col.spacing(10)).into()
     }
@@ -210,6 +516,7 @@ pub struct ManagerSelectInputsView {
     cancel_button: iced::button::State,
     next_button: iced::button::State,
     new_output_button: iced::button::State,
+    auto_select_button: iced::button::State,
 }
 
 impl ManagerSelectInputsView {
@@ -220,27 +527,84 @@ impl ManagerSelectInputsView {
             next_button: iced::button::State::new(),
             scroll: scrollable::State::new(),
             new_output_button: iced::button::State::new(),
+            auto_select_button: iced::button::State::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
-        selected_inputs: Vec<Element<'a, Message>>,
+        ctx: &Context,
+        groups: Vec<(String, Vec<Element<'a, Message>>)>,
+        input_amount: u64,
+        output_amount: u64,
+        estimated_fee: u64,
+        warning: Option<&Error>,
         valid: bool,
     ) -> Element<'a, Message> {
         let mut col_inputs = Column::new()
             .spacing(20)
             .width(Length::Fill)
             .align_items(iced::Align::Center);
-        for (i, element) in selected_inputs.into_iter().enumerate() {
-            if i > 0 {
-                col_inputs = col_inputs.push(separation().width(Length::Fill));
+        for (title, elements) in groups {
+            col_inputs = col_inputs
+                .push(Container::new(text::bold(text::simple(&title))).width(Length::Fill));
+            for (i, element) in elements.into_iter().enumerate() {
+                if i > 0 {
+                    col_inputs = col_inputs.push(separation().width(Length::Fill));
+                }
+                col_inputs = col_inputs.push(element);
             }
-            col_inputs = col_inputs.push(element);
         }
         let element: Element<_> = col_inputs.max_width(500).into();
 
-        let mut footer = Column::new();
+        let change = input_amount.saturating_sub(output_amount + estimated_fee);
+        let mut summary = Column::new()
+            .push(
+                Row::new()
+                    .push(Container::new(text::simple("Selected")).width(Length::Fill))
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(input_amount),
+                        ctx.converter.unit
+                    )))),
+            )
+            .push(
+                Row::new()
+                    .push(Container::new(text::simple("Estimated fee")).width(Length::Fill))
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(estimated_fee),
+                        ctx.converter.unit
+                    )))),
+            )
+            .push(
+                Row::new()
+                    .push(Container::new(text::simple("Change")).width(Length::Fill))
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(change),
+                        ctx.converter.unit
+                    )))),
+            )
+            .spacing(5)
+            .max_width(500);
+        if let Some(error) = warning {
+            summary = summary.push(card::alert_warning(Container::new(text::simple(
+                &error.to_string(),
+            ))));
+        }
+
+        let mut footer = Column::new()
+            .spacing(20)
+            .align_items(iced::Align::Center)
+            .push(
+                button::cancel(
+                    &mut self.auto_select_button,
+                    Container::new(text::simple("Select automatically")).padding(10),
+                )
+                .on_press(Message::AutoSelectInputs),
+            );
         if valid {
             footer = footer.push(Container::new(
                 button::primary(
@@ -287,6 +651,11 @@ impl ManagerSelectInputsView {
                             .width(Length::Fill)
                             .align_x(iced::Align::Center),
                     )
+                    .push(
+                        Container::new(card::white(Container::new(summary)))
+                            .width(Length::Fill)
+                            .align_x(iced::Align::Center),
+                    )
                     .push(
                         Column::new()
                             .push(footer)
@@ -308,16 +677,28 @@ pub fn manager_send_input_view<'a>(
     outpoint: &str,
     amount: &u64,
     selected: bool,
+    restriction: Option<&str>,
 ) -> Element<'a, InputMessage> {
+    let amount = text::bold(text::simple(&format!(
+        "{}",
+        ctx.converter.converts(*amount)
+    )));
+    // Vaults that cannot be spent are displayed without checkbox.
+    if let Some(reason) = restriction {
+        let col = Column::new()
+            .push(
+                Row::new()
+                    .push(Container::new(text::small(outpoint)).width(Length::Fill))
+                    .push(amount)
+                    .spacing(20),
+            )
+            .push(text::small(reason))
+            .spacing(5);
+        return card::grey(Container::new(col)).width(Length::Fill).into();
+    }
     let checkbox =
         Checkbox::new(selected, &format!("{}", outpoint), InputMessage::Selected).text_size(15);
-    let row = Row::new()
-        .push(checkbox)
-        .push(text::bold(text::simple(&format!(
-            "{}",
-            ctx.converter.converts(*amount)
-        ))))
-        .spacing(20);
+    let row = Row::new().push(checkbox).push(amount).spacing(20);
     Container::new(row).width(Length::Fill).into()
 }
 
@@ -327,6 +708,10 @@ pub struct ManagerSelectFeeView {
     cancel_button: iced::button::State,
     next_button: iced::button::State,
     back_button: iced::button::State,
+    fast_button: iced::button::State,
+    normal_button: iced::button::State,
+    slow_button: iced::button::State,
+    feerate_input: text_input::State,
 }
 
 impl ManagerSelectFeeView {
@@ -336,10 +721,104 @@ impl ManagerSelectFeeView {
             next_button: iced::button::State::new(),
             back_button: iced::button::State::new(),
             scroll: scrollable::State::new(),
+            fast_button: iced::button::State::new(),
+            normal_button: iced::button::State::new(),
+            slow_button: iced::button::State::new(),
+            feerate_input: text_input::State::new(),
         }
     }
 
-    pub fn view<'a>(&'a mut self, valid: bool) -> Element<'a, Message> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        feerate: &str,
+        estimates: Option<&FeeEstimates>,
+        priority: Option<FeePriority>,
+        fee: Option<u64>,
+        change: Option<u64>,
+        // percentage of the spent amount exceeded by the fee, if any.
+        fee_warning: Option<u64>,
+        processing: bool,
+        warning: Option<&Error>,
+        valid: bool,
+    ) -> Element<'a, Message> {
+        let mut col = Column::new().spacing(10).max_width(500);
+        if let Some(estimates) = estimates {
+            let mut presets = Row::new().spacing(10);
+            for (state, preset) in vec![
+                (&mut self.fast_button, FeePriority::Fast),
+                (&mut self.normal_button, FeePriority::Normal),
+                (&mut self.slow_button, FeePriority::Slow),
+            ] {
+                let label = format!("{}: {} sat/vbyte", preset, estimates.feerate(preset));
+                let content = if priority == Some(preset) {
+                    text::bold(text::simple(&label))
+                } else {
+                    text::simple(&label)
+                };
+                presets = presets.push(
+                    button::white_card_button(state, Container::new(content))
+                        .on_press(Message::FeePriority(preset)),
+                );
+            }
+            col = col
+                .push(text::bold(text::simple("Select priority:")))
+                .push(presets);
+        }
+
+        col = col
+            .push(text::bold(text::simple("Enter fee rate (sat/vbyte):")))
+            .push(
+                TextInput::new(
+                    &mut self.feerate_input,
+                    "1",
+                    feerate,
+                    Message::FeerateEdited,
+                )
+                .padding(10),
+            );
+
+        if processing {
+            col = col.push(text::simple("Computing fee..."));
+        } else if let Some(change) = change {
+            let fee = match fee {
+                Some(fee) => format!("{} {}", ctx.converter.converts(fee), ctx.converter.unit),
+                None => "unknown".to_string(),
+            };
+            col = col.push(card::white(Container::new(
+                Column::new()
+                    .push(
+                        Row::new()
+                            .push(Container::new(text::simple("Total fee")).width(Length::Fill))
+                            .push(text::bold(text::simple(&fee))),
+                    )
+                    .push(
+                        Row::new()
+                            .push(Container::new(text::simple("Change")).width(Length::Fill))
+                            .push(text::bold(text::simple(&format!(
+                                "{} {}",
+                                ctx.converter.converts(change),
+                                ctx.converter.unit
+                            )))),
+                    )
+                    .spacing(5),
+            )));
+        }
+
+        if let Some(percent) = fee_warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(&format!(
+                "The fee is more than {}% of the spent amount",
+                percent
+            )))));
+        }
+
+        if let Some(error) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                &error.to_string(),
+            ))));
+        }
+
         let mut footer = Row::new().spacing(20);
         if valid {
             footer = footer.push(Container::new(
@@ -383,7 +862,7 @@ impl ManagerSelectFeeView {
                             ),
                     )
                     .push(
-                        Container::new(text::simple("Select fee"))
+                        Container::new(col)
                             .width(Length::Fill)
                             .align_x(iced::Align::Center),
                     )
@@ -407,36 +886,122 @@ impl ManagerSelectFeeView {
 pub struct ManagerSignView {
     scroll: scrollable::State,
     cancel_button: iced::button::State,
-    next_button: iced::button::State,
+    retry_button: iced::button::State,
     back_button: iced::button::State,
+    memo_input: text_input::State,
 }
 
 impl ManagerSignView {
     pub fn new() -> Self {
         ManagerSignView {
+            memo_input: text_input::State::new(),
             cancel_button: iced::button::State::new(),
-            next_button: iced::button::State::new(),
+            retry_button: iced::button::State::new(),
             back_button: iced::button::State::new(),
             scroll: scrollable::State::new(),
         }
     }
 
-    pub fn view<'a>(&'a mut self, valid: bool) -> Element<'a, Message> {
-        let mut footer = Row::new().spacing(20);
-        if valid {
-            footer = footer.push(Container::new(
-                button::primary(
-                    &mut self.next_button,
-                    Container::new(text::simple("Continue")).padding(10),
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        warning: Option<&Error>,
+        spend_tx: &Psbt,
+        fee: Option<u64>,
+        memo: &str,
+        violations: &[Violation],
+        signatures: Element<'a, Message>,
+        signer: Element<'a, Message>,
+        signed: bool,
+        processing: bool,
+    ) -> Element<'a, Message> {
+        let mut summary = Column::new().spacing(5);
+        for output in &spend_tx.global.unsigned_tx.output {
+            let address = bitcoin::Address::from_script(&output.script_pubkey, ctx.network)
+                .map(|address| address.to_string())
+                .unwrap_or_else(|| format!("{:x}", output.script_pubkey));
+            summary = summary.push(
+                Row::new()
+                    .push(Container::new(text::small(&address)).width(Length::Fill))
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(output.value),
+                        ctx.converter.unit
+                    ))))
+                    .spacing(20),
+            );
+        }
+        summary = summary.push(separation().width(Length::Fill)).push(
+            Row::new()
+                .push(Container::new(text::simple("Fee")).width(Length::Fill))
+                .push(text::bold(text::simple(&match fee {
+                    Some(fee) => format!("{} {}", ctx.converter.converts(fee), ctx.converter.unit),
+                    None => "unknown".to_string(),
+                })))
+                .spacing(20),
+        );
+
+        let mut col = Column::new()
+            .push(card::white(Container::new(summary)))
+            .push(
+                Column::new()
+                    .spacing(10)
+                    .push(text::bold(text::simple("Memo:")))
+                    .push(
+                        TextInput::new(
+                            &mut self.memo_input,
+                            "Description of the spend",
+                            memo,
+                            Message::MemoEdited,
+                        )
+                        .padding(10),
+                    ),
+            )
+            .spacing(20)
+            .max_width(800);
+
+        let blocked = policy::is_blocked(violations);
+        if !violations.is_empty() {
+            let mut rules = Column::new()
+                .spacing(5)
+                .push(text::bold(text::simple(if blocked {
+                    "The spend transaction breaks the spending policy and cannot be submitted:"
+                } else {
+                    "The spend transaction may be rejected by the watchtowers:"
+                })));
+            for violation in violations {
+                rules = rules.push(text::small(&violation.to_string()));
+            }
+            col = col.push(card::alert_warning(Container::new(rules)));
+        }
+
+        col = col
+            .push(card::white(Container::new(signatures)))
+            .push(card::white(Container::new(signer)));
+
+        if let Some(error) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                &error.to_string(),
+            ))));
+        }
+
+        if processing {
+            col = col.push(text::simple("Submitting spend transaction..."));
+        } else if signed && !blocked {
+            col = col.push(
+                Container::new(
+                    button::primary(
+                        &mut self.retry_button,
+                        Container::new(text::simple("Submit again")).padding(10),
+                    )
+                    .on_press(Message::Next),
                 )
-                .on_press(Message::Next),
-            ));
-        } else {
-            footer = footer.push(Container::new(button::primary_disable(
-                &mut self.next_button,
-                Container::new(text::simple("Continue")).padding(10),
-            )));
+                .width(Length::Fill)
+                .align_x(iced::Align::Center),
+            );
         }
+
         Container::new(
             Scrollable::new(&mut self.scroll).push(Container::new(
                 Column::new()
@@ -465,16 +1030,10 @@ impl ManagerSignView {
                             ),
                     )
                     .push(
-                        Container::new(text::simple("Sign tx"))
+                        Container::new(col)
                             .width(Length::Fill)
                             .align_x(iced::Align::Center),
                     )
-                    .push(
-                        Column::new()
-                            .push(footer)
-                            .width(Length::Fill)
-                            .align_items(iced::Align::Center),
-                    )
                     .spacing(20),
             )),
         )
@@ -484,3 +1043,153 @@ impl ManagerSignView {
         .into()
     }
 }
+
+/// ManagerSignaturesView lists the manager signatures of the spend transaction
+/// and exchanges the spend transaction with the other managers.
+#[derive(Debug)]
+pub struct ManagerSignaturesView {
+    path_input: text_input::State,
+    export_button: iced::button::State,
+    import_button: iced::button::State,
+}
+
+impl ManagerSignaturesView {
+    pub fn new() -> Self {
+        ManagerSignaturesView {
+            path_input: text_input::State::new(),
+            export_button: iced::button::State::new(),
+            import_button: iced::button::State::new(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a mut self,
+        _ctx: &Context,
+        signatures: &[(ExtendedPubKey, bool)],
+        our_key: Option<ExtendedPubKey>,
+        threshold: usize,
+        path: &str,
+        warning: Option<&String>,
+        exported: Option<&PathBuf>,
+    ) -> Element<'a, Message> {
+        let count = signatures.iter().filter(|(_, signed)| *signed).count();
+        let mut col = Column::new()
+            .spacing(10)
+            .push(text::bold(text::simple(&format!(
+                "{} of the {} required manager signatures",
+                count, threshold
+            ))));
+        for (key, signed) in signatures {
+            let mut name = format!("Manager {}", key.fingerprint());
+            if Some(*key) == our_key {
+                name.push_str(" (you)");
+            }
+            col = col.push(
+                Row::new()
+                    .spacing(20)
+                    .push(Container::new(text::simple(&name)).width(Length::Fill))
+                    .push(if *signed {
+                        text::success(text::simple("Signed"))
+                    } else {
+                        text::simple("Not signed")
+                    }),
+            );
+        }
+        col = col.push(text::small(
+            "The cosigning servers sign once the spend transaction is submitted",
+        ));
+
+        if count < threshold {
+            col = col
+                .push(separation().width(Length::Fill))
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(iced::Align::Center)
+                        .push(
+                            Container::new(text::simple(
+                                "Export the spend transaction for the other managers:",
+                            ))
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            button::cancel(
+                                &mut self.export_button,
+                                Container::new(text::simple("Export")).padding(10),
+                            )
+                            .on_press(Message::ExportSpendTx),
+                        ),
+                )
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(iced::Align::Center)
+                        .push(
+                            TextInput::new(
+                                &mut self.path_input,
+                                "Spend transaction signed by another manager",
+                                path,
+                                Message::SpendTxPathEdited,
+                            )
+                            .padding(10),
+                        )
+                        .push(
+                            button::cancel(
+                                &mut self.import_button,
+                                Container::new(text::simple("Import")).padding(10),
+                            )
+                            .on_press(Message::ImportSpendTx),
+                        ),
+                );
+            if let Some(path) = exported {
+                col = col.push(text::small(&format!(
+                    "Spend transaction exported to {}",
+                    path.to_string_lossy()
+                )));
+            }
+        }
+
+        if let Some(message) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+        col.into()
+    }
+}
+
+#[derive(Debug)]
+pub struct ManagerSpendSuccessView {
+    home_button: iced::button::State,
+}
+
+impl ManagerSpendSuccessView {
+    pub fn new() -> Self {
+        ManagerSpendSuccessView {
+            home_button: iced::button::State::new(),
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        Container::new(
+            Column::new()
+                .push(card::success(Container::new(text::success(text::simple(
+                    "Spend transaction submitted, the vaults will be unvaulted",
+                )))))
+                .push(
+                    button::primary(
+                        &mut self.home_button,
+                        Container::new(text::simple("Go back home")).padding(10),
+                    )
+                    .on_press(Message::Menu(Menu::Home)),
+                )
+                .spacing(20)
+                .align_items(iced::Align::Center),
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+}
@@ -5,18 +5,25 @@ mod home;
 pub mod installing;
 mod layout;
 pub mod manager;
+mod monitor;
 mod network;
+mod notification;
 mod sidebar;
 pub mod sign;
 pub mod stakeholder;
 pub mod vault;
 
 pub use deposit::DepositView;
-pub use history::HistoryView;
+pub use history::{HistoryControls, HistoryView};
 pub use home::{ManagerHomeView, StakeholderHomeView};
 pub use manager::ManagerSendView;
+pub use monitor::{MonitorView, MonitoredVault};
 pub use network::{ManagerNetworkView, StakeholderNetworkView};
-pub use stakeholder::{StakeholderACKDepositView, StakeholderACKFundsView};
+pub use notification::NotificationsView;
+pub use stakeholder::{
+    RevocationBatchView, StakeholderACKDepositView, StakeholderACKFundsView,
+    StakeholderDelegateView,
+};
 
 use bitcoin::Network;
 
@@ -33,6 +40,8 @@ pub struct Context {
     pub menu: Menu,
     pub role: Role,
     pub role_edit: bool,
+    /// number of notifications not read yet, displayed by the sidebar.
+    pub unread_notifications: usize,
 }
 
 impl Context {
@@ -50,6 +59,7 @@ impl Context {
             menu,
             network,
             network_up: false,
+            unread_notifications: 0,
         }
     }
 }
@@ -63,6 +73,7 @@ impl std::default::Default for Context {
             role: Role::Manager,
             menu: Menu::Home,
             role_edit: false,
+            unread_notifications: 0,
         }
     }
 }
@@ -0,0 +1,159 @@
+use iced::{scrollable, Align, Column, Container, Element, Length, Row, Scrollable};
+
+use crate::revaultd::model::{Vault, VaultStatus};
+
+use crate::ui::{
+    component::{badge, button, card, navbar, separation, text},
+    error::Error,
+    message::Message,
+    view::{layout, sidebar::Sidebar, Context},
+};
+
+/// MonitoredVault is a vault being unvaulted with what is known of its spend.
+#[derive(Debug)]
+pub struct MonitoredVault {
+    pub vault: Vault,
+    /// addresses and amounts of the spend transaction outputs.
+    pub destinations: Vec<(String, u64)>,
+    /// blocks left before the spend transaction can be broadcast,
+    /// unknown if the spend transaction is unknown.
+    pub remaining_blocks: Option<u64>,
+    pub unvault_confirmed: bool,
+    pub cancel_error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct MonitorView {
+    scroll: scrollable::State,
+    sidebar: Sidebar,
+    cancel_buttons: Vec<iced::button::State>,
+}
+
+impl MonitorView {
+    pub fn new() -> Self {
+        MonitorView {
+            sidebar: Sidebar::new(),
+            scroll: scrollable::State::new(),
+            cancel_buttons: Vec::new(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        warning: Option<&Error>,
+        blockheight: Option<&u64>,
+        vaults: Vec<MonitoredVault>,
+    ) -> Element<'a, Message> {
+        let mut col = Column::new().spacing(20).push(
+            Row::new()
+                .push(
+                    Container::new(text::bold(text::simple(&format!(
+                        "{} vaults being unvaulted",
+                        vaults.len()
+                    ))))
+                    .width(Length::Fill),
+                )
+                .push(text::small(&match blockheight {
+                    Some(height) => format!("Block height: {}", height),
+                    None => "Block height: unknown".to_string(),
+                })),
+        );
+
+        self.cancel_buttons
+            .resize_with(vaults.len(), iced::button::State::new);
+        for (monitored, state) in vaults.iter().zip(self.cancel_buttons.iter_mut()) {
+            col = col.push(monitored_vault(ctx, monitored, state));
+        }
+
+        layout::dashboard(
+            navbar(layout::navbar_warning(warning)),
+            self.sidebar.view(ctx),
+            layout::main_section(Container::new(
+                Scrollable::new(&mut self.scroll).push(Container::new(col)),
+            )),
+        )
+        .into()
+    }
+}
+
+fn monitored_vault<'a>(
+    ctx: &Context,
+    monitored: &MonitoredVault,
+    cancel_button: &'a mut iced::button::State,
+) -> Element<'a, Message> {
+    let vault = &monitored.vault;
+    let mut col = Column::new().spacing(10).push(
+        Row::new()
+            .spacing(20)
+            .align_items(Align::Center)
+            .push(if vault.status == VaultStatus::Canceling {
+                badge::vault_canceling()
+            } else {
+                badge::vault_unvaulting()
+            })
+            .push(
+                Column::new()
+                    .push(text::bold(text::small(&vault.address)))
+                    .push(text::small(&vault.status.to_string()))
+                    .width(Length::Fill),
+            )
+            .push(text::bold(text::simple(&format!(
+                "{} {}",
+                ctx.converter.converts(vault.amount),
+                ctx.converter.unit
+            )))),
+    );
+
+    col = col.push(separation().width(Length::Fill));
+    if monitored.destinations.is_empty() {
+        col = col.push(text::small(
+            "Destination unknown until the spend is broadcast",
+        ));
+    } else {
+        for (address, amount) in &monitored.destinations {
+            col = col.push(
+                Row::new()
+                    .push(Container::new(text::small(address)).width(Length::Fill))
+                    .push(text::small(&format!(
+                        "{} {}",
+                        ctx.converter.converts(*amount),
+                        ctx.converter.unit
+                    ))),
+            );
+        }
+    }
+
+    if vault.status != VaultStatus::Canceling {
+        let remaining = match monitored.remaining_blocks {
+            Some(0) => text::danger(text::bold(text::simple(
+                "Timelock expired, the spend can be broadcast",
+            ))),
+            Some(blocks) if !monitored.unvault_confirmed => text::simple(&format!(
+                "{} blocks to cancel once the unvault is confirmed",
+                blocks
+            )),
+            Some(blocks) => text::bold(text::simple(&format!("{} blocks left to cancel", blocks))),
+            None => text::simple("Blocks left to cancel unknown"),
+        };
+        col = col.push(
+            Row::new()
+                .spacing(20)
+                .align_items(Align::Center)
+                .push(Container::new(remaining).width(Length::Fill))
+                .push(
+                    button::important(cancel_button, button::button_content(None, "Cancel"))
+                        .on_press(Message::CancelVault(vault.outpoint())),
+                ),
+        );
+    }
+
+    if let Some(error) = &monitored.cancel_error {
+        col = col.push(card::alert_warning(Container::new(text::simple(&format!(
+            "Failed to cancel: {}",
+            error
+        )))));
+    }
+
+    card::white(Container::new(col)).into()
+}
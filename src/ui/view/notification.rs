@@ -0,0 +1,130 @@
+use chrono::NaiveDateTime;
+use iced::{scrollable, Align, Column, Container, Element, Length, Row, Scrollable};
+
+use crate::notification::{EventKind, NotificationCenter};
+
+use crate::ui::{
+    component::{button, card, navbar, separation, text},
+    message::Message,
+    view::{layout, sidebar::Sidebar, Context},
+};
+
+#[derive(Debug)]
+pub struct NotificationsView {
+    scroll: scrollable::State,
+    sidebar: Sidebar,
+    read_button: iced::button::State,
+    notification_buttons: Vec<iced::button::State>,
+    toggle_buttons: Vec<iced::button::State>,
+}
+
+impl NotificationsView {
+    pub fn new() -> Self {
+        NotificationsView {
+            scroll: scrollable::State::new(),
+            sidebar: Sidebar::new(),
+            read_button: iced::button::State::new(),
+            notification_buttons: Vec::new(),
+            toggle_buttons: Vec::new(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        center: &NotificationCenter,
+    ) -> Element<'a, Message> {
+        let mut col_notifications = Column::new().spacing(10).push(
+            Row::new()
+                .align_items(Align::Center)
+                .push(
+                    Container::new(text::bold(text::simple(&format!(
+                        "Notifications ({} unread)",
+                        center.unread()
+                    ))))
+                    .width(Length::Fill),
+                )
+                .push(
+                    button::cancel(
+                        &mut self.read_button,
+                        Container::new(text::simple("Mark all as read")).padding(10),
+                    )
+                    .on_press(Message::MarkNotificationsRead),
+                ),
+        );
+        if center.notifications.is_empty() {
+            col_notifications = col_notifications.push(text::simple("No notification yet"));
+        }
+        self.notification_buttons
+            .resize_with(center.notifications.len(), iced::button::State::new);
+        for (notification, state) in center
+            .notifications
+            .iter()
+            .zip(self.notification_buttons.iter_mut())
+        {
+            let date = NaiveDateTime::from_timestamp(notification.received_at, 0);
+            let message = if notification.read {
+                text::small(&notification.message)
+            } else {
+                text::bold(text::small(&notification.message))
+            };
+            col_notifications = col_notifications.push(
+                Row::new()
+                    .spacing(20)
+                    .push(Container::new(text::small(&date.to_string())).width(Length::Units(150)))
+                    .push(Container::new(message).width(Length::Fill))
+                    .push(
+                        button::cancel(
+                            state,
+                            Container::new(text::small(if notification.read {
+                                "Mark as unread"
+                            } else {
+                                "Mark as read"
+                            }))
+                            .padding(5),
+                        )
+                        .on_press(Message::ToggleNotificationRead(notification.id)),
+                    ),
+            );
+        }
+
+        let mut col_preferences =
+            Column::new()
+                .spacing(10)
+                .push(text::bold(text::simple(&format!(
+                    "Desktop notifications of the {} role",
+                    ctx.role
+                ))));
+        self.toggle_buttons
+            .resize_with(EventKind::ALL.len(), iced::button::State::new);
+        for (kind, state) in EventKind::ALL.iter().zip(self.toggle_buttons.iter_mut()) {
+            let enabled = center.preferences.enabled(ctx.role, *kind);
+            let toggle = if enabled {
+                button::primary(state, Container::new(text::simple("On")).padding(5))
+            } else {
+                button::cancel(state, Container::new(text::simple("Off")).padding(5))
+            };
+            col_preferences = col_preferences.push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(Container::new(text::simple(&kind.to_string())).width(Length::Fill))
+                    .push(toggle.on_press(Message::ToggleNotification(*kind))),
+            );
+        }
+
+        layout::dashboard(
+            navbar(None),
+            self.sidebar.view(ctx),
+            layout::main_section(Container::new(
+                Scrollable::new(&mut self.scroll).push(Container::new(
+                    Column::new()
+                        .spacing(20)
+                        .push(card::white(Container::new(col_notifications)))
+                        .push(separation().width(Length::Fill))
+                        .push(card::white(Container::new(col_preferences))),
+                )),
+            )),
+        )
+        .into()
+    }
+}
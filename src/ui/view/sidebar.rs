@@ -1,10 +1,11 @@
-use iced::{pick_list, Column, Container, Length, Row};
+use iced::{pick_list, Container, Length, Row};
 
 use crate::revault::Role;
 use crate::ui::{
     component::{button, separation, text, TransparentPickListStyle},
     icon::{
         deposit_icon, dot_icon, history_icon, home_icon, network_icon, send_icon, settings_icon,
+        shield_notif_icon, withdrawal_icon,
     },
     menu::Menu,
     message::Message,
@@ -19,6 +20,8 @@ pub struct Sidebar {
     history_menu_button: iced::button::State,
     network_menu_button: iced::button::State,
     spend_menu_button: iced::button::State,
+    monitor_menu_button: iced::button::State,
+    notifications_menu_button: iced::button::State,
     settings_menu_button: iced::button::State,
 }
 
@@ -30,6 +33,8 @@ impl Sidebar {
             history_menu_button: iced::button::State::new(),
             network_menu_button: iced::button::State::new(),
             spend_menu_button: iced::button::State::new(),
+            monitor_menu_button: iced::button::State::new(),
+            notifications_menu_button: iced::button::State::new(),
             settings_menu_button: iced::button::State::new(),
             pick_role: pick_list::State::default(),
         }
@@ -117,6 +122,25 @@ impl Sidebar {
             .on_press(Message::Menu(Menu::Deposit))
         };
 
+        let unread = context.unread_notifications;
+        let notifications_label = if unread > 0 {
+            format!("Notifications ({})", unread)
+        } else {
+            "Notifications".to_string()
+        };
+        let notifications_button = if context.menu == Menu::Notifications {
+            button::primary(
+                &mut self.notifications_menu_button,
+                button::button_content(Some(shield_notif_icon()), &notifications_label),
+            )
+        } else {
+            button::transparent(
+                &mut self.notifications_menu_button,
+                button::button_content(Some(shield_notif_icon()), &notifications_label),
+            )
+        }
+        .on_press(Message::Menu(Menu::Notifications));
+
         let actions = if context.role == Role::Manager {
             Container::new(
                 button::transparent(
@@ -127,7 +151,22 @@ impl Sidebar {
                 .width(iced::Length::Units(200)),
             )
         } else {
-            Container::new(Column::new())
+            let monitor_button = if context.menu == Menu::Monitor {
+                button::primary(
+                    &mut self.monitor_menu_button,
+                    button::button_content(Some(withdrawal_icon()), "Monitor"),
+                )
+            } else {
+                button::transparent(
+                    &mut self.monitor_menu_button,
+                    button::button_content(Some(withdrawal_icon()), "Monitor"),
+                )
+            };
+            Container::new(
+                monitor_button
+                    .on_press(Message::Menu(Menu::Monitor))
+                    .width(iced::Length::Units(200)),
+            )
         };
         layout::sidebar(
             layout::sidebar_menu(vec![
@@ -136,6 +175,7 @@ impl Sidebar {
                 Container::new(home_button.width(Length::Units(200))),
                 Container::new(history_button.width(Length::Units(200))),
                 Container::new(network_button.width(Length::Units(200))),
+                Container::new(notifications_button.width(Length::Units(200))),
                 separation().width(Length::Units(200)),
                 Container::new(deposit_button.width(Length::Units(200))),
                 actions,
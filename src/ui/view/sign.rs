@@ -1,8 +1,12 @@
+use std::path::{Path, PathBuf};
+
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
-use iced::{Align, Column, Container, Element, Length, Row, TextInput};
+use iced::{Align, Column, Container, Element, Length, QRCode, Row, TextInput};
 
 use crate::{
+    psbt::{signature_counts, Conflict, Format},
     revault::TransactionKind,
+    signer::Device,
     ui::{
         component::{button, card, separation, text},
         message::SignMessage,
@@ -14,6 +18,9 @@ use crate::{
 pub struct DirectSignatureView {
     indirect_button: iced::button::State,
     sign_button: iced::button::State,
+    refresh_button: iced::button::State,
+    software_button: iced::button::State,
+    device_buttons: Vec<iced::button::State>,
 }
 
 impl DirectSignatureView {
@@ -21,13 +28,24 @@ impl DirectSignatureView {
         DirectSignatureView {
             indirect_button: iced::button::State::default(),
             sign_button: iced::button::State::default(),
+            refresh_button: iced::button::State::default(),
+            software_button: iced::button::State::default(),
+            device_buttons: Vec::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view(
         &mut self,
         _ctx: &Context,
+        processing: &bool,
         transaction_kind: &TransactionKind,
+        devices: &[Device],
+        selected: Option<usize>,
+        holds_our_key: Option<bool>,
+        software_signer: bool,
+        software_signer_error: Option<&String>,
+        warning: Option<&String>,
     ) -> Element<SignMessage> {
         let title = match transaction_kind {
             TransactionKind::Emergency => {
@@ -43,7 +61,7 @@ impl DirectSignatureView {
             }
         };
 
-        let col = Column::new()
+        let mut col = Column::new()
             .push(
                 Row::new()
                     .push(Container::new(title).width(Length::Fill))
@@ -57,26 +75,99 @@ impl DirectSignatureView {
                     )
                     .align_items(Align::Center),
             )
-            .push(separation().width(Length::Fill))
-            .push(
+            .push(separation().width(Length::Fill));
+
+        if devices.is_empty() {
+            col = col.push(
                 Container::new(text::simple("Connect device"))
                     .padding(20)
                     .width(Length::Fill)
                     .align_x(Align::Center),
-            )
+            );
+        }
+
+        self.device_buttons
+            .resize_with(devices.len(), iced::button::State::default);
+        for (i, (device, state)) in devices
+            .iter()
+            .zip(self.device_buttons.iter_mut())
+            .enumerate()
+        {
+            let label = match &device.fingerprint {
+                Some(fingerprint) => format!("{} ({})", device.model, fingerprint),
+                None => format!("{} (locked)", device.model),
+            };
+            let label = if selected == Some(i) {
+                text::bold(text::simple(&label))
+            } else {
+                text::simple(&label)
+            };
+            col = col.push(
+                button::white_card_button(state, Container::new(label).width(Length::Fill))
+                    .on_press(SignMessage::SelectDevice(i))
+                    .width(Length::Fill),
+            );
+        }
+
+        let mut buttons = Row::new()
             .push(
-                Container::new(
-                    button::primary(
-                        &mut self.sign_button,
-                        button::button_content(None, " Sign transaction "),
-                    )
-                    .on_press(SignMessage::ChangeMethod),
+                button::transparent(
+                    &mut self.refresh_button,
+                    button::button_content(None, "Refresh devices"),
+                )
+                .on_press(SignMessage::ListDevices),
+            )
+            .spacing(10)
+            .align_items(Align::Center);
+        if software_signer {
+            buttons = buttons.push(
+                button::transparent(
+                    &mut self.software_button,
+                    button::button_content(None, "Sign with software key"),
                 )
+                .on_press(SignMessage::SignWithKey),
+            );
+        }
+        col = col.push(buttons);
+
+        if let Some(e) = software_signer_error {
+            col = col.push(card::alert_warning(Container::new(text::simple(&format!(
+                "The software signer key cannot be used: {}",
+                e
+            )))));
+        }
+        if holds_our_key == Some(false) {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                "The selected device does not seem to hold your key",
+            ))));
+        }
+        if let Some(message) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+
+        let sign_button = if *processing {
+            button::primary_disable(
+                &mut self.sign_button,
+                button::button_content(None, " Processing "),
+            )
+        } else if selected.is_some() {
+            button::primary(
+                &mut self.sign_button,
+                button::button_content(None, " Sign transaction "),
+            )
+            .on_press(SignMessage::SignWithDevice)
+        } else {
+            button::primary_disable(
+                &mut self.sign_button,
+                button::button_content(None, " Sign transaction "),
+            )
+        };
+        col = col.push(
+            Container::new(sign_button)
                 .width(Length::Fill)
                 .align_x(Align::Center),
-            )
-            .spacing(10);
-        Container::new(col).into()
+        );
+        Container::new(col.spacing(10)).into()
     }
 }
 
@@ -84,8 +175,18 @@ impl DirectSignatureView {
 pub struct IndirectSignatureView {
     direct_button: iced::button::State,
     sign_button: iced::button::State,
+    add_button: iced::button::State,
     copy_button: iced::button::State,
+    save_button: iced::button::State,
+    save_base64_button: iced::button::State,
+    load_button: iced::button::State,
+    load_qr_codes_button: iced::button::State,
+    qr_code_button: iced::button::State,
     psbt_input: iced::text_input::State,
+    psbt_path_input: iced::text_input::State,
+    /// QR codes of the psbt parts, displayed one after the other.
+    qr_codes: Vec<iced::qr_code::State>,
+    qr_code_index: usize,
 }
 
 impl IndirectSignatureView {
@@ -93,11 +194,49 @@ impl IndirectSignatureView {
         IndirectSignatureView {
             direct_button: iced::button::State::default(),
             sign_button: iced::button::State::default(),
+            add_button: iced::button::State::default(),
             copy_button: iced::button::State::default(),
+            save_button: iced::button::State::default(),
+            save_base64_button: iced::button::State::default(),
+            load_button: iced::button::State::default(),
+            load_qr_codes_button: iced::button::State::default(),
+            qr_code_button: iced::button::State::default(),
             psbt_input: iced::text_input::State::new(),
+            psbt_path_input: iced::text_input::State::new(),
+            qr_codes: Vec::new(),
+            qr_code_index: 0,
+        }
+    }
+
+    // Parts are loaded directly in the view in order to cache the created qrcodes.
+    pub fn load_qr_codes(&mut self, parts: &[String]) {
+        self.qr_codes = parts
+            .iter()
+            .filter_map(|part| iced::qr_code::State::new(part.to_uppercase()).ok())
+            .collect();
+        self.qr_code_index = 0;
+    }
+
+    pub fn clear_qr_codes(&mut self) {
+        self.qr_codes = Vec::new();
+        self.qr_code_index = 0;
+    }
+
+    pub fn has_qr_codes(&self) -> bool {
+        !self.qr_codes.is_empty()
+    }
+
+    pub fn animated(&self) -> bool {
+        self.qr_codes.len() > 1
+    }
+
+    pub fn next_qr_code(&mut self) {
+        if !self.qr_codes.is_empty() {
+            self.qr_code_index = (self.qr_code_index + 1) % self.qr_codes.len();
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view(
         &mut self,
         _ctx: &Context,
@@ -105,6 +244,12 @@ impl IndirectSignatureView {
         transaction_kind: &TransactionKind,
         psbt: &Psbt,
         psbt_input: &str,
+        combined: &Psbt,
+        imported: usize,
+        conflicts: &[Conflict],
+        psbt_path: &str,
+        psbt_dir: &Path,
+        saved: Option<&PathBuf>,
         warning: Option<&String>,
     ) -> Element<SignMessage> {
         let title = match transaction_kind {
@@ -154,7 +299,83 @@ impl IndirectSignatureView {
                         .align_items(Align::Center),
                 )
                 .width(Length::Fill),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        button::transparent(
+                            &mut self.save_button,
+                            button::button_content(None, "Save PSBT"),
+                        )
+                        .on_press(SignMessage::Save(Format::Binary)),
+                    )
+                    .push(
+                        button::transparent(
+                            &mut self.save_base64_button,
+                            button::button_content(None, "Save as base64"),
+                        )
+                        .on_press(SignMessage::Save(Format::Base64)),
+                    )
+                    .push(
+                        button::transparent(
+                            &mut self.qr_code_button,
+                            button::button_content(
+                                None,
+                                if self.qr_codes.is_empty() {
+                                    "Show QR code"
+                                } else {
+                                    "Hide QR code"
+                                },
+                            ),
+                        )
+                        .on_press(SignMessage::ToggleQrCode),
+                    )
+                    .spacing(10)
+                    .align_items(Align::Center),
             );
+        let total = self.qr_codes.len();
+        if let Some(qr_code) = self.qr_codes.get_mut(self.qr_code_index) {
+            let mut qr_col = Column::new()
+                .push(QRCode::new(qr_code).cell_size(3))
+                .align_items(Align::Center)
+                .spacing(10);
+            if total > 1 {
+                qr_col = qr_col.push(text::small(&format!(
+                    "Part {}/{}",
+                    self.qr_code_index + 1,
+                    total
+                )));
+            }
+            col = col.push(
+                Container::new(qr_col)
+                    .width(Length::Fill)
+                    .align_x(Align::Center),
+            );
+        }
+        if let Some(path) = saved {
+            col = col.push(text::small(&format!("PSBT saved to {}", path.display())));
+        }
+        if imported > 0 {
+            let mut counts = Column::new().push(text::simple(&format!(
+                "{} signed PSBT{} combined",
+                imported,
+                if imported > 1 { "s" } else { "" }
+            )));
+            for (index, count) in signature_counts(combined).iter().enumerate() {
+                counts = counts.push(text::small(&format!(
+                    "Input {}: {} signature{}",
+                    index,
+                    count,
+                    if *count > 1 { "s" } else { "" }
+                )));
+            }
+            col = col.push(card::white(Container::new(counts.spacing(5))));
+        }
+        for conflict in conflicts {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                &conflict.to_string(),
+            ))));
+        }
         if let Some(message) = warning {
             col = col.push(card::alert_warning(Container::new(text::simple(message))));
         }
@@ -167,31 +388,79 @@ impl IndirectSignatureView {
                         &mut self.sign_button,
                         button::button_content(None, " Processing "),
                     )
-                    .on_press(SignMessage::Sign),
+                    .on_press(SignMessage::Confirm),
                 ));
         } else {
+            let sign_button = if imported > 0 {
+                button::primary(
+                    &mut self.sign_button,
+                    button::button_content(None, " Sign transaction "),
+                )
+                .on_press(SignMessage::Confirm)
+            } else {
+                button::primary_disable(
+                    &mut self.sign_button,
+                    button::button_content(None, " Sign transaction "),
+                )
+            };
             col = col
                 .push(
-                    TextInput::new(
-                        &mut self.psbt_input,
-                        "Signed PSBT",
-                        &psbt_input,
-                        SignMessage::PsbtEdited,
-                    )
-                    .size(15)
-                    .width(Length::Fill)
-                    .padding(10),
+                    Row::new()
+                        .push(
+                            TextInput::new(
+                                &mut self.psbt_input,
+                                "Signed PSBT",
+                                &psbt_input,
+                                SignMessage::PsbtEdited,
+                            )
+                            .size(15)
+                            .width(Length::Fill)
+                            .padding(10),
+                        )
+                        .push(
+                            button::transparent(
+                                &mut self.add_button,
+                                button::button_content(None, "Add signed PSBT"),
+                            )
+                            .on_press(SignMessage::Sign),
+                        )
+                        .spacing(10)
+                        .align_items(Align::Center),
                 )
                 .push(
-                    Container::new(
-                        button::primary(
-                            &mut self.sign_button,
-                            button::button_content(None, " Sign transaction "),
+                    Row::new()
+                        .push(
+                            TextInput::new(
+                                &mut self.psbt_path_input,
+                                &format!("Signed PSBT file in {}", psbt_dir.display()),
+                                &psbt_path,
+                                SignMessage::PsbtPathEdited,
+                            )
+                            .size(15)
+                            .width(Length::Fill)
+                            .padding(10),
                         )
-                        .on_press(SignMessage::Sign),
-                    )
-                    .width(Length::Fill)
-                    .align_x(Align::Center),
+                        .push(
+                            button::transparent(
+                                &mut self.load_button,
+                                button::button_content(None, "Load signed PSBT"),
+                            )
+                            .on_press(SignMessage::Load),
+                        )
+                        .push(
+                            button::transparent(
+                                &mut self.load_qr_codes_button,
+                                button::button_content(None, "Load QR images"),
+                            )
+                            .on_press(SignMessage::LoadQrCodes),
+                        )
+                        .spacing(10)
+                        .align_items(Align::Center),
+                )
+                .push(
+                    Container::new(sign_button)
+                        .width(Length::Fill)
+                        .align_x(Align::Center),
                 );
         }
         Container::new(col.spacing(10)).into()
@@ -1,4 +1,8 @@
-use iced::{scrollable, Align, Column, Container, Element, Length, Row, Scrollable};
+use std::path::PathBuf;
+
+use iced::{
+    scrollable, text_input, Align, Column, Container, Element, Length, Row, Scrollable, TextInput,
+};
 
 use bitcoin::util::psbt::PartiallySignedTransaction;
 
@@ -6,9 +10,10 @@ use crate::revaultd::model::Vault;
 
 use crate::ui::{
     component::{badge, button, card, separation, text, ContainerBackgroundStyle},
+    error::Error,
     icon,
     menu::Menu,
-    message::{DepositMessage, Message},
+    message::{BatchMessage, DelegateMessage, DepositMessage, Message},
     view::Context,
 };
 
@@ -16,6 +21,7 @@ use crate::ui::{
 pub struct StakeholderACKFundsView {
     scroll: scrollable::State,
     close_button: iced::button::State,
+    batch_button: iced::button::State,
 }
 
 impl StakeholderACKFundsView {
@@ -23,17 +29,45 @@ impl StakeholderACKFundsView {
         StakeholderACKFundsView {
             scroll: scrollable::State::new(),
             close_button: iced::button::State::new(),
+            batch_button: iced::button::State::new(),
         }
     }
 
+    /// If batch is some, the batch signing is displayed instead of the deposits.
     pub fn view<'a>(
         &'a mut self,
         _ctx: &Context,
         deposits: Vec<Element<'a, Message>>,
+        batch: Option<Element<'a, Message>>,
     ) -> Element<'a, Message> {
         let mut col_deposits = Column::new();
-        for element in deposits.into_iter() {
-            col_deposits = col_deposits.push(element);
+        if let Some(batch) = batch {
+            col_deposits = col_deposits.push(batch);
+        } else {
+            if deposits.len() > 1 {
+                col_deposits = col_deposits.push(
+                    Row::new()
+                        .spacing(20)
+                        .align_items(Align::Center)
+                        .push(
+                            Container::new(text::simple(&format!(
+                                "{} deposits to secure, sign all their revocation transactions at once:",
+                                deposits.len()
+                            )))
+                            .width(Length::Fill),
+                        )
+                        .push(
+                            button::primary(
+                                &mut self.batch_button,
+                                Container::new(text::simple("Batch mode")).padding(10),
+                            )
+                            .on_press(Message::Batch(BatchMessage::Start)),
+                        ),
+                );
+            }
+            for element in deposits.into_iter() {
+                col_deposits = col_deposits.push(element);
+            }
         }
         let element: Element<_> = col_deposits.spacing(20).max_width(1000).into();
         let col = Column::new()
@@ -140,18 +174,459 @@ pub fn stakeholder_deposit_pending<'a>(
     .into()
 }
 
+/// deposit_header displays the address and the amount of the deposit to sign.
+fn deposit_header<'a, T: 'a>(ctx: &Context, deposit: &Vault) -> Container<'a, T> {
+    Container::new(
+        Row::new()
+            .push(
+                Container::new(
+                    Row::new()
+                        .push(badge::shield())
+                        .push(
+                            Container::new(text::bold(text::small(&deposit.address)))
+                                .align_y(Align::Center),
+                        )
+                        .spacing(20)
+                        .align_items(Align::Center),
+                )
+                .width(Length::Fill),
+            )
+            .push(
+                Container::new(
+                    Row::new()
+                        .push(text::bold(text::simple(&format!(
+                            "{}",
+                            ctx.converter.converts(deposit.amount)
+                        ))))
+                        .push(text::small(&format!(" {}", ctx.converter.unit)))
+                        .align_items(Align::Center),
+                )
+                .width(Length::Shrink),
+            )
+            .spacing(20)
+            .align_items(Align::Center),
+    )
+}
+
+#[derive(Debug)]
+pub struct StakeholderDelegateView {
+    scroll: scrollable::State,
+    close_button: iced::button::State,
+    start_button: iced::button::State,
+    retry_button: iced::button::State,
+    home_button: iced::button::State,
+    select_buttons: Vec<iced::button::State>,
+}
+
+impl StakeholderDelegateView {
+    pub fn new() -> Self {
+        StakeholderDelegateView {
+            scroll: scrollable::State::new(),
+            close_button: iced::button::State::new(),
+            start_button: iced::button::State::new(),
+            retry_button: iced::button::State::new(),
+            home_button: iced::button::State::new(),
+            select_buttons: Vec::new(),
+        }
+    }
+
+    /// vaults are the secured vaults, whether they are selected and delegated.
+    /// signing is the vault whose unvault transaction is being signed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        warning: Option<&Error>,
+        vaults: Vec<(&Vault, bool, bool)>,
+        signing: Option<&Vault>,
+        signer: Option<Element<'a, Message>>,
+        submit_warning: Option<&String>,
+        done: bool,
+    ) -> Element<'a, Message> {
+        let selected: Vec<&(&Vault, bool, bool)> =
+            vaults.iter().filter(|(_, selected, _)| *selected).collect();
+        let selected_amount: u64 = selected.iter().map(|(v, _, _)| v.amount).sum();
+        let delegated_amount: u64 = selected
+            .iter()
+            .filter(|(_, _, delegated)| *delegated)
+            .map(|(v, _, _)| v.amount)
+            .sum();
+
+        let mut col = Column::new().spacing(20);
+        if let Some(error) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(
+                &error.to_string(),
+            ))));
+        }
+
+        if done {
+            col = col
+                .push(card::success(Container::new(text::success(text::simple(
+                    &format!(
+                        "{} {} delegated, the managers can now spend them",
+                        ctx.converter.converts(delegated_amount),
+                        ctx.converter.unit
+                    ),
+                )))))
+                .push(
+                    button::primary(
+                        &mut self.home_button,
+                        Container::new(text::simple("Go back home")).padding(10),
+                    )
+                    .on_press(Message::Menu(Menu::Home)),
+                );
+        } else if let (Some(vault), Some(signer)) = (signing, signer) {
+            let mut col_signing = Column::new()
+                .spacing(20)
+                .push(text::bold(text::simple(&format!(
+                    "Delegated {}/{} {}",
+                    ctx.converter.converts(delegated_amount),
+                    ctx.converter.converts(selected_amount),
+                    ctx.converter.unit
+                ))))
+                .push(text::simple(&format!(
+                    "Sign the unvault transaction of the vault {}",
+                    vault.outpoint()
+                )))
+                .push(signer);
+            if let Some(error) = submit_warning {
+                col_signing = col_signing.push(card::alert_warning(Container::new(
+                    Column::new().spacing(20).push(text::simple(error)).push(
+                        button::primary(
+                            &mut self.retry_button,
+                            button::button_content(None, "Retry"),
+                        )
+                        .on_press(Message::Delegate(DelegateMessage::Retry)),
+                    ),
+                )));
+            }
+            col = col.push(card::white(Container::new(col_signing)));
+        } else if vaults.is_empty() {
+            col = col.push(text::simple("No secured vault to delegate"));
+        } else {
+            col = col.push(text::simple(
+                "Select the vaults to delegate to the managers, \
+                their unvault transaction will be signed",
+            ));
+            self.select_buttons
+                .resize_with(vaults.len(), iced::button::State::new);
+            let mut col_vaults = Column::new().spacing(10);
+            for ((i, (vault, selected, _)), state) in vaults
+                .iter()
+                .enumerate()
+                .zip(self.select_buttons.iter_mut())
+            {
+                let row = Row::new()
+                    .spacing(20)
+                    .align_items(Align::Center)
+                    .push(if *selected {
+                        badge::shield_success()
+                    } else {
+                        badge::shield()
+                    })
+                    .push(Container::new(text::small(&vault.address)).width(Length::Fill))
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(vault.amount),
+                        ctx.converter.unit
+                    ))));
+                col_vaults = col_vaults.push(
+                    button::white_card_button(state, Container::new(row))
+                        .on_press(Message::Delegate(DelegateMessage::Select(i))),
+                );
+            }
+            let mut start_button = button::primary(
+                &mut self.start_button,
+                Container::new(text::simple(&format!(
+                    "Delegate {} {}",
+                    ctx.converter.converts(selected_amount),
+                    ctx.converter.unit
+                )))
+                .padding(10),
+            );
+            if !selected.is_empty() {
+                start_button = start_button.on_press(Message::Delegate(DelegateMessage::Start));
+            }
+            col = col.push(col_vaults).push(start_button);
+        }
+
+        let col = Column::new()
+            .push(
+                Row::new().push(Column::new().width(Length::Fill)).push(
+                    Container::new(
+                        button::cancel(
+                            &mut self.close_button,
+                            Container::new(text::simple("X Close")).padding(10),
+                        )
+                        .on_press(Message::Menu(Menu::Home)),
+                    )
+                    .width(Length::Shrink),
+                ),
+            )
+            .push(
+                Container::new(col.max_width(1000))
+                    .width(Length::Fill)
+                    .align_x(Align::Center),
+            )
+            .spacing(50);
+        Container::new(Scrollable::new(&mut self.scroll).push(col))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(ContainerBackgroundStyle)
+            .padding(20)
+            .into()
+    }
+}
+
+#[derive(Debug)]
+pub struct RevocationBatchView {
+    path_input: text_input::State,
+    export_button: iced::button::State,
+    import_button: iced::button::State,
+    sign_button: iced::button::State,
+    submit_button: iced::button::State,
+    close_button: iced::button::State,
+}
+
+impl RevocationBatchView {
+    pub fn new() -> Self {
+        RevocationBatchView {
+            path_input: text_input::State::new(),
+            export_button: iced::button::State::new(),
+            import_button: iced::button::State::new(),
+            sign_button: iced::button::State::new(),
+            submit_button: iced::button::State::new(),
+            close_button: iced::button::State::new(),
+        }
+    }
+
+    /// Each item is a vault with its status and whether the status is a success,
+    /// a failure or none of them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        items: Vec<(&Vault, String, Option<bool>)>,
+        submitted: usize,
+        path: &str,
+        exported: Option<&PathBuf>,
+        software_signer: bool,
+        software_signer_error: Option<&String>,
+        warning: Option<&String>,
+        import_report: Option<&(usize, Vec<String>)>,
+    ) -> Element<'a, BatchMessage> {
+        let total = items.len();
+        let failures = items.iter().filter(|(_, _, ok)| *ok == Some(false)).count();
+        let mut col = Column::new()
+            .spacing(20)
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        Container::new(text::bold(text::simple(&format!(
+                            "Revocation transactions of {} deposits",
+                            total
+                        ))))
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        button::cancel(
+                            &mut self.close_button,
+                            Container::new(text::simple("Leave batch mode")).padding(10),
+                        )
+                        .on_press(BatchMessage::Close),
+                    ),
+            )
+            .push(text::simple(&format!(
+                "{}/{} deposits secured, {} failures",
+                submitted, total, failures
+            )));
+
+        let mut col_items = Column::new().spacing(10);
+        for (vault, status, ok) in items {
+            let status = match ok {
+                Some(true) => text::success(text::simple(&status)),
+                Some(false) => text::danger(text::simple(&status)),
+                None => text::simple(&status),
+            };
+            col_items = col_items.push(
+                Row::new()
+                    .spacing(20)
+                    .align_items(Align::Center)
+                    .push(Container::new(text::small(&vault.address)).width(Length::Fill))
+                    .push(text::small(&format!(
+                        "{} {}",
+                        ctx.converter.converts(vault.amount),
+                        ctx.converter.unit
+                    )))
+                    .push(Container::new(status).width(Length::Units(200))),
+            );
+        }
+        col = col.push(col_items).push(separation().width(Length::Fill));
+
+        col = col
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        Container::new(text::simple(
+                            "Export the transactions to sign on your device:",
+                        ))
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        button::cancel(
+                            &mut self.export_button,
+                            Container::new(text::simple("Export")).padding(10),
+                        )
+                        .on_press(BatchMessage::Export),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        TextInput::new(
+                            &mut self.path_input,
+                            "Directory of the signed transactions",
+                            path,
+                            BatchMessage::PathEdited,
+                        )
+                        .padding(10),
+                    )
+                    .push(
+                        button::cancel(
+                            &mut self.import_button,
+                            Container::new(text::simple("Import")).padding(10),
+                        )
+                        .on_press(BatchMessage::Import),
+                    ),
+            );
+        if let Some(path) = exported {
+            col = col.push(text::small(&format!(
+                "Transactions exported to {}",
+                path.to_string_lossy()
+            )));
+        }
+
+        if let Some(e) = software_signer_error {
+            col = col.push(card::alert_warning(Container::new(text::simple(&format!(
+                "The software signer key cannot be used: {}",
+                e
+            )))));
+        }
+        if software_signer {
+            col = col.push(
+                button::cancel(
+                    &mut self.sign_button,
+                    Container::new(text::simple("Sign with the configured key")).padding(10),
+                )
+                .on_press(BatchMessage::SignWithKey),
+            );
+        }
+
+        if let Some((imported, rejected)) = import_report {
+            let mut report = Column::new().spacing(5).push(text::simple(&format!(
+                "{} signed transactions imported",
+                imported
+            )));
+            for reason in rejected {
+                report = report.push(text::danger(text::small(reason)));
+            }
+            col = col.push(report);
+        }
+
+        if let Some(message) = warning {
+            col = col.push(card::alert_warning(Container::new(text::simple(message))));
+        }
+
+        col = col.push(
+            button::primary(
+                &mut self.submit_button,
+                Container::new(text::simple("Submit signed deposits")).padding(10),
+            )
+            .on_press(BatchMessage::Submit),
+        );
+
+        card::white(Container::new(col)).into()
+    }
+}
+
 #[derive(Debug)]
 pub struct StakeholderACKDepositView {
     retry_button: iced::button::State,
+    skip_button: iced::button::State,
 }
 
 impl StakeholderACKDepositView {
     pub fn new() -> Self {
         StakeholderACKDepositView {
             retry_button: iced::button::State::default(),
+            skip_button: iced::button::State::default(),
         }
     }
 
+    /// failed displays a deposit whose revocation transactions could not be retrieved.
+    pub fn failed<'a>(
+        &'a mut self,
+        ctx: &Context,
+        deposit: &Vault,
+        error: &str,
+    ) -> Element<'a, DepositMessage> {
+        card::white(Container::new(
+            Column::new()
+                .spacing(20)
+                .push(deposit_header(ctx, deposit))
+                .push(card::alert_warning(Container::new(text::simple(&format!(
+                    "Failed to retrieve the revocation transactions: {}",
+                    error
+                )))))
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .push(
+                            button::primary(
+                                &mut self.retry_button,
+                                button::button_content(None, "Retry"),
+                            )
+                            .on_press(DepositMessage::Retry),
+                        )
+                        .push(
+                            button::cancel(
+                                &mut self.skip_button,
+                                button::button_content(None, "Skip"),
+                            )
+                            .on_press(DepositMessage::Skip),
+                        ),
+                ),
+        ))
+        .into()
+    }
+
+    /// skipped displays a deposit left for later, its signature can be resumed.
+    pub fn skipped<'a>(
+        &'a mut self,
+        ctx: &Context,
+        deposit: &Vault,
+    ) -> Element<'a, DepositMessage> {
+        card::white(Container::new(
+            Row::new()
+                .spacing(20)
+                .align_items(Align::Center)
+                .push(Container::new(deposit_header(ctx, deposit)).width(Length::Fill))
+                .push(
+                    button::primary(
+                        &mut self.retry_button,
+                        button::button_content(None, "Resume"),
+                    )
+                    .on_press(DepositMessage::Resume),
+                ),
+        ))
+        .into()
+    }
+
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
@@ -247,36 +722,7 @@ impl StakeholderACKDepositView {
         };
 
         let mut col = Column::new()
-            .push(Container::new(
-                Row::new()
-                    .push(
-                        Container::new(
-                            Row::new()
-                                .push(badge::shield())
-                                .push(
-                                    Container::new(text::bold(text::small(&deposit.address)))
-                                        .align_y(Align::Center),
-                                )
-                                .spacing(20)
-                                .align_items(Align::Center),
-                        )
-                        .width(Length::Fill),
-                    )
-                    .push(
-                        Container::new(
-                            Row::new()
-                                .push(text::bold(text::simple(&format!(
-                                    "{}",
-                                    ctx.converter.converts(deposit.amount)
-                                ))))
-                                .push(text::small(&format!(" {}", ctx.converter.unit)))
-                                .align_items(Align::Center),
-                        )
-                        .width(Length::Shrink),
-                    )
-                    .spacing(20)
-                    .align_items(Align::Center),
-            ))
+            .push(deposit_header(ctx, deposit))
             .push(separation().width(Length::Fill))
             .push(row_transactions.spacing(10))
             .push(signer)
@@ -301,6 +747,18 @@ impl StakeholderACKDepositView {
             )))
         }
 
+        col = col.push(
+            Container::new(
+                button::cancel(
+                    &mut self.skip_button,
+                    button::button_content(None, "Sign later"),
+                )
+                .on_press(DepositMessage::Skip),
+            )
+            .width(Length::Fill)
+            .align_x(Align::End),
+        );
+
         card::white(Container::new(col)).into()
     }
 }
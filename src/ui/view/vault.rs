@@ -10,6 +10,52 @@ use crate::ui::{
 
 use crate::revaultd::model::{BroadcastedTransaction, Vault, VaultStatus, VaultTransactions};
 
+/// VaultListPagination moves between the pages of a vault list.
+#[derive(Debug)]
+pub struct VaultListPagination {
+    previous_button: iced::button::State,
+    next_button: iced::button::State,
+}
+
+impl VaultListPagination {
+    pub fn new() -> Self {
+        VaultListPagination {
+            previous_button: iced::button::State::new(),
+            next_button: iced::button::State::new(),
+        }
+    }
+
+    pub fn view<'a>(&'a mut self, page: usize, pages: usize) -> Container<'a, Message> {
+        if pages <= 1 {
+            return Container::new(Column::new());
+        }
+        let mut previous = button::transparent(
+            &mut self.previous_button,
+            Container::new(text::small("< Previous")).padding(5),
+        );
+        if page > 0 {
+            previous = previous.on_press(Message::VaultsPage(page - 1));
+        }
+        let mut next = button::transparent(
+            &mut self.next_button,
+            Container::new(text::small("Next >")).padding(5),
+        );
+        if page + 1 < pages {
+            next = next.on_press(Message::VaultsPage(page + 1));
+        }
+        Container::new(
+            Row::new()
+                .push(previous)
+                .push(text::small(&format!("Page {} of {}", page + 1, pages)))
+                .push(next)
+                .spacing(20)
+                .align_items(Align::Center),
+        )
+        .width(Length::Fill)
+        .center_x()
+    }
+}
+
 #[derive(Debug)]
 pub struct VaultModal {
     copy_button: iced::button::State,
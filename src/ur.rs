@@ -0,0 +1,479 @@
+//! Uniform Resources (BC-UR) encoding of PSBTs, used to transfer them
+//! with animated QR codes to and from air-gapped signing devices.
+//!
+//! Only the pure fragments of the multi-part encoding are produced and
+//! decoded: the encoder cycles over them and the decoder rejects the
+//! fountain-mixed ones as unsupported, the pure ones are enough for a
+//! camera scanning a loop.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+const UR_TYPE: &str = "crypto-psbt";
+
+/// Minimal length of a multi-part fragment in bytes.
+const MIN_FRAGMENT_LEN: usize = 10;
+
+/// The 256 bytewords, the minimal encoding of a byte is the first and the
+/// last letters of its word.
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    NotUR,
+    WrongType(String),
+    InvalidBytewords,
+    InvalidChecksum,
+    InvalidCbor,
+    /// The part does not belong to the same message than the previous ones.
+    InconsistentPart,
+    /// The part is a mix of fragments made by the fountain encoder.
+    UnsupportedMixedPart,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::NotUR => write!(f, "QR code is not an UR"),
+            Self::WrongType(t) => write!(f, "UR type is {}, expected {}", t, UR_TYPE),
+            Self::InvalidBytewords => write!(f, "UR has invalid bytewords"),
+            Self::InvalidChecksum => write!(f, "UR has an invalid checksum"),
+            Self::InvalidCbor => write!(f, "UR has an invalid payload"),
+            Self::InconsistentPart => write!(f, "UR part belongs to another PSBT"),
+            Self::UnsupportedMixedPart => write!(
+                f,
+                "UR part is mixed with the fountain code, which is not supported"
+            ),
+        }
+    }
+}
+
+/// encode returns the UR parts of the serialized psbt, each fragment is at most
+/// max_fragment_len bytes long. A single part is returned if the psbt fits in it.
+pub fn encode(psbt: &[u8], max_fragment_len: usize) -> Vec<String> {
+    let mut message = Vec::new();
+    cbor_head(&mut message, 2, psbt.len() as u64);
+    message.extend_from_slice(psbt);
+
+    if message.len() <= max_fragment_len {
+        return vec![format!("ur:{}/{}", UR_TYPE, bytewords_encode(&message))];
+    }
+
+    let fragment_len = fragment_len(message.len(), max_fragment_len);
+    let seq_len = (message.len() + fragment_len - 1) / fragment_len;
+    let checksum = crc32(&message);
+    let mut parts = Vec::with_capacity(seq_len);
+    for (i, chunk) in message.chunks(fragment_len).enumerate() {
+        let mut fragment = chunk.to_vec();
+        fragment.resize(fragment_len, 0);
+        parts.push(encode_part(
+            i + 1,
+            seq_len,
+            message.len(),
+            checksum,
+            &fragment,
+        ));
+    }
+    parts
+}
+
+/// encode_part returns the UR of the multi-part fragment.
+fn encode_part(
+    seq_num: usize,
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    fragment: &[u8],
+) -> String {
+    let mut part = Vec::new();
+    cbor_head(&mut part, 4, 5);
+    cbor_head(&mut part, 0, seq_num as u64);
+    cbor_head(&mut part, 0, seq_len as u64);
+    cbor_head(&mut part, 0, message_len as u64);
+    cbor_head(&mut part, 0, checksum as u64);
+    cbor_head(&mut part, 2, fragment.len() as u64);
+    part.extend_from_slice(fragment);
+    format!(
+        "ur:{}/{}-{}/{}",
+        UR_TYPE,
+        seq_num,
+        seq_len,
+        bytewords_encode(&part)
+    )
+}
+
+/// Decoder collects the UR parts of a psbt until it is complete.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    fragments: BTreeMap<usize, Vec<u8>>,
+    result: Option<Vec<u8>>,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// result returns the serialized psbt once all the parts were received.
+    pub fn result(&self) -> Option<&Vec<u8>> {
+        self.result.as_ref()
+    }
+
+    /// progress returns the number of received parts and the total number of parts.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.fragments.len(), self.seq_len)
+    }
+
+    pub fn receive(&mut self, part: &str) -> Result<(), Error> {
+        let part = part.trim().to_lowercase();
+        let rest = part.strip_prefix("ur:").ok_or(Error::NotUR)?;
+        let mut components = rest.split('/');
+        let ur_type = components.next().ok_or(Error::NotUR)?;
+        if ur_type != UR_TYPE {
+            return Err(Error::WrongType(ur_type.to_string()));
+        }
+        let components: Vec<&str> = components.collect();
+        match components.as_slice() {
+            [payload] => {
+                let message = bytewords_decode(payload)?;
+                self.result = Some(cbor_bytes(&message)?.to_vec());
+                Ok(())
+            }
+            [_, payload] => self.receive_fragment(&bytewords_decode(payload)?),
+            _ => Err(Error::NotUR),
+        }
+    }
+
+    fn receive_fragment(&mut self, part: &[u8]) -> Result<(), Error> {
+        let (major, len, mut cursor) = cbor_read_head(part, 0)?;
+        if major != 4 || len != 5 {
+            return Err(Error::InvalidCbor);
+        }
+        let mut values = [0_u64; 4];
+        for value in values.iter_mut() {
+            let (major, v, next) = cbor_read_head(part, cursor)?;
+            if major != 0 {
+                return Err(Error::InvalidCbor);
+            }
+            *value = v;
+            cursor = next;
+        }
+        let fragment = cbor_bytes(&part[cursor..])?;
+        let (seq_num, seq_len, message_len, checksum) = (
+            values[0] as usize,
+            values[1] as usize,
+            values[2] as usize,
+            values[3] as u32,
+        );
+        if seq_len == 0 || seq_num == 0 {
+            return Err(Error::InvalidCbor);
+        }
+
+        if self.fragments.is_empty() {
+            self.seq_len = seq_len;
+            self.message_len = message_len;
+            self.checksum = checksum;
+        } else if self.seq_len != seq_len
+            || self.message_len != message_len
+            || self.checksum != checksum
+        {
+            return Err(Error::InconsistentPart);
+        }
+
+        if self.result.is_some() {
+            return Ok(());
+        }
+        // Fragments with a sequence number above the sequence length are
+        // mixed with the fountain code.
+        if seq_num > seq_len {
+            return Err(Error::UnsupportedMixedPart);
+        }
+        self.fragments.insert(seq_num, fragment.to_vec());
+
+        if self.fragments.len() == self.seq_len {
+            let mut message: Vec<u8> = self.fragments.values().flatten().cloned().collect();
+            message.truncate(self.message_len);
+            if crc32(&message) != self.checksum {
+                self.fragments.clear();
+                return Err(Error::InvalidChecksum);
+            }
+            self.result = Some(cbor_bytes(&message)?.to_vec());
+        }
+        Ok(())
+    }
+}
+
+/// fragment_len finds the smallest fragment length splitting the message
+/// into fragments of at most max_fragment_len bytes.
+fn fragment_len(message_len: usize, max_fragment_len: usize) -> usize {
+    let max_fragment_count = std::cmp::max(1, message_len / MIN_FRAGMENT_LEN);
+    let mut len = message_len;
+    for count in 1..=max_fragment_count {
+        len = (message_len + count - 1) / count;
+        if len <= max_fragment_len {
+            break;
+        }
+    }
+    len
+}
+
+fn bytewords_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity((data.len() + 4) * 2);
+    for byte in data.iter().chain(crc32(data).to_be_bytes().iter()) {
+        let word = BYTEWORDS[*byte as usize];
+        s.push_str(&word[0..1]);
+        s.push_str(&word[3..4]);
+    }
+    s
+}
+
+fn bytewords_decode(s: &str) -> Result<Vec<u8>, Error> {
+    if s.len() % 2 != 0 || s.len() < 8 || !s.is_ascii() {
+        return Err(Error::InvalidBytewords);
+    }
+    let mut data = Vec::with_capacity(s.len() / 2);
+    for pair in s.as_bytes().chunks(2) {
+        let byte = BYTEWORDS
+            .iter()
+            .position(|word| {
+                let word = word.as_bytes();
+                word[0] == pair[0] && word[3] == pair[1]
+            })
+            .ok_or(Error::InvalidBytewords)?;
+        data.push(byte as u8);
+    }
+    let checksum = data.split_off(data.len() - 4);
+    if crc32(&data).to_be_bytes()[..] != checksum[..] {
+        return Err(Error::InvalidChecksum);
+    }
+    Ok(data)
+}
+
+/// crc32 is the CRC-32/ISO-HDLC checksum used by the UR specification.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn cbor_head(buf: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        buf.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        buf.push(major | 24);
+        buf.push(value as u8);
+    } else if value <= u16::MAX as u64 {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u32::MAX as u64 {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// cbor_read_head returns the major type, the value of the head at the
+/// cursor position and the position following it.
+fn cbor_read_head(buf: &[u8], cursor: usize) -> Result<(u8, u64, usize), Error> {
+    let first = *buf.get(cursor).ok_or(Error::InvalidCbor)?;
+    let (major, info) = (first >> 5, first & 0x1f);
+    let len = match info {
+        0..=23 => return Ok((major, info as u64, cursor + 1)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return Err(Error::InvalidCbor),
+    };
+    let start = cursor.checked_add(1).ok_or(Error::InvalidCbor)?;
+    let end = start.checked_add(len).ok_or(Error::InvalidCbor)?;
+    let bytes = buf.get(start..end).ok_or(Error::InvalidCbor)?;
+    let value = bytes.iter().fold(0_u64, |v, b| (v << 8) | *b as u64);
+    Ok((major, value, end))
+}
+
+/// cbor_bytes returns the content of the byte string starting the buffer.
+fn cbor_bytes(buf: &[u8]) -> Result<&[u8], Error> {
+    let (major, len, cursor) = cbor_read_head(buf, 0)?;
+    if major != 2 {
+        return Err(Error::InvalidCbor);
+    }
+    let end = usize::try_from(len)
+        .ok()
+        .and_then(|len| cursor.checked_add(len))
+        .ok_or(Error::InvalidCbor)?;
+    buf.get(cursor..end).ok_or(Error::InvalidCbor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn encode_decode_single_part() {
+        let psbt = message(50);
+        let parts = encode(&psbt, 120);
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].starts_with("ur:crypto-psbt/"));
+
+        let mut decoder = Decoder::new();
+        decoder.receive(&parts[0].to_uppercase()).unwrap();
+        assert_eq!(decoder.result(), Some(&psbt));
+    }
+
+    #[test]
+    fn encode_decode_multi_part() {
+        let psbt = message(1000);
+        let parts = encode(&psbt, 120);
+        assert!(parts.len() > 1);
+
+        // Parts are received in any order and twice when the animation loops.
+        let mut decoder = Decoder::new();
+        for part in parts.iter().rev().chain(parts.iter()) {
+            decoder.receive(part).unwrap();
+        }
+        assert_eq!(decoder.progress(), (parts.len(), parts.len()));
+        assert_eq!(decoder.result(), Some(&psbt));
+    }
+
+    #[test]
+    fn decode_missing_part() {
+        let parts = encode(&message(1000), 120);
+        let mut decoder = Decoder::new();
+        for part in parts.iter().skip(1) {
+            decoder.receive(part).unwrap();
+        }
+        assert_eq!(decoder.result(), None);
+        assert_eq!(decoder.progress(), (parts.len() - 1, parts.len()));
+    }
+
+    #[test]
+    fn decode_malformed() {
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.receive("hello"), Err(Error::NotUR));
+        assert_eq!(
+            decoder.receive("ur:bytes/lpamchcfatttcyclehgsdphdhgehfghkkkdl"),
+            Err(Error::WrongType("bytes".to_string()))
+        );
+        assert_eq!(decoder.receive("ur:crypto-psbt/a/b/c"), Err(Error::NotUR));
+        assert_eq!(
+            decoder.receive("ur:crypto-psbt/abc"),
+            Err(Error::InvalidBytewords)
+        );
+        assert_eq!(
+            decoder.receive("ur:crypto-psbt/qqqqqqqqqq"),
+            Err(Error::InvalidBytewords)
+        );
+
+        // A corrupted byteword breaks the checksum.
+        let part = encode(&message(50), 120).remove(0);
+        let mut corrupted = part.clone().into_bytes();
+        let i = "ur:crypto-psbt/".len();
+        let replacement: &[u8] = if &part[i..i + 2] == "ae" {
+            b"ad"
+        } else {
+            b"ae"
+        };
+        corrupted[i..i + 2].copy_from_slice(replacement);
+        assert_eq!(
+            decoder.receive(std::str::from_utf8(&corrupted).unwrap()),
+            Err(Error::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn decode_inconsistent_parts() {
+        let first = encode(&message(1000), 120);
+        let second = encode(&message(1200), 120);
+        let mut decoder = Decoder::new();
+        decoder.receive(&first[0]).unwrap();
+        assert_eq!(decoder.receive(&second[1]), Err(Error::InconsistentPart));
+    }
+
+    #[test]
+    fn decode_mixed_part_unsupported() {
+        let parts = encode(&message(1000), 120);
+        let mut decoder = Decoder::new();
+        decoder.receive(&parts[0]).unwrap();
+        let (_, seq_len) = decoder.progress();
+        let mixed = encode_part(
+            seq_len + 1,
+            seq_len,
+            decoder.message_len,
+            decoder.checksum,
+            &decoder.fragments[&1],
+        );
+        assert_eq!(decoder.receive(&mixed), Err(Error::UnsupportedMixedPart));
+    }
+
+    #[test]
+    fn decode_fragment_zero_sequence() {
+        let mut decoder = Decoder::new();
+        let part = encode_part(0, 2, 20, 0, &[0; 10]);
+        assert_eq!(decoder.receive(&part), Err(Error::InvalidCbor));
+    }
+
+    #[test]
+    fn cbor_bytes_length_overflow() {
+        // Byte string announcing u64::MAX bytes.
+        let mut buf = vec![0x5b];
+        buf.extend_from_slice(&[0xff; 8]);
+        buf.extend_from_slice(&[0; 4]);
+        assert_eq!(cbor_bytes(&buf), Err(Error::InvalidCbor));
+        // Byte string longer than the buffer.
+        assert_eq!(cbor_bytes(&[0x45, 1, 2]), Err(Error::InvalidCbor));
+        // Not a byte string.
+        assert_eq!(cbor_bytes(&[0x01]), Err(Error::InvalidCbor));
+        assert_eq!(cbor_bytes(&[0x42, 1, 2]), Ok(&[1_u8, 2][..]));
+    }
+
+    #[test]
+    fn cbor_head_round_trip() {
+        for value in &[0, 23, 24, 255, 256, 65535, 65536, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            cbor_head(&mut buf, 0, *value);
+            assert_eq!(cbor_read_head(&buf, 0), Ok((0, *value, buf.len())));
+        }
+        assert_eq!(cbor_read_head(&[0x1b, 1, 2], 0), Err(Error::InvalidCbor));
+        assert_eq!(cbor_read_head(&[0x1c], 0), Err(Error::InvalidCbor));
+    }
+}